@@ -2,3 +2,4 @@ use solana_sdk::pubkey;
 use solana_sdk::pubkey::Pubkey;
 
 pub const PUBKEY_WSOL: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+pub const PUBKEY_USD1: Pubkey = pubkey!("USD1ttGY1N17NEEHLmELoaybftRBUSErhqYiQzvEmuB");
@@ -0,0 +1,69 @@
+use solana_sdk::{account::Account, clock::Epoch, pubkey::Pubkey};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::Mint,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MintInfo {
+    pub token_program: Pubkey,
+    pub transfer_fee_config: Option<TransferFeeConfig>,
+    // Epoch the transfer fee is resolved for
+    pub epoch: Epoch,
+}
+
+impl MintInfo {
+    pub fn new(token_program: Pubkey) -> Self {
+        Self {
+            token_program,
+            transfer_fee_config: None,
+            epoch: 0,
+        }
+    }
+
+    pub fn from_account(mint: &Pubkey, account: &Account, epoch: Epoch) -> anyhow::Result<Self> {
+        if account.owner == spl_token::ID {
+            return Ok(Self::new(spl_token::ID));
+        }
+
+        if account.owner != spl_token_2022::ID {
+            return Err(anyhow::anyhow!("Mint {} is not owned by a token program: {}", mint, account.owner));
+        }
+
+        let state = StateWithExtensions::<Mint>::unpack(&account.data)?;
+        Ok(Self {
+            token_program: spl_token_2022::ID,
+            transfer_fee_config: state.get_extension::<TransferFeeConfig>().ok().copied(),
+            epoch,
+        })
+    }
+
+    pub fn is_token_2022(&self) -> bool {
+        self.token_program == spl_token_2022::ID
+    }
+
+    // Transfer fee in effect at `self.epoch`
+    pub fn get_epoch_transfer_fee(&self) -> Option<&TransferFee> {
+        Some(self.transfer_fee_config.as_ref()?.get_epoch_fee(self.epoch))
+    }
+
+    // A scheduled fee change takes effect at a later epoch, so the fee has to be resolved again once it starts
+    pub fn has_pending_transfer_fee(&self) -> bool {
+        self.transfer_fee_config
+            .is_some_and(|config| u64::from(config.newer_transfer_fee.epoch) > self.epoch)
+    }
+
+    // Fee withheld by the Token-2022 transfer-fee extension when `amount` is transferred
+    pub fn get_transfer_fee(&self, amount: u64) -> u64 {
+        self.get_epoch_transfer_fee()
+            .and_then(|transfer_fee| transfer_fee.calculate_fee(amount))
+            .unwrap_or(0)
+    }
+
+    pub fn amount_after_transfer_fee(&self, amount: u64) -> u64 {
+        amount - self.get_transfer_fee(amount)
+    }
+}
@@ -1,5 +1,10 @@
 pub mod accounts;
+pub mod mint_info;
+pub mod risk_policy;
+pub mod signer;
+pub mod submission_policy;
 pub mod trading_client;
 pub mod trading_endpoint;
+pub mod wallet_set;
 
 pub use trading_client::*;
@@ -0,0 +1,261 @@
+use crate::instruction::builder::PriorityFee;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+pub const DEFAULT_RISK_WINDOW: Duration = Duration::from_secs(60);
+
+// Limits enforced before any transaction is signed, `None` meaning unlimited
+#[derive(Debug, Clone)]
+pub struct RiskPolicy {
+    pub max_sol_per_trade: Option<u64>,
+    pub max_sol_per_window: Option<u64>,
+    pub window: Duration,
+    pub max_slippage_basis_points: Option<u64>,
+    pub max_priority_fee_lamports: Option<u64>,
+    pub max_tip_lamports: Option<u64>,
+    pub allowed_mints: Option<HashSet<Pubkey>>,
+    pub denied_mints: HashSet<Pubkey>,
+}
+
+impl Default for RiskPolicy {
+    fn default() -> Self {
+        Self {
+            max_sol_per_trade: None,
+            max_sol_per_window: None,
+            window: DEFAULT_RISK_WINDOW,
+            max_slippage_basis_points: None,
+            max_priority_fee_lamports: None,
+            max_tip_lamports: None,
+            allowed_mints: None,
+            denied_mints: HashSet::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RiskRejection {
+    /// The kill switch is engaged, nothing gets signed
+    KillSwitch,
+    /// The mint is not in the allow list
+    MintNotAllowed(Pubkey),
+    /// The mint is in the deny list
+    MintDenied(Pubkey),
+    /// A single trade spends more SOL than allowed
+    TradeAmount {
+        sol_amount: u64,
+        max: u64,
+    },
+    /// The wallet would spend more SOL than allowed within the rolling window
+    WindowAmount {
+        wallet: Pubkey,
+        spent: u64,
+        sol_amount: u64,
+        max: u64,
+    },
+    Slippage {
+        slippage_basis_points: u64,
+        max: u64,
+    },
+    PriorityFee {
+        lamports: u64,
+        max: u64,
+    },
+    Tip {
+        lamports: u64,
+        max: u64,
+    },
+}
+
+impl fmt::Display for RiskRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sol = |lamports: &u64| *lamports as f64 / LAMPORTS_PER_SOL as f64;
+        match self {
+            RiskRejection::KillSwitch => write!(f, "Kill switch is engaged"),
+            RiskRejection::MintNotAllowed(mint) => write!(f, "Mint is not allowed: {}", mint),
+            RiskRejection::MintDenied(mint) => write!(f, "Mint is denied: {}", mint),
+            RiskRejection::TradeAmount { sol_amount, max } => write!(f, "Trade of {} SOL exceeds the limit of {} SOL", sol(sol_amount), sol(max)),
+            RiskRejection::WindowAmount {
+                wallet,
+                spent,
+                sol_amount,
+                max,
+            } => write!(
+                f,
+                "Trade of {} SOL by {} exceeds the window limit of {} SOL, {} SOL already spent",
+                sol(sol_amount),
+                wallet,
+                sol(max),
+                sol(spent)
+            ),
+            RiskRejection::Slippage { slippage_basis_points, max } => write!(f, "Slippage of {} bps exceeds the limit of {} bps", slippage_basis_points, max),
+            RiskRejection::PriorityFee { lamports, max } => write!(f, "Priority fee of {} lamports exceeds the limit of {} lamports", lamports, max),
+            RiskRejection::Tip { lamports, max } => write!(f, "Tip of {} lamports exceeds the limit of {} lamports", lamports, max),
+        }
+    }
+}
+
+impl std::error::Error for RiskRejection {}
+
+// Spending recorded by a passed trade check, handed back through `RiskGuard::settle` once the trade is broadcast or failed
+#[derive(Debug, Default)]
+pub struct RiskReservation {
+    entries: Vec<(Pubkey, Instant, u64)>,
+}
+
+// Enforces the `RiskPolicy` and tracks per-wallet spending, rejections are returned as `RiskRejection` inside `anyhow::Error`
+#[derive(Default)]
+pub struct RiskGuard {
+    policy: RwLock<RiskPolicy>,
+    killed: AtomicBool,
+    spent: Mutex<HashMap<Pubkey, VecDeque<(Instant, u64)>>>,
+}
+
+impl RiskGuard {
+    pub fn new(policy: RiskPolicy) -> Self {
+        Self {
+            policy: RwLock::new(policy),
+            ..Default::default()
+        }
+    }
+
+    pub fn policy(&self) -> RiskPolicy {
+        self.policy.read().unwrap().clone()
+    }
+
+    pub fn set_policy(&self, policy: RiskPolicy) {
+        *self.policy.write().unwrap() = policy;
+    }
+
+    pub fn kill(&self) {
+        self.killed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.killed.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+    }
+
+    // SOL spent by `wallet` within the current window
+    pub fn get_spent(&self, wallet: &Pubkey) -> u64 {
+        let window = self.policy.read().unwrap().window;
+        let mut spent = self.spent.lock().unwrap();
+        spent.get_mut(wallet).map(|entries| Self::prune(entries, window)).unwrap_or(0)
+    }
+
+    pub fn check_transaction(&self, fee: Option<PriorityFee>, tip: Option<u64>) -> Result<(), RiskRejection> {
+        if self.is_killed() {
+            return Err(RiskRejection::KillSwitch);
+        }
+
+        let policy = self.policy.read().unwrap();
+        if let (Some(fee), Some(max)) = (fee, policy.max_priority_fee_lamports) {
+            let lamports = ((fee.unit_price as u128 * fee.unit_limit as u128).div_ceil(1_000_000)) as u64;
+            if lamports > max {
+                return Err(RiskRejection::PriorityFee { lamports, max });
+            }
+        }
+        if let (Some(lamports), Some(max)) = (tip, policy.max_tip_lamports) {
+            if lamports > max {
+                return Err(RiskRejection::Tip { lamports, max });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn check_slippage(&self, slippage_basis_points: u64) -> Result<(), RiskRejection> {
+        match self.policy.read().unwrap().max_slippage_basis_points {
+            Some(max) if slippage_basis_points > max => Err(RiskRejection::Slippage { slippage_basis_points, max }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_trade(&self, wallet: &Pubkey, mint: &Pubkey, sol_amount: u64) -> Result<RiskReservation, RiskRejection> {
+        self.check_trades(mint, &[(*wallet, sol_amount)])
+    }
+
+    // Checks every (wallet, SOL spent) trade on `mint` and reserves the spending only if all of them pass
+    pub fn check_trades(&self, mint: &Pubkey, trades: &[(Pubkey, u64)]) -> Result<RiskReservation, RiskRejection> {
+        if self.is_killed() {
+            return Err(RiskRejection::KillSwitch);
+        }
+
+        let policy = self.policy.read().unwrap();
+        if policy.denied_mints.contains(mint) {
+            return Err(RiskRejection::MintDenied(*mint));
+        }
+        if policy.allowed_mints.as_ref().is_some_and(|allowed| !allowed.contains(mint)) {
+            return Err(RiskRejection::MintNotAllowed(*mint));
+        }
+
+        let mut spent = self.spent.lock().unwrap();
+        let mut pending = HashMap::<Pubkey, u64>::new();
+        for (wallet, sol_amount) in trades.iter().copied() {
+            if let Some(max) = policy.max_sol_per_trade {
+                if sol_amount > max {
+                    return Err(RiskRejection::TradeAmount { sol_amount, max });
+                }
+            }
+
+            if let Some(max) = policy.max_sol_per_window {
+                let wallet_spent =
+                    spent.get_mut(&wallet).map(|entries| Self::prune(entries, policy.window)).unwrap_or(0) + pending.get(&wallet).copied().unwrap_or(0);
+                if wallet_spent.saturating_add(sol_amount) > max {
+                    return Err(RiskRejection::WindowAmount {
+                        wallet,
+                        spent: wallet_spent,
+                        sol_amount,
+                        max,
+                    });
+                }
+            }
+            *pending.entry(wallet).or_default() += sol_amount;
+        }
+
+        let now = Instant::now();
+        let mut reservation = RiskReservation::default();
+        for (wallet, sol_amount) in pending.into_iter().filter(|(_, sol_amount)| *sol_amount > 0) {
+            spent.entry(wallet).or_default().push_back((now, sol_amount));
+            reservation.entries.push((wallet, now, sol_amount));
+        }
+
+        Ok(reservation)
+    }
+
+    // Keeps the reserved spending if `result` succeeded, releases it otherwise so failed trades do not count towards the window
+    pub fn settle<T>(&self, reservation: RiskReservation, result: anyhow::Result<T>) -> anyhow::Result<T> {
+        if result.is_err() {
+            self.release(reservation);
+        }
+        result
+    }
+
+    pub fn release(&self, reservation: RiskReservation) {
+        let mut spent = self.spent.lock().unwrap();
+        for (wallet, time, sol_amount) in reservation.entries {
+            if let Some(entries) = spent.get_mut(&wallet) {
+                if let Some(index) = entries.iter().position(|entry| *entry == (time, sol_amount)) {
+                    entries.remove(index);
+                }
+            }
+        }
+    }
+
+    fn prune(entries: &mut VecDeque<(Instant, u64)>, window: Duration) -> u64 {
+        while entries.front().is_some_and(|(time, _)| time.elapsed() > window) {
+            entries.pop_front();
+        }
+        entries.iter().map(|(_, sol_amount)| sol_amount).sum()
+    }
+}
@@ -0,0 +1,220 @@
+use base64::{engine::general_purpose, Engine};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::{Signer, SignerError},
+};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+pub const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Any signer usable from async trading code: keypairs, remote signers, hardware wallets...
+pub trait TxSigner: Signer + Send + Sync {}
+
+impl<T: Signer + Send + Sync + ?Sized> TxSigner for T {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteSignerTransport {
+    /// Plain HTTP/1.1 `POST` to `host:port` at `path`
+    Http { host: String, path: String },
+    /// One JSON request per line over a Unix domain socket
+    Unix(PathBuf),
+}
+
+// Signs through an external service holding the key.
+// Request: `{"pubkey": "<base58>", "message": "<base64>"}`, response: `{"signature": "<base58>"}` or `{"error": "..."}`.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    pub pubkey: Pubkey,
+    pub transport: RemoteSignerTransport,
+    pub timeout: Duration,
+}
+
+impl RemoteSigner {
+    pub fn new(pubkey: Pubkey, transport: RemoteSignerTransport) -> Self {
+        Self {
+            pubkey,
+            transport,
+            timeout: REMOTE_SIGNER_TIMEOUT,
+        }
+    }
+
+    // `url` in the form `http://host:port/path`, TLS is expected to be terminated by a local proxy
+    pub fn http(pubkey: Pubkey, url: &str) -> anyhow::Result<Self> {
+        let address = url
+            .strip_prefix("http://")
+            .ok_or_else(|| anyhow::anyhow!("Remote signer url must start with http://: {}", url))?;
+        let (host, path) = match address.find('/') {
+            Some(index) => (&address[..index], &address[index..]),
+            None => (address, "/"),
+        };
+        let host = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+
+        Ok(Self::new(pubkey, RemoteSignerTransport::Http { host, path: path.to_string() }))
+    }
+
+    pub fn unix(pubkey: Pubkey, path: impl Into<PathBuf>) -> Self {
+        Self::new(pubkey, RemoteSignerTransport::Unix(path.into()))
+    }
+
+    // `TcpStream::connect` waits for the OS timeout, so each resolved address is tried within `timeout` instead
+    fn connect(host: &str, timeout: Duration) -> anyhow::Result<TcpStream> {
+        let mut last_error = None;
+        for address in host.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.map_or_else(|| anyhow::anyhow!("Remote signer host not resolved: {}", host), Into::into))
+    }
+
+    fn request(&self, body: &str) -> anyhow::Result<String> {
+        match &self.transport {
+            RemoteSignerTransport::Http { host, path } => {
+                let mut stream = Self::connect(host, self.timeout)?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                write!(
+                    stream,
+                    "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    path,
+                    host,
+                    body.len(),
+                    body
+                )?;
+
+                let (status, body) = read_http_response(&mut BufReader::new(stream))?;
+                if !status.starts_with('2') {
+                    return Err(anyhow::anyhow!("Remote signer http status {}: {}", status, body));
+                }
+                Ok(body)
+            }
+            #[cfg(unix)]
+            RemoteSignerTransport::Unix(path) => {
+                let mut stream = std::os::unix::net::UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(self.timeout))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                writeln!(stream, "{}", body)?;
+
+                let mut response = String::new();
+                BufReader::new(stream).read_line(&mut response)?;
+                Ok(response)
+            }
+            #[cfg(not(unix))]
+            RemoteSignerTransport::Unix(_) => Err(anyhow::anyhow!("Unix sockets are not supported on this platform")),
+        }
+    }
+
+    // Signs on the blocking thread pool, for async callers that should not wait on the remote signer
+    pub async fn sign_message_async(&self, message: &[u8]) -> anyhow::Result<Signature> {
+        let (signer, message) = (self.clone(), message.to_vec());
+        tokio::task::spawn_blocking(move || signer.sign(&message)).await?
+    }
+
+    fn sign(&self, message: &[u8]) -> anyhow::Result<Signature> {
+        let body = serde_json::json!({
+            "pubkey": self.pubkey.to_string(),
+            "message": general_purpose::STANDARD.encode(message),
+        });
+        let response = serde_json::Value::from_str(&self.request(&body.to_string())?)?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow::anyhow!("Remote signer error: {}", error));
+        }
+
+        let signature = response["signature"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid remote signer response: {}", response))?;
+        let signature = Signature::from_str(signature)?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(anyhow::anyhow!("Remote signer returned an invalid signature for {}", self.pubkey));
+        }
+
+        Ok(signature)
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    // Transactions are signed synchronously, so on a multi-threaded runtime the worker hands its other tasks off while it waits
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let result = match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => tokio::task::block_in_place(|| self.sign(message)),
+            _ => self.sign(message),
+        };
+        result.map_err(|e| SignerError::Connection(e.to_string()))
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+// Status code and body of an HTTP/1.1 response delimited by `Content-Length` or chunked transfer encoding
+fn read_http_response(reader: &mut impl BufRead) -> anyhow::Result<(String, String)> {
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .ok_or_else(|| anyhow::anyhow!("Malformed remote signer response: {}", status_line.trim_end()))?
+        .to_string();
+
+    let mut content_length = None;
+    let mut chunked = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Err(anyhow::anyhow!("Remote signer response ended in the headers"));
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.parse::<usize>()?);
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.to_ascii_lowercase().contains("chunked");
+            }
+        }
+    }
+
+    let body = if chunked {
+        let mut body = vec![];
+        loop {
+            let mut size = String::new();
+            reader.read_line(&mut size)?;
+            // Chunk extensions follow a `;`
+            let size = size.trim_end().split(';').next().unwrap_or_default();
+            let size = usize::from_str_radix(size.trim(), 16).map_err(|_| anyhow::anyhow!("Invalid chunk size in remote signer response: {}", size))?;
+            if size == 0 {
+                break;
+            }
+            let start = body.len();
+            body.resize(start + size, 0);
+            reader.read_exact(&mut body[start..])?;
+            let mut crlf = [0; 2];
+            reader.read_exact(&mut crlf)?;
+        }
+        body
+    } else {
+        let content_length = content_length.ok_or_else(|| anyhow::anyhow!("Remote signer response has neither Content-Length nor chunked encoding"))?;
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        body
+    };
+
+    Ok((status, String::from_utf8(body)?))
+}
@@ -0,0 +1,114 @@
+use crate::{instruction::builder::PriorityFee, swqos::swqos_rpc::SWQoSHttpError};
+use rand::Rng;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use std::{future::Future, time::Duration};
+
+pub const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+pub const DEFAULT_RETRY_MAX_DELAY: Duration = Duration::from_secs(2);
+pub const DEFAULT_REBROADCAST_TIMEOUT: Duration = Duration::from_secs(60);
+pub const SUBMISSION_STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// How signed transactions are resubmitted. The default sends once, without retry or rebroadcast.
+#[derive(Debug, Clone)]
+pub struct SubmissionPolicy {
+    // Attempts per provider while it answers 5xx or 429, the first one included
+    pub max_attempts: u32,
+    // Retry delays are drawn uniformly up to `retry_base_delay * 2^retry`, capped at `retry_max_delay`
+    pub retry_base_delay: Duration,
+    pub retry_max_delay: Duration,
+    // Resends the same signed transactions every interval until one is seen on chain or the blockhash expires
+    pub rebroadcast_interval: Option<Duration>,
+    // Upper bound of the rebroadcast, the only one for durable nonce transactions
+    pub rebroadcast_timeout: Duration,
+    // Used by `TradingEndpoint::submit_with_escalation`
+    pub escalation: Option<FeeEscalation>,
+}
+
+impl Default for SubmissionPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            retry_base_delay: DEFAULT_RETRY_BASE_DELAY,
+            retry_max_delay: DEFAULT_RETRY_MAX_DELAY,
+            rebroadcast_interval: None,
+            rebroadcast_timeout: DEFAULT_REBROADCAST_TIMEOUT,
+            escalation: None,
+        }
+    }
+}
+
+// Re-signs an unconfirmed transaction with a fresh blockhash and higher fees, up to the ceilings
+#[derive(Debug, Clone)]
+pub struct FeeEscalation {
+    pub max_attempts: u32,
+    // How long an attempt is given to confirm before it is re-signed
+    pub confirm_timeout: Duration,
+    // Percent added to the compute unit price and the tip at each attempt
+    pub bump_percent: u64,
+    pub max_priority_fee_lamports: u64,
+    pub max_tip_lamports: u64,
+}
+
+impl Default for FeeEscalation {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            confirm_timeout: Duration::from_secs(5),
+            bump_percent: 50,
+            max_priority_fee_lamports: LAMPORTS_PER_SOL / 100,
+            max_tip_lamports: LAMPORTS_PER_SOL / 100,
+        }
+    }
+}
+
+impl FeeEscalation {
+    // Fee and tip of the next attempt. Fees already above a ceiling are left as they are.
+    pub fn bump(&self, fee: Option<PriorityFee>, tip: Option<u64>) -> (Option<PriorityFee>, Option<u64>) {
+        let bumped = |amount: u64, max: u64| {
+            if amount >= max {
+                return amount;
+            }
+            (amount as u128 * (100 + self.bump_percent) as u128 / 100).clamp(amount as u128 + 1, max as u128) as u64
+        };
+
+        let fee = fee.map(|fee| {
+            let max_unit_price = (self.max_priority_fee_lamports as u128 * 1_000_000 / fee.unit_limit.max(1) as u128).min(u64::MAX as u128) as u64;
+            PriorityFee {
+                unit_limit: fee.unit_limit,
+                unit_price: bumped(fee.unit_price, max_unit_price),
+            }
+        });
+        let tip = tip.map(|tip| bumped(tip, self.max_tip_lamports));
+
+        (fee, tip)
+    }
+}
+
+impl SubmissionPolicy {
+    pub fn retry_delay(&self, retry: u32) -> Duration {
+        let max = self.retry_base_delay.saturating_mul(2u32.saturating_pow(retry)).min(self.retry_max_delay);
+        Duration::from_micros(rand::rng().random_range(0..=max.as_micros() as u64))
+    }
+
+    // Runs `send` again after a jittered backoff while it fails with a 5xx or 429 status, up to `max_attempts` times
+    pub async fn send_with_retry<T, F, Fut>(&self, mut send: F) -> anyhow::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            let result = send().await;
+            let retryable = match &result {
+                Err(err) => err.downcast_ref::<SWQoSHttpError>().is_some_and(|err| err.is_retryable()),
+                Ok(_) => false,
+            };
+            if !retryable || retry + 1 >= self.max_attempts {
+                return result;
+            }
+
+            tokio::time::sleep(self.retry_delay(retry)).await;
+            retry += 1;
+        }
+    }
+}
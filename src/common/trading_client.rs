@@ -1,10 +1,20 @@
-use super::trading_endpoint::TradingEndpoint;
+use super::{accounts::PUBKEY_WSOL, signer::TxSigner, trading_endpoint::TradingEndpoint};
 use crate::{
-    dex::{dex_traits::DexTrait, types::DexType},
+    dex::{
+        aggregator::Aggregator,
+        aggregator_types::DEFAULT_AGGREGATOR_API_URL,
+        amm_calc::{amm_split_buy, split_buy_numeric},
+        dex_traits::DexTrait,
+        types::{CreateATA, DexType, PoolInfo, TokenAmountType},
+    },
+    instruction::builder::PriorityFee,
     swqos::SWQoSType,
 };
 use solana_client::nonblocking::rpc_client::RpcClient;
-use std::{collections::HashMap, sync::Arc};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+pub const SPLIT_NUMERIC_STEPS: u64 = 100;
 
 pub struct TradingConfig {
     pub rpc_url: String,
@@ -16,20 +26,314 @@ pub struct TradingClient {
     pub dexs: HashMap<DexType, Arc<dyn DexTrait>>,
 }
 
+// No native venue of the mint is live, the only case `buy` and `sell` hand over to the aggregator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoLivePool(pub Pubkey);
+
+impl fmt::Display for NoLivePool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No live pool found for mint: {}", self.0)
+    }
+}
+
+impl std::error::Error for NoLivePool {}
+
+#[derive(Clone)]
+pub struct Route {
+    pub dex_type: DexType,
+    pub dex: Arc<dyn DexTrait>,
+    pub pool_info: PoolInfo,
+}
+
+impl Route {
+    // Only SOL-quoted routes have `sol_reserves` in lamports that can be compared, and are paid for with the SOL amounts
+    pub fn is_sol_quoted(&self, mint: &Pubkey) -> bool {
+        self.dex.get_quote_mint(mint, self.pool_info.extra_address.as_ref()) == PUBKEY_WSOL
+    }
+}
+
+// One venue of a split buy, `sol_amount` being the maximum cost after slippage
+#[derive(Debug, Clone)]
+pub struct SplitLeg {
+    pub dex_type: DexType,
+    pub pool: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitTrade {
+    pub legs: Vec<SplitLeg>,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RoutedTrade {
+    pub dex_type: DexType,
+    pub pool: Pubkey,
+    pub signatures: Vec<Signature>,
+}
+
+impl RoutedTrade {
+    fn aggregator(signatures: Vec<Signature>) -> Self {
+        Self {
+            dex_type: DexType::Aggregator,
+            pool: Pubkey::default(),
+            signatures,
+        }
+    }
+}
+
 impl TradingClient {
     pub fn new(config: TradingConfig) -> anyhow::Result<Self> {
         let rpc = Arc::new(RpcClient::new(config.rpc_url));
-        let swqos = config.swqos.into_iter().map(|swqos| swqos.instantiate(rpc.clone())).collect();
+        let swqos = config
+            .swqos
+            .into_iter()
+            .map(|swqos| swqos.instantiate(rpc.clone()))
+            .collect::<anyhow::Result<_>>()?;
         let endpoint = Arc::new(TradingEndpoint::new(rpc, swqos));
         let dexs = DexType::all().into_iter().map(|dex| (dex, dex.instantiate(endpoint.clone()))).collect();
 
         Ok(Self { endpoint, dexs })
     }
 
+    // Opts in to the external aggregator, which `buy` and `sell` fall back to when `mint` has no live native venue.
+    // Without `api_url` the public API is used.
+    pub fn with_aggregator(mut self, api_url: Option<&str>, api_key: Option<String>) -> Self {
+        let aggregator = Aggregator::with_api(self.endpoint.clone(), api_url.unwrap_or(DEFAULT_AGGREGATOR_API_URL), api_key);
+        self.dexs.insert(DexType::Aggregator, Arc::new(aggregator));
+        self
+    }
+
     pub async fn initialize(&self) -> anyhow::Result<()> {
-        for (_, dex) in &self.dexs {
+        for dex in self.dexs.values() {
             dex.initialize().await?;
         }
         Ok(())
     }
+
+    // Live pools of `mint` on the given native DEXes, probed in parallel
+    async fn get_routes(&self, mint: &Pubkey, dexs: Vec<(&DexType, &Arc<dyn DexTrait>)>) -> Vec<Route> {
+        let dexs = dexs.into_iter().filter(|(dex_type, _)| **dex_type != DexType::Aggregator);
+        let routes = futures::future::join_all(dexs.map(|(dex_type, dex)| async move {
+            dex.get_pool(mint).await.map(|pool_info| Route {
+                dex_type: *dex_type,
+                dex: dex.clone(),
+                pool_info,
+            })
+        }))
+        .await;
+
+        routes
+            .into_iter()
+            .filter_map(|route| route.ok())
+            .filter(|route| !route.pool_info.complete && route.pool_info.token_reserves > 0)
+            .collect()
+    }
+
+    // Finds the SOL-quoted venue `mint` currently trades on: a bonding curve that has not completed, else the deepest pool.
+    // DEXes whose pool lookup scans program accounts are only probed when no derivable pool is live.
+    // Fails with `NoLivePool`, on which `buy` and `sell` fall back to the aggregator when enabled.
+    pub async fn resolve(&self, mint: &Pubkey) -> anyhow::Result<Route> {
+        let (derived, scanned): (Vec<_>, Vec<_>) = self.dexs.iter().partition(|(_, dex)| !dex.get_pool_scans());
+
+        for dexs in [derived, scanned] {
+            let route = self
+                .get_routes(mint, dexs)
+                .await
+                .into_iter()
+                .filter(|route| route.is_sol_quoted(mint))
+                .max_by_key(|route| (route.dex_type.is_bonding_curve(), route.pool_info.sol_reserves));
+            if let Some(route) = route {
+                return Ok(route);
+            }
+        }
+
+        Err(NoLivePool(*mint).into())
+    }
+
+    pub async fn buy(
+        &self,
+        payer: &dyn TxSigner,
+        mint: &Pubkey,
+        sol_amount: u64,
+        slippage_basis_points: u64,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<RoutedTrade> {
+        self.endpoint.risk.check_slippage(slippage_basis_points)?;
+        let (route, blockhash, mint_info) = tokio::try_join!(
+            async { anyhow::Ok(self.resolve(mint).await) },
+            self.endpoint.get_latest_blockhash(),
+            self.endpoint.get_mint_info(mint)
+        )?;
+        let route = match (route, self.dexs.get(&DexType::Aggregator)) {
+            (Ok(route), _) => route,
+            (Err(e), Some(aggregator)) if e.is::<NoLivePool>() => {
+                let signatures = aggregator.buy(payer, mint, sol_amount, slippage_basis_points, fee, tip).await?;
+                return Ok(RoutedTrade::aggregator(signatures));
+            }
+            (Err(e), _) => return Err(e),
+        };
+
+        let mut pool_info = route.pool_info.clone();
+        let swap = route.dex.quote_buy_item(&mut pool_info, &mint_info, sol_amount, slippage_basis_points);
+        let signatures = route.dex.buy_immediately(
+            payer,
+            mint,
+            pool_info.extra_address.as_ref(),
+            swap.sol_amount,
+            swap.token_amount,
+            blockhash,
+            CreateATA::Idempotent,
+            fee,
+            tip,
+        )?;
+
+        Ok(RoutedTrade {
+            dex_type: route.dex_type,
+            pool: pool_info.pool,
+            signatures,
+        })
+    }
+
+    // Buys across every live SOL-quoted venue of `mint` in one transaction, splitting `sol_amount` to maximise the tokens received.
+    // `sol_amount` includes the buy fees each venue charges on top of the SOL reaching its pool.
+    pub async fn split_buy(
+        &self,
+        payer: &dyn TxSigner,
+        mint: &Pubkey,
+        sol_amount: u64,
+        slippage_basis_points: u64,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<SplitTrade> {
+        self.endpoint.risk.check_slippage(slippage_basis_points)?;
+        let (routes, blockhash, mint_info) = tokio::try_join!(
+            async { anyhow::Ok(self.get_routes(mint, self.dexs.iter().collect()).await) },
+            self.endpoint.get_latest_blockhash(),
+            self.endpoint.get_mint_info(mint)
+        )?;
+        let routes = routes.into_iter().filter(|route| route.is_sol_quoted(mint)).collect::<Vec<_>>();
+        if routes.is_empty() {
+            return Err(NoLivePool(*mint).into());
+        }
+
+        // A fee of f on top of the input prices a pool like one holding (1 + f) times its SOL reserves
+        let fees = routes
+            .iter()
+            .map(|route| route.dex.get_buy_fee_basis_points(&route.pool_info))
+            .collect::<Vec<_>>();
+        let sol_amounts = if routes.iter().all(|route| route.dex.is_constant_product()) {
+            let reserves = routes
+                .iter()
+                .zip(&fees)
+                .map(|(route, fee)| (Self::amount_with_fee(route.pool_info.sol_reserves, *fee), route.pool_info.token_reserves))
+                .collect::<Vec<_>>();
+            amm_split_buy(sol_amount, &reserves)
+        } else {
+            split_buy_numeric(sol_amount, routes.len(), SPLIT_NUMERIC_STEPS, |index, sol_amount| {
+                let route = &routes[index];
+                let sol_amount = Self::amount_without_fee(sol_amount, fees[index]);
+                route.dex.quote_buy(&route.pool_info, &mint_info, sol_amount).token_amount
+            })
+        };
+
+        let mut legs = vec![];
+        let mut instructions = vec![];
+        for ((route, sol_amount), fee) in routes.into_iter().zip(sol_amounts).zip(fees).filter(|((_, sol_amount), _)| *sol_amount > 0) {
+            let sol_amount = Self::amount_without_fee(sol_amount, fee);
+            let mut pool_info = route.pool_info;
+            let swap = route.dex.quote_buy_item(&mut pool_info, &mint_info, sol_amount, slippage_basis_points);
+            let extra_address = pool_info.extra_address.as_ref();
+            let create_ata = if legs.is_empty() { CreateATA::Idempotent } else { CreateATA::None };
+            let instruction = route.dex.build_buy_instruction(payer, mint, extra_address, swap)?;
+            instructions.extend(
+                route
+                    .dex
+                    .wrap_buy_instruction(payer, mint, extra_address, swap.sol_amount, instruction, create_ata)?,
+            );
+            legs.push(SplitLeg {
+                dex_type: route.dex_type,
+                pool: pool_info.pool,
+                sol_amount: swap.sol_amount,
+                token_amount: swap.token_amount,
+            });
+        }
+
+        let max_sol_amount = legs.iter().map(|leg| leg.sol_amount).sum();
+        let reservation = self.endpoint.risk.check_trade(&payer.pubkey(), mint, max_sol_amount)?;
+        let result = self.endpoint.build_and_broadcast_tx(payer, instructions, blockhash, fee, tip, None);
+        let signatures = self.endpoint.risk.settle(reservation, result)?;
+
+        Ok(SplitTrade { legs, signatures })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn sell(
+        &self,
+        payer: &dyn TxSigner,
+        mint: &Pubkey,
+        token_amount: TokenAmountType,
+        slippage_basis_points: u64,
+        close_mint_ata: bool,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<RoutedTrade> {
+        self.endpoint.risk.check_slippage(slippage_basis_points)?;
+        let payer_pubkey = payer.pubkey();
+        let mint_info = self.endpoint.get_mint_info(mint).await?;
+        let (route, blockhash, token_amount) = tokio::try_join!(
+            async { anyhow::Ok(self.resolve(mint).await) },
+            self.endpoint.get_latest_blockhash(),
+            token_amount.to_amount(self.endpoint.rpc.clone(), &payer_pubkey, mint, &mint_info.token_program)
+        )?;
+        let route = match (route, self.dexs.get(&DexType::Aggregator)) {
+            (Ok(route), _) => route,
+            (Err(e), Some(aggregator)) if e.is::<NoLivePool>() => {
+                let signatures = aggregator
+                    .sell(
+                        payer,
+                        mint,
+                        TokenAmountType::Amount(token_amount),
+                        slippage_basis_points,
+                        close_mint_ata,
+                        fee,
+                        tip,
+                    )
+                    .await?;
+                return Ok(RoutedTrade::aggregator(signatures));
+            }
+            (Err(e), _) => return Err(e),
+        };
+
+        let mut pool_info = route.pool_info.clone();
+        let swap = route.dex.quote_sell_item(&mut pool_info, &mint_info, token_amount, slippage_basis_points);
+        let signatures = route.dex.sell_immediately(
+            payer,
+            mint,
+            pool_info.extra_address.as_ref(),
+            swap.token_amount,
+            swap.sol_amount,
+            close_mint_ata,
+            blockhash,
+            fee,
+            tip,
+        )?;
+
+        Ok(RoutedTrade {
+            dex_type: route.dex_type,
+            pool: pool_info.pool,
+            signatures,
+        })
+    }
+
+    fn amount_with_fee(amount: u64, fee_basis_points: u64) -> u64 {
+        (amount as u128 * (10_000 + fee_basis_points) as u128 / 10_000) as u64
+    }
+
+    fn amount_without_fee(amount: u64, fee_basis_points: u64) -> u64 {
+        (amount as u128 * 10_000 / (10_000 + fee_basis_points) as u128) as u64
+    }
 }
@@ -1,48 +1,316 @@
+use super::{
+    accounts::PUBKEY_WSOL,
+    mint_info::MintInfo,
+    risk_policy::RiskGuard,
+    signer::TxSigner,
+    submission_policy::{SubmissionPolicy, SUBMISSION_STATUS_POLL_INTERVAL},
+};
 use crate::{
-    instruction::builder::{build_transaction, PriorityFee, TipFee},
-    swqos::SWQoSTrait,
+    instruction::builder::{build_transaction, build_transaction_with_tips, DurableNonce, PriorityFee, TipFee},
+    swqos::{
+        scoreboard::{ProviderScore, ProviderScoreboard, RoutingPolicy, LAND_ATTRIBUTION_TIMEOUT},
+        spawn_keep_alive,
+        swqos_rpc::EndpointLatency,
+        BundleStatus, SWQoSTrait, MAX_BUNDLE_TRANSACTIONS,
+    },
 };
+use solana_address_lookup_table_interface::state::AddressLookupTable;
 use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_nonce::{state::State as NonceState, versions::Versions as NonceVersions};
 use solana_sdk::{
-    hash::Hash,
-    instruction::Instruction,
-    signature::{Keypair, Signature},
+    commitment_config::CommitmentConfig, hash::Hash, instruction::Instruction, message::AddressLookupTableAccount, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status::TransactionStatus;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
 };
-use std::sync::Arc;
+
+pub const BUNDLE_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct TradingEndpoint {
     pub rpc: Arc<RpcClient>,
     pub swqos: Arc<Vec<Arc<dyn SWQoSTrait>>>,
+    pub mints: RwLock<HashMap<Pubkey, MintInfo>>,
+    pub risk: RiskGuard,
+    pub scoreboard: Arc<ProviderScoreboard>,
+    pub routing: RwLock<RoutingPolicy>,
+    pub broadcast: RwLock<BroadcastStrategy>,
+    pub submission: RwLock<SubmissionPolicy>,
+    nonce: RwLock<Option<DurableNonce>>,
 }
 
 pub struct BatchTxItem {
-    pub payer: Keypair,
+    pub payer: Arc<dyn TxSigner>,
     pub instructions: Vec<Instruction>,
+    pub other_signers: Vec<Arc<dyn TxSigner>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TipPlacement {
+    #[default]
+    LastTransaction,
+    // Appends a tip-only transaction paid by the payer of the last item
+    DedicatedTransaction,
+}
+
+// How a single transaction is fanned out to several tipped providers. Batches and bundles are always sent per provider,
+// so they are rejected under any other strategy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BroadcastStrategy {
+    /// One transaction per provider, each paying only its own tip. Each provider's copy is a distinct transaction,
+    /// so several of them can land and execute the trade more than once
+    #[default]
+    PerProvider,
+    /// One transaction paying the tip of every provider, sent to all of them. It executes at most once,
+    /// but every tip is paid when it lands, whichever provider included it
+    SingleTransaction,
+    /// One transaction per provider, each paying only its own tip, all advancing the same durable nonce so that
+    /// only the first to land executes. The nonce authority must be the payer, and one trade can be in flight per nonce account
+    SharedNonce(Pubkey),
+}
+
+#[derive(Clone)]
+pub struct BundleSubmission {
+    // Index in `TradingEndpoint::swqos`
+    pub provider: usize,
+    pub swqos: Arc<dyn SWQoSTrait>,
+    pub bundle_id: String,
+    pub signatures: Vec<Signature>,
 }
 
 impl TradingEndpoint {
     pub fn new(rpc: Arc<RpcClient>, swqos: Vec<Arc<dyn SWQoSTrait>>) -> Self {
-        Self { rpc, swqos: Arc::new(swqos) }
+        Self {
+            rpc,
+            scoreboard: Arc::new(ProviderScoreboard::new(swqos.len())),
+            swqos: Arc::new(swqos),
+            // The native mint is always SPL Token, so WSOL quotes never need to be resolved
+            mints: RwLock::new(HashMap::from([(PUBKEY_WSOL, MintInfo::new(spl_token::ID))])),
+            risk: RiskGuard::default(),
+            routing: RwLock::new(RoutingPolicy::default()),
+            broadcast: RwLock::new(BroadcastStrategy::default()),
+            submission: RwLock::new(SubmissionPolicy::default()),
+            nonce: RwLock::new(None),
+        }
+    }
+
+    pub fn set_broadcast_strategy(&self, strategy: BroadcastStrategy) {
+        *self.broadcast.write().unwrap() = strategy;
+        *self.nonce.write().unwrap() = None;
+    }
+
+    pub fn set_submission_policy(&self, policy: SubmissionPolicy) {
+        *self.submission.write().unwrap() = policy;
+    }
+
+    // Fetches the current nonce of `nonce_account` and caches it for `BroadcastStrategy::SharedNonce`
+    pub async fn refresh_nonce(&self, nonce_account: &Pubkey) -> anyhow::Result<DurableNonce> {
+        let account = self.rpc.get_account(nonce_account).await?;
+        let versions = bincode::deserialize::<NonceVersions>(&account.data)?;
+        let NonceState::Initialized(data) = versions.state() else {
+            return Err(anyhow::anyhow!("Nonce account not initialized: {}", nonce_account));
+        };
+
+        let nonce = DurableNonce {
+            nonce_account: *nonce_account,
+            authority: data.authority,
+            nonce: data.blockhash(),
+        };
+        *self.nonce.write().unwrap() = Some(nonce);
+
+        Ok(nonce)
+    }
+
+    fn get_cached_nonce(&self, nonce_account: &Pubkey) -> anyhow::Result<DurableNonce> {
+        self.nonce
+            .read()
+            .unwrap()
+            .filter(|nonce| nonce.nonce_account == *nonce_account)
+            .ok_or_else(|| anyhow::anyhow!("Nonce not loaded, call get_latest_blockhash or refresh_nonce first: {}", nonce_account))
+    }
+
+    pub fn set_routing_policy(&self, policy: RoutingPolicy) {
+        *self.routing.write().unwrap() = policy;
+    }
+
+    pub fn get_provider_scores(&self) -> Vec<ProviderScore> {
+        self.scoreboard.get_scores(&self.swqos)
     }
 
+    // Pings every SWQoS now and then every `interval`, so that trades after idle periods skip the TLS handshake
+    pub fn start_keep_alive(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        spawn_keep_alive(self.swqos.clone(), interval)
+    }
+
+    // (name, latency) of every SWQoS, `None` for clients that do not record it
+    pub fn get_endpoint_latencies(&self) -> Vec<(String, Option<EndpointLatency>)> {
+        self.swqos.iter().map(|swqos| (swqos.get_name().to_string(), swqos.get_latency())).collect()
+    }
+
+    // Indices of the SWQoS the current routing policy sends to
+    pub fn select_swqos(&self) -> anyhow::Result<Vec<usize>> {
+        let selected = self.scoreboard.select(&self.routing.read().unwrap(), &self.swqos);
+        if selected.is_empty() {
+            return Err(anyhow::anyhow!("No SWQoS selected by routing policy: {:?}", self.routing.read().unwrap()));
+        }
+        Ok(selected)
+    }
+
+    // Times one send to `provider` for the scoreboard, tracking `signatures` for land attribution when it succeeds
+    async fn record_send<T>(
+        scoreboard: Arc<ProviderScoreboard>,
+        provider: usize,
+        signatures: Vec<Signature>,
+        send: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        let started = Instant::now();
+        let result = send.await;
+        scoreboard.record_send(provider, started.elapsed(), result.is_ok());
+        if result.is_ok() {
+            for signature in signatures {
+                scoreboard.track(signature, provider);
+            }
+        }
+        result
+    }
+
+    // Resolves the tracked signatures against the chain, crediting the providers that were sent the landed ones.
+    // Signatures still unseen after `LAND_ATTRIBUTION_TIMEOUT` are counted as not landed. Returns the number of landed signatures.
+    pub async fn attribute_landings(&self) -> anyhow::Result<usize> {
+        let pending = self.scoreboard.get_pending();
+        let mut landed = 0;
+        for chunk in pending.chunks(256) {
+            let signatures = chunk.iter().map(|(signature, _)| *signature).collect::<Vec<_>>();
+            let statuses = self.rpc.get_signature_statuses(&signatures).await?.value;
+            for ((signature, sent_at), status) in chunk.iter().zip(statuses) {
+                if status.is_some() {
+                    self.scoreboard.record_landed(signature, true);
+                    landed += 1;
+                } else if sent_at.elapsed() >= LAND_ATTRIBUTION_TIMEOUT {
+                    self.scoreboard.record_landed(signature, false);
+                }
+            }
+        }
+
+        Ok(landed)
+    }
+
+    // Also refreshes the durable nonce under `BroadcastStrategy::SharedNonce`, as a landed trade advances it
     pub async fn get_latest_blockhash(&self) -> anyhow::Result<Hash> {
+        let strategy = *self.broadcast.read().unwrap();
+        if let BroadcastStrategy::SharedNonce(nonce_account) = strategy {
+            let (blockhash, _) = tokio::try_join!(async { Ok(self.rpc.get_latest_blockhash().await?) }, self.refresh_nonce(&nonce_account))?;
+            return Ok(blockhash);
+        }
+
         let blockhash = self.rpc.get_latest_blockhash().await?;
         Ok(blockhash)
     }
 
+    // Resolves the token program and transfer-fee settings of `mint` and caches them
+    pub async fn get_mint_info(&self, mint: &Pubkey) -> anyhow::Result<MintInfo> {
+        if let Some(mut mint_info) = self.get_cached_mint_info(mint) {
+            if mint_info.has_pending_transfer_fee() {
+                mint_info.epoch = self.rpc.get_epoch_info().await?.epoch;
+                self.set_mint_info(mint, mint_info);
+            }
+            return Ok(mint_info);
+        }
+
+        let (account, epoch_info) = tokio::try_join!(self.rpc.get_account(mint), self.rpc.get_epoch_info())?;
+        let mint_info = MintInfo::from_account(mint, &account, epoch_info.epoch)?;
+        self.set_mint_info(mint, mint_info);
+
+        Ok(mint_info)
+    }
+
+    pub fn get_cached_mint_info(&self, mint: &Pubkey) -> Option<MintInfo> {
+        self.mints.read().unwrap().get(mint).copied()
+    }
+
+    pub fn set_mint_info(&self, mint: &Pubkey, mint_info: MintInfo) {
+        self.mints.write().unwrap().insert(*mint, mint_info);
+    }
+
+    pub async fn get_lookup_tables(&self, addresses: &[Pubkey]) -> anyhow::Result<Vec<AddressLookupTableAccount>> {
+        if addresses.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let accounts = self.rpc.get_multiple_accounts(addresses).await?;
+        addresses
+            .iter()
+            .zip(accounts)
+            .map(|(key, account)| {
+                let account = account.ok_or_else(|| anyhow::anyhow!("Lookup table not found: {}", key))?;
+                let table = AddressLookupTable::deserialize(&account.data)?;
+                Ok(AddressLookupTableAccount {
+                    key: *key,
+                    addresses: table.addresses.to_vec(),
+                })
+            })
+            .collect()
+    }
+
+    pub async fn get_token_program(&self, mint: &Pubkey) -> anyhow::Result<Pubkey> {
+        Ok(self.get_mint_info(mint).await?.token_program)
+    }
+
+    // Cached token program of `mint`, resolved by `get_mint_info` or set by `set_token_program`
+    pub fn get_cached_token_program(&self, mint: &Pubkey) -> anyhow::Result<Pubkey> {
+        self.get_cached_mint_info(mint)
+            .map(|mint_info| mint_info.token_program)
+            .ok_or_else(|| anyhow::anyhow!("Token program of {} not loaded, call get_mint_info first", mint))
+    }
+
+    pub fn set_token_program(&self, mint: &Pubkey, token_program: &Pubkey) {
+        self.set_mint_info(mint, MintInfo::new(*token_program));
+    }
+
     pub fn build_and_broadcast_tx(
         &self,
-        payer: &Keypair,
+        payer: &dyn TxSigner,
         instructions: Vec<Instruction>,
         blockhash: Hash,
         fee: Option<PriorityFee>,
         tip: Option<u64>,
-        other_signers: Option<Vec<&Keypair>>,
+        other_signers: Option<Vec<&dyn TxSigner>>,
     ) -> anyhow::Result<Vec<Signature>> {
-        let mut signatures = vec![];
-        let mut txs = Vec::new();
+        self.build_and_broadcast_tx_with_lookup_tables(payer, instructions, blockhash, fee, tip, other_signers, &[])
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_and_broadcast_tx_with_lookup_tables(
+        &self,
+        payer: &dyn TxSigner,
+        instructions: Vec<Instruction>,
+        blockhash: Hash,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+        other_signers: Option<Vec<&dyn TxSigner>>,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> anyhow::Result<Vec<Signature>> {
+        self.risk.check_transaction(fee, tip)?;
 
-        for swqos in self.swqos.iter() {
+        let strategy = *self.broadcast.read().unwrap();
+        let nonce = match strategy {
+            BroadcastStrategy::SharedNonce(nonce_account) => {
+                let nonce = self.get_cached_nonce(&nonce_account)?;
+                if nonce.authority != payer.pubkey() {
+                    return Err(anyhow::anyhow!("Nonce authority is not the payer: {}", nonce.authority));
+                }
+                Some(nonce)
+            }
+            _ => None,
+        };
+
+        let mut providers = vec![];
+        for index in self.select_swqos()? {
+            let swqos = &self.swqos[index];
             let tip = if let Some(tip_account) = swqos.get_tip_account() {
                 if let Some(tip) = tip {
                     Some(TipFee {
@@ -52,63 +320,341 @@ impl TradingEndpoint {
                 } else {
                     // If no tip is provided, skip this Tip-SWQoS
                     eprintln!("No tip provided for SWQoS: {}", swqos.get_name());
-                    txs.push(None);
                     continue;
                 }
             } else {
                 None
             };
+            providers.push((index, tip));
+        }
+
+        let mut signatures = vec![];
+        let mut txs = Vec::new();
+        if strategy == BroadcastStrategy::SingleTransaction {
+            let tips = providers.iter().filter_map(|(_, tip)| *tip).collect::<Vec<_>>();
+            let total_tip = tips.iter().map(|tip| tip.tip_lamports).sum::<u64>();
+            self.risk.check_transaction(fee, Some(total_tip))?;
 
-            let tx = build_transaction(payer, instructions.clone(), blockhash, fee, tip, other_signers.clone())?;
+            let tx = build_transaction_with_tips(payer, instructions, blockhash, fee, &tips, other_signers, lookup_tables, None)?;
             signatures.push(tx.signatures[0]);
-            txs.push(Some(tx));
+            txs.extend(providers.iter().map(|(index, _)| (*index, tx.clone())));
+        } else {
+            for (index, tip) in providers {
+                let tx = build_transaction_with_tips(
+                    payer,
+                    instructions.clone(),
+                    blockhash,
+                    fee,
+                    tip.as_slice(),
+                    other_signers.clone(),
+                    lookup_tables,
+                    nonce.as_ref(),
+                )?;
+                signatures.push(tx.signatures[0]);
+                txs.push((index, tx));
+            }
         }
 
         let all_swqos = self.swqos.clone();
+        let scoreboard = self.scoreboard.clone();
+        let policy = self.submission.read().unwrap().clone();
+        let rpc = self.rpc.clone();
+        // Durable nonce transactions do not expire with their blockhash
+        let expiring_blockhash = nonce.is_none().then_some(blockhash);
         tokio::spawn(async move {
             let mut tasks = vec![];
-            for (swqos, tx) in all_swqos.iter().zip(txs.iter()) {
-                if let Some(tx) = tx {
-                    tasks.push(swqos.send_transaction(tx.clone()));
-                }
+            for (index, tx) in txs.iter() {
+                tasks.push(policy.send_with_retry(|| {
+                    Self::record_send(
+                        scoreboard.clone(),
+                        *index,
+                        vec![tx.signatures[0]],
+                        all_swqos[*index].send_transaction(tx.clone()),
+                    )
+                }));
             }
             let result = futures::future::join_all(tasks).await;
             let errors = result.into_iter().filter_map(|res| res.err()).collect::<Vec<_>>();
-            if errors.len() > 0 {
+            if !errors.is_empty() {
                 eprintln!("Errors occurred while sending transactions: {:?}", errors);
             }
+
+            if let Some(interval) = policy.rebroadcast_interval {
+                Self::rebroadcast(rpc, all_swqos, txs, expiring_blockhash, interval, policy.rebroadcast_timeout).await;
+            }
         });
 
         Ok(signatures)
     }
 
+    // Resends `txs` every `interval` until one of them is seen on chain, `blockhash` expires or `timeout` elapses.
+    // Rebroadcasts are not recorded on the scoreboard, lands are attributed to the first send.
+    async fn rebroadcast(
+        rpc: Arc<RpcClient>,
+        swqos: Arc<Vec<Arc<dyn SWQoSTrait>>>,
+        txs: Vec<(usize, VersionedTransaction)>,
+        blockhash: Option<Hash>,
+        interval: Duration,
+        timeout: Duration,
+    ) {
+        let started = Instant::now();
+        let mut signatures = txs.iter().map(|(_, tx)| tx.signatures[0]).collect::<Vec<_>>();
+        signatures.dedup();
+        loop {
+            tokio::time::sleep(interval).await;
+            if started.elapsed() >= timeout {
+                return;
+            }
+            if let Ok(statuses) = rpc.get_signature_statuses(&signatures).await {
+                if statuses.value.iter().any(|status| status.is_some()) {
+                    return;
+                }
+            }
+            if let Some(blockhash) = blockhash {
+                if let Ok(false) = rpc.is_blockhash_valid(&blockhash, CommitmentConfig::processed()).await {
+                    return;
+                }
+            }
+
+            futures::future::join_all(txs.iter().map(|(index, tx)| swqos[*index].send_transaction(tx.clone()))).await;
+        }
+    }
+
+    // Broadcasts the transaction and, while it is not confirmed within `FeeEscalation::confirm_timeout`, re-signs it with
+    // a fresh blockhash and bumped fees. An earlier attempt can still land until its blockhash expires, so the trade may
+    // execute more than once, except under `BroadcastStrategy::SharedNonce` where every attempt uses the same nonce.
+    // Returns the signature that landed.
+    pub async fn submit_with_escalation(
+        &self,
+        payer: &dyn TxSigner,
+        instructions: Vec<Instruction>,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+        other_signers: Option<Vec<&dyn TxSigner>>,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> anyhow::Result<Signature> {
+        let escalation = self
+            .submission
+            .read()
+            .unwrap()
+            .escalation
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No fee escalation in the submission policy"))?;
+
+        let (mut fee, mut tip) = (fee, tip);
+        let mut signatures = vec![];
+        for attempt in 0..escalation.max_attempts {
+            // Only the first attempt refreshes the durable nonce
+            let blockhash = if attempt == 0 {
+                self.get_latest_blockhash().await?
+            } else {
+                (fee, tip) = escalation.bump(fee, tip);
+                self.rpc.get_latest_blockhash().await?
+            };
+            signatures.extend(self.build_and_broadcast_tx_with_lookup_tables(
+                payer,
+                instructions.clone(),
+                blockhash,
+                fee,
+                tip,
+                other_signers.clone(),
+                lookup_tables,
+            )?);
+
+            let started = Instant::now();
+            while started.elapsed() < escalation.confirm_timeout {
+                tokio::time::sleep(SUBMISSION_STATUS_POLL_INTERVAL).await;
+                // A failed status lookup is retried at the next poll, as if nothing had confirmed yet
+                let Ok(statuses) = self.rpc.get_signature_statuses(&signatures).await else {
+                    continue;
+                };
+                if let Some(signature) = Self::get_confirmed_signature(&signatures, statuses.value)? {
+                    return Ok(signature);
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!("Transaction not confirmed after {} attempts", escalation.max_attempts))
+    }
+
+    // The first of `signatures` confirmed on chain, failing if it landed with an error
+    fn get_confirmed_signature(signatures: &[Signature], statuses: Vec<Option<TransactionStatus>>) -> anyhow::Result<Option<Signature>> {
+        for (signature, status) in signatures.iter().zip(statuses) {
+            let Some(status) = status.filter(|status| status.satisfies_commitment(CommitmentConfig::confirmed())) else {
+                continue;
+            };
+            if let Some(err) = status.err {
+                return Err(anyhow::anyhow!("Transaction failed: {} {}", signature, err));
+            }
+            return Ok(Some(*signature));
+        }
+
+        Ok(None)
+    }
+
     pub async fn build_and_broadcast_batch_txs(&self, items: Vec<BatchTxItem>, blockhash: Hash, fee: PriorityFee, tip: u64) -> anyhow::Result<Vec<Signature>> {
+        self.check_per_provider_strategy()?;
+        self.risk.check_transaction(Some(fee), Some(tip))?;
+        if items.len() > MAX_BUNDLE_TRANSACTIONS && self.swqos.iter().any(|swqos| swqos.supports_bundles()) {
+            return Err(anyhow::anyhow!("Batch exceeds the bundle limit of {} transactions", MAX_BUNDLE_TRANSACTIONS));
+        }
+
+        let policy = self.submission.read().unwrap().clone();
         let mut tasks = vec![];
         let mut signatures = vec![];
-        for swqos in self.swqos.iter() {
+        for index in self.select_swqos()? {
+            let swqos = &self.swqos[index];
             let tip_account = swqos
                 .get_tip_account()
                 .ok_or(anyhow::anyhow!("No tip account provided for SWQoS: {}", swqos.get_name()))?;
-            let mut tip = Some(TipFee {
+            let tip = TipFee {
                 tip_account,
                 tip_lamports: tip,
-            });
-
-            let txs = items
-                .iter()
-                .map(|item| build_transaction(&item.payer, item.instructions.clone(), blockhash, Some(fee), tip.take(), None))
-                .collect::<Result<Vec<_>, _>>()?;
+            };
 
-            signatures.extend(txs.iter().map(|tx| tx.signatures[0]));
-            tasks.push(swqos.send_transactions(txs));
+            let txs = Self::build_bundle_txs(&items, blockhash, Some(fee), tip, TipPlacement::LastTransaction)?;
+            let tx_signatures = txs.iter().map(|tx| tx.signatures[0]).collect::<Vec<_>>();
+            signatures.extend(tx_signatures.iter().copied());
+            let (scoreboard, policy) = (self.scoreboard.clone(), policy.clone());
+            tasks.push(async move {
+                policy
+                    .send_with_retry(|| Self::record_send(scoreboard.clone(), index, tx_signatures.clone(), swqos.send_transactions(txs.clone())))
+                    .await
+            });
         }
 
         let result = futures::future::join_all(tasks).await;
         let errors = result.into_iter().filter_map(|res| res.err()).collect::<Vec<_>>();
-        if errors.len() > 0 {
+        if !errors.is_empty() {
             return Err(anyhow::anyhow!("{:?}", errors));
         }
 
         Ok(signatures)
     }
+
+    // Sends the items as one atomic bundle to every bundle-capable SWQoS, returning one submission per accepted bundle
+    pub async fn build_and_broadcast_bundle(
+        &self,
+        items: Vec<BatchTxItem>,
+        blockhash: Hash,
+        fee: Option<PriorityFee>,
+        tip: u64,
+        tip_placement: TipPlacement,
+    ) -> anyhow::Result<Vec<BundleSubmission>> {
+        self.check_per_provider_strategy()?;
+        self.risk.check_transaction(fee, Some(tip))?;
+
+        let policy = self.submission.read().unwrap().clone();
+        let mut tasks = vec![];
+        for index in self.select_swqos()?.into_iter().filter(|index| self.swqos[*index].supports_bundles()) {
+            let swqos = &self.swqos[index];
+            let tip_account = swqos
+                .get_tip_account()
+                .ok_or(anyhow::anyhow!("No tip account provided for SWQoS: {}", swqos.get_name()))?;
+            let tip = TipFee {
+                tip_account,
+                tip_lamports: tip,
+            };
+
+            let txs = Self::build_bundle_txs(&items, blockhash, fee, tip, tip_placement)?;
+            let signatures = txs.iter().map(|tx| tx.signatures[0]).collect::<Vec<_>>();
+            let swqos = swqos.clone();
+            let (scoreboard, policy) = (self.scoreboard.clone(), policy.clone());
+            tasks.push(async move {
+                let bundle_id = policy
+                    .send_with_retry(|| Self::record_send(scoreboard.clone(), index, signatures.clone(), swqos.send_bundle(txs.clone())))
+                    .await?;
+                anyhow::Ok(BundleSubmission {
+                    provider: index,
+                    swqos,
+                    bundle_id,
+                    signatures,
+                })
+            });
+        }
+
+        if tasks.is_empty() {
+            return Err(anyhow::anyhow!("No bundle-capable SWQoS configured"));
+        }
+
+        let (submissions, errors): (Vec<_>, Vec<_>) = futures::future::join_all(tasks).await.into_iter().partition(|res| res.is_ok());
+        let submissions = submissions.into_iter().filter_map(|res| res.ok()).collect::<Vec<_>>();
+        let errors = errors.into_iter().filter_map(|res| res.err()).collect::<Vec<_>>();
+        if submissions.is_empty() {
+            return Err(anyhow::anyhow!("{:?}", errors));
+        }
+        if !errors.is_empty() {
+            eprintln!("Errors occurred while sending bundles: {:?}", errors);
+        }
+
+        Ok(submissions)
+    }
+
+    // Batches and bundles carry their own tip per provider and cannot share a nonce across several transactions
+    fn check_per_provider_strategy(&self) -> anyhow::Result<()> {
+        let strategy = *self.broadcast.read().unwrap();
+        if strategy != BroadcastStrategy::PerProvider {
+            return Err(anyhow::anyhow!("Broadcast strategy {:?} is not supported for batches and bundles", strategy));
+        }
+        Ok(())
+    }
+
+    pub fn build_bundle_txs(
+        items: &[BatchTxItem],
+        blockhash: Hash,
+        fee: Option<PriorityFee>,
+        tip: TipFee,
+        tip_placement: TipPlacement,
+    ) -> anyhow::Result<Vec<VersionedTransaction>> {
+        let last_payer = &items.last().ok_or(anyhow::anyhow!("Bundle has no transactions"))?.payer;
+        let last_index = items.len() - 1;
+        let mut txs = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let tip = (tip_placement == TipPlacement::LastTransaction && index == last_index).then_some(tip);
+                let other_signers = (!item.other_signers.is_empty()).then(|| item.other_signers.iter().map(|signer| signer.as_ref()).collect());
+                build_transaction(item.payer.as_ref(), item.instructions.clone(), blockhash, fee, tip, other_signers)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if tip_placement == TipPlacement::DedicatedTransaction {
+            txs.push(build_transaction(last_payer.as_ref(), vec![], blockhash, None, Some(tip), None)?);
+        }
+
+        if txs.len() > MAX_BUNDLE_TRANSACTIONS {
+            return Err(anyhow::anyhow!(
+                "Bundle exceeds the limit of {} transactions: {}",
+                MAX_BUNDLE_TRANSACTIONS,
+                txs.len()
+            ));
+        }
+
+        Ok(txs)
+    }
+
+    // Polls the bundle status until it lands, fails, or `timeout` elapses, in which case it is reported as dropped
+    pub async fn wait_for_bundle(&self, submission: &BundleSubmission, timeout: Duration) -> anyhow::Result<BundleStatus> {
+        let started = Instant::now();
+        loop {
+            let statuses = submission.swqos.get_bundle_statuses(std::slice::from_ref(&submission.bundle_id)).await?;
+            if let Some(status) = statuses.into_iter().next().filter(|status| status.is_final()) {
+                self.record_bundle_landed(submission, &status);
+                return Ok(status);
+            }
+
+            if started.elapsed() >= timeout {
+                self.record_bundle_landed(submission, &BundleStatus::Dropped);
+                return Ok(BundleStatus::Dropped);
+            }
+            tokio::time::sleep(BUNDLE_STATUS_POLL_INTERVAL).await;
+        }
+    }
+
+    fn record_bundle_landed(&self, submission: &BundleSubmission, status: &BundleStatus) {
+        let landed = matches!(status, BundleStatus::Landed { .. });
+        for signature in &submission.signatures {
+            self.scoreboard.record_landed(signature, landed);
+        }
+    }
 }
@@ -0,0 +1,360 @@
+use crate::{
+    common::signer::TxSigner,
+    dex::types::{BatchBuyParam, BatchSellParam, TokenAmountType},
+    instruction::builder::{build_transaction, PriorityFee},
+    swqos::{
+        default::{DefaultSWQoSClient, TransferInfo},
+        SWQoSTrait,
+    },
+};
+use rand::Rng;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_derivation_path::DerivationPath;
+use solana_keypair::seed_derivable::keypair_from_seed_and_derivation_path;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use spl_associated_token_account::{get_associated_token_address_with_program_id, instruction::create_associated_token_account_idempotent};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{instruction::harvest_withheld_tokens_to_mint, TransferFeeConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::{Account as TokenAccount, Mint},
+};
+use std::{path::Path, sync::Arc};
+
+pub const MAX_TRANSFERS_PER_TX: usize = 20;
+pub const MAX_SWEEPS_PER_TX: usize = 4;
+
+pub struct WalletSet {
+    pub wallets: Vec<Arc<dyn TxSigner>>,
+}
+
+// How an amount is spread across the wallets of a set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Allocation {
+    /// The same amount for every wallet
+    Each(u64),
+    /// A total split evenly, the remainder going to the first wallets
+    Split(u64),
+    /// A total split proportionally to one weight per wallet
+    Weighted(u64, Vec<u64>),
+    /// A uniformly random amount per wallet within the inclusive range
+    Random(u64, u64),
+    /// One explicit amount per wallet
+    Amounts(Vec<u64>),
+}
+
+// Outcome of one sweep transaction, the others are still sent when it fails
+#[derive(Debug)]
+pub struct SweepResult {
+    pub wallets: Vec<Pubkey>,
+    pub result: anyhow::Result<Signature>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalletBalance {
+    pub pubkey: Pubkey,
+    pub lamports: u64,
+    pub token_amount: u64,
+    pub has_token_account: bool,
+}
+
+impl Allocation {
+    pub fn amounts(&self, count: usize) -> anyhow::Result<Vec<u64>> {
+        match self {
+            Allocation::Each(amount) => Ok(vec![*amount; count]),
+            Allocation::Split(total) => {
+                if count == 0 {
+                    return Ok(vec![]);
+                }
+                let base = total / count as u64;
+                let remainder = (total % count as u64) as usize;
+                Ok((0..count).map(|index| base + (index < remainder) as u64).collect())
+            }
+            Allocation::Weighted(total, weights) => {
+                if weights.len() != count {
+                    return Err(anyhow::anyhow!("Expected {} weights, got {}", count, weights.len()));
+                }
+                let weight_sum = weights.iter().map(|weight| *weight as u128).sum::<u128>();
+                if weight_sum == 0 {
+                    return Err(anyhow::anyhow!("Weights sum to zero"));
+                }
+                Ok(weights.iter().map(|weight| (*total as u128 * *weight as u128 / weight_sum) as u64).collect())
+            }
+            Allocation::Random(min, max) => {
+                if min > max {
+                    return Err(anyhow::anyhow!("Invalid random range: {} > {}", min, max));
+                }
+                let mut rng = rand::rng();
+                Ok((0..count).map(|_| rng.random_range(*min..=*max)).collect())
+            }
+            Allocation::Amounts(amounts) => {
+                if amounts.len() != count {
+                    return Err(anyhow::anyhow!("Expected {} amounts, got {}", count, amounts.len()));
+                }
+                Ok(amounts.clone())
+            }
+        }
+    }
+}
+
+impl WalletSet {
+    pub fn new(wallets: Vec<Arc<dyn TxSigner>>) -> Self {
+        Self { wallets }
+    }
+
+    pub fn from_keypairs(keypairs: Vec<Keypair>) -> Self {
+        Self::new(keypairs.into_iter().map(|keypair| Arc::new(keypair) as Arc<dyn TxSigner>).collect())
+    }
+
+    // Every `*.json` keyfile of the directory, ordered by file name
+    pub fn from_dir(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let mut paths = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|path| path.extension().is_some_and(|extension| extension == "json"));
+        paths.sort();
+
+        let wallets = paths
+            .iter()
+            .map(|path| solana_keypair::read_keypair_file(path).map_err(|e| anyhow::anyhow!("Failed to read keyfile {}: {}", path.display(), e)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self::from_keypairs(wallets))
+    }
+
+    // A JSON file holding either one keypair as a byte array, or an array of keypairs as byte arrays or base58 strings
+    pub fn from_keyfile(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let value = serde_json::from_str::<serde_json::Value>(&content)?;
+        let entries = value.as_array().ok_or_else(|| anyhow::anyhow!("Keyfile must contain a JSON array"))?;
+
+        if entries.iter().all(|entry| entry.is_u64()) {
+            return Ok(Self::from_keypairs(vec![Self::parse_keypair(&value)?]));
+        }
+
+        let wallets = entries.iter().map(Self::parse_keypair).collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self::from_keypairs(wallets))
+    }
+
+    // Keypairs at the BIP44 paths m/44'/501'/{index}'/0' for `index` in `0..count`
+    pub fn from_seed(seed: &[u8], count: u32) -> anyhow::Result<Self> {
+        let wallets = (0..count)
+            .map(|index| {
+                keypair_from_seed_and_derivation_path(seed, Some(DerivationPath::new_bip44(Some(index), Some(0))))
+                    .map_err(|e| anyhow::anyhow!("Failed to derive wallet {}: {}", index, e))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Self::from_keypairs(wallets))
+    }
+
+    pub fn from_seed_phrase(seed_phrase: &str, passphrase: &str, count: u32) -> anyhow::Result<Self> {
+        let seed = solana_seed_phrase::generate_seed_from_seed_phrase_and_passphrase(seed_phrase, passphrase);
+        Self::from_seed(&seed, count)
+    }
+
+    fn parse_keypair(value: &serde_json::Value) -> anyhow::Result<Keypair> {
+        let bytes = match value {
+            serde_json::Value::String(encoded) => solana_sdk::bs58::decode(encoded).into_vec()?,
+            serde_json::Value::Array(bytes) => bytes
+                .iter()
+                .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| anyhow::anyhow!("Invalid keypair bytes"))?,
+            _ => return Err(anyhow::anyhow!("Invalid keypair entry: {}", value)),
+        };
+
+        Ok(Keypair::try_from(bytes.as_slice())?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        self.wallets.iter().map(|wallet| wallet.pubkey()).collect()
+    }
+
+    // SOL balance of every wallet, and its token balance for `mint` when given
+    pub async fn get_balances(&self, rpc: &RpcClient, mint: Option<(&Pubkey, &Pubkey)>) -> anyhow::Result<Vec<WalletBalance>> {
+        let pubkeys = self.pubkeys();
+        let mut keys = pubkeys.clone();
+        if let Some((mint, token_program)) = mint {
+            keys.extend(
+                pubkeys
+                    .iter()
+                    .map(|pubkey| get_associated_token_address_with_program_id(pubkey, mint, token_program)),
+            );
+        }
+
+        let mut accounts = vec![];
+        for chunk in keys.chunks(100) {
+            accounts.extend(rpc.get_multiple_accounts(chunk).await?);
+        }
+
+        let (wallet_accounts, token_accounts) = accounts.split_at(pubkeys.len());
+        pubkeys
+            .iter()
+            .enumerate()
+            .map(|(index, pubkey)| {
+                let token_account = token_accounts.get(index).and_then(|account| account.as_ref());
+                let token_amount = match token_account {
+                    Some(account) => StateWithExtensions::<TokenAccount>::unpack(&account.data)?.base.amount,
+                    None => 0,
+                };
+                Ok(WalletBalance {
+                    pubkey: *pubkey,
+                    lamports: wallet_accounts[index].as_ref().map(|account| account.lamports).unwrap_or_default(),
+                    token_amount,
+                    has_token_account: token_account.is_some(),
+                })
+            })
+            .collect()
+    }
+
+    pub async fn fund(
+        &self,
+        client: &DefaultSWQoSClient,
+        from: &dyn TxSigner,
+        allocation: &Allocation,
+        fee: Option<PriorityFee>,
+    ) -> anyhow::Result<Vec<Signature>> {
+        let amounts = allocation.amounts(self.len())?;
+        let transfers = self
+            .pubkeys()
+            .into_iter()
+            .zip(amounts)
+            .filter(|(_, amount)| *amount > 0)
+            .map(|(to, amount)| TransferInfo { to, amount })
+            .collect::<Vec<_>>();
+
+        let mut signatures = vec![];
+        let mut transfers = transfers.into_iter().peekable();
+        while transfers.peek().is_some() {
+            let chunk = transfers.by_ref().take(MAX_TRANSFERS_PER_TX).collect::<Vec<_>>();
+            signatures.push(client.batch_transfer(from, chunk, fee).await?);
+        }
+
+        Ok(signatures)
+    }
+
+    // Moves every token of `mint` and then all SOL back to `to`, closing the token accounts so their rent is reclaimed.
+    // Transfer fees withheld in the token accounts of a Token-2022 mint are harvested to the mint first, as accounts holding
+    // them cannot be closed. `to` pays the transaction fees so the wallets end up empty. Returns one result per transaction sent.
+    pub async fn sweep(
+        &self,
+        client: &DefaultSWQoSClient,
+        to: &dyn TxSigner,
+        mint: Option<&Pubkey>,
+        fee: Option<PriorityFee>,
+    ) -> anyhow::Result<Vec<SweepResult>> {
+        let rpc = client.rpc_client.clone();
+        let mint = match mint {
+            Some(mint) => {
+                let account = rpc.get_account(mint).await?;
+                let state = StateWithExtensions::<Mint>::unpack(&account.data)?;
+                let has_transfer_fee = state.get_extension::<TransferFeeConfig>().is_ok();
+                Some((*mint, account.owner, state.base.decimals, has_transfer_fee))
+            }
+            None => None,
+        };
+        let (balances, blockhash) = tokio::try_join!(
+            self.get_balances(&rpc, mint.as_ref().map(|(mint, token_program, _, _)| (mint, token_program))),
+            async { Ok(rpc.get_latest_blockhash().await?) },
+        )?;
+
+        let mut results = vec![];
+        for (wallets, balances) in self.wallets.chunks(MAX_SWEEPS_PER_TX).zip(balances.chunks(MAX_SWEEPS_PER_TX)) {
+            let mut instructions = vec![];
+            let mut signers = vec![];
+            for (wallet, balance) in wallets.iter().zip(balances) {
+                let first_instruction = instructions.len();
+                if let Some((mint, token_program, decimals, has_transfer_fee)) = &mint {
+                    let wallet_ata = get_associated_token_address_with_program_id(&wallet.pubkey(), mint, token_program);
+                    let to_ata = get_associated_token_address_with_program_id(&to.pubkey(), mint, token_program);
+                    if balance.token_amount > 0 {
+                        instructions.push(create_associated_token_account_idempotent(&to.pubkey(), &to.pubkey(), mint, token_program));
+                        instructions.push(spl_token_2022::instruction::transfer_checked(
+                            token_program,
+                            &wallet_ata,
+                            mint,
+                            &to_ata,
+                            &wallet.pubkey(),
+                            &[],
+                            balance.token_amount,
+                            *decimals,
+                        )?);
+                    }
+                    if balance.has_token_account && *has_transfer_fee {
+                        instructions.push(harvest_withheld_tokens_to_mint(token_program, mint, &[&wallet_ata])?);
+                    }
+                    if balance.has_token_account {
+                        instructions.push(spl_token_2022::instruction::close_account(
+                            token_program,
+                            &wallet_ata,
+                            &to.pubkey(),
+                            &wallet.pubkey(),
+                            &[],
+                        )?);
+                    }
+                }
+                if balance.lamports > 0 {
+                    instructions.push(solana_sdk::system_instruction::transfer(&wallet.pubkey(), &to.pubkey(), balance.lamports));
+                }
+                if instructions.len() > first_instruction {
+                    signers.push(wallet.as_ref());
+                }
+            }
+
+            if instructions.is_empty() {
+                continue;
+            }
+
+            let swept = signers.iter().map(|signer| signer.pubkey()).collect();
+            let result = match build_transaction(to, instructions, blockhash, fee, None, Some(signers)) {
+                Ok(transaction) => {
+                    let signature = transaction.signatures[0];
+                    client.send_transaction(transaction).await.map(|_| signature)
+                }
+                Err(err) => Err(err),
+            };
+            results.push(SweepResult { wallets: swept, result });
+        }
+
+        Ok(results)
+    }
+
+    pub fn batch_buy_params(&self, allocation: &Allocation) -> anyhow::Result<Vec<BatchBuyParam>> {
+        let amounts = allocation.amounts(self.len())?;
+        Ok(self
+            .wallets
+            .iter()
+            .zip(amounts)
+            .filter(|(_, sol_amount)| *sol_amount > 0)
+            .map(|(wallet, sol_amount)| BatchBuyParam {
+                payer: wallet.clone(),
+                sol_amount,
+            })
+            .collect())
+    }
+
+    pub fn batch_sell_params(&self, token_amount: TokenAmountType, close_mint_ata: bool) -> Vec<BatchSellParam> {
+        self.wallets
+            .iter()
+            .map(|wallet| BatchSellParam {
+                payer: wallet.clone(),
+                token_amount,
+                close_mint_ata,
+            })
+            .collect()
+    }
+}
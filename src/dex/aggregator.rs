@@ -0,0 +1,188 @@
+use super::{
+    aggregator_types::*,
+    dex_traits::DexTrait,
+    types::{Create, PoolInfo, SwapInfo, TokenAmountType},
+};
+use crate::{
+    common::{accounts::PUBKEY_WSOL, signer::TxSigner, trading_endpoint::TradingEndpoint},
+    instruction::builder::PriorityFee,
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signature};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::{sync::Arc, time::Duration};
+
+pub const AGGREGATOR_API_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Swaps through an external Jupiter-compatible API (`/quote` and `/swap-instructions`), covering venues without a native integration.
+// The returned instructions are signed and broadcast through our own pipeline, with our priority fee and tips.
+pub struct Aggregator {
+    pub endpoint: Arc<TradingEndpoint>,
+    pub api_url: String,
+    pub api_key: Option<String>,
+    pub client: reqwest::Client,
+}
+
+#[async_trait::async_trait]
+impl DexTrait for Aggregator {
+    async fn initialize(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn initialized(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn get_trading_endpoint(&self) -> Arc<TradingEndpoint> {
+        self.endpoint.clone()
+    }
+
+    fn use_wsol(&self) -> bool {
+        true
+    }
+
+    async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo> {
+        Err(anyhow::anyhow!("Aggregator routes have no single pool: {}", mint))
+    }
+
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+        Err(anyhow::anyhow!("Not supported"))
+    }
+
+    fn build_buy_instruction(&self, _: &dyn TxSigner, _: &Pubkey, _: Option<&Pubkey>, _: SwapInfo) -> anyhow::Result<Instruction> {
+        Err(anyhow::anyhow!("Aggregator swaps are built from a quote, use buy or sell"))
+    }
+
+    fn build_sell_instruction(&self, _: &dyn TxSigner, _: &Pubkey, _: Option<&Pubkey>, _: SwapInfo) -> anyhow::Result<Instruction> {
+        Err(anyhow::anyhow!("Aggregator swaps are built from a quote, use buy or sell"))
+    }
+
+    async fn buy(
+        &self,
+        payer: &dyn TxSigner,
+        mint: &Pubkey,
+        sol_amount: u64,
+        slippage_basis_points: u64,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<Vec<Signature>> {
+        self.endpoint.risk.check_slippage(slippage_basis_points)?;
+        let reservation = self.endpoint.risk.check_trade(&payer.pubkey(), mint, sol_amount)?;
+
+        let result = self.swap(payer, &PUBKEY_WSOL, mint, sol_amount, slippage_basis_points, vec![], fee, tip).await;
+        self.endpoint.risk.settle(reservation, result)
+    }
+
+    async fn sell(
+        &self,
+        payer: &dyn TxSigner,
+        mint: &Pubkey,
+        token_amount: TokenAmountType,
+        slippage_basis_points: u64,
+        close_mint_ata: bool,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<Vec<Signature>> {
+        self.endpoint.risk.check_slippage(slippage_basis_points)?;
+        self.endpoint.risk.check_trade(&payer.pubkey(), mint, 0)?;
+
+        let payer_pubkey = payer.pubkey();
+        let token_program = self.endpoint.get_token_program(mint).await?;
+        let token_amount = token_amount.to_amount(self.endpoint.rpc.clone(), &payer_pubkey, mint, &token_program).await?;
+
+        let mut post_instructions = vec![];
+        if close_mint_ata {
+            let mint_ata = get_associated_token_address_with_program_id(&payer_pubkey, mint, &token_program);
+            post_instructions.push(spl_token_2022::instruction::close_account(
+                &token_program,
+                &mint_ata,
+                &payer_pubkey,
+                &payer_pubkey,
+                &[&payer_pubkey],
+            )?);
+        }
+
+        self.swap(payer, mint, &PUBKEY_WSOL, token_amount, slippage_basis_points, post_instructions, fee, tip)
+            .await
+    }
+}
+
+impl Aggregator {
+    pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
+        Self::with_api(endpoint, DEFAULT_AGGREGATOR_API_URL, None)
+    }
+
+    // `api_url` is the base the `/quote` and `/swap-instructions` paths are appended to, `api_key` is sent as `x-api-key`
+    pub fn with_api(endpoint: Arc<TradingEndpoint>, api_url: &str, api_key: Option<String>) -> Self {
+        Self {
+            endpoint,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key,
+            client: reqwest::Client::builder().timeout(AGGREGATOR_API_TIMEOUT).build().unwrap(),
+        }
+    }
+
+    fn request(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_key {
+            Some(api_key) => request.header("x-api-key", api_key),
+            None => request,
+        }
+    }
+
+    async fn send(request: reqwest::RequestBuilder) -> anyhow::Result<serde_json::Value> {
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("Aggregator api error: {}: {}", status, body));
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    pub async fn quote(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount: u64, slippage_basis_points: u64) -> anyhow::Result<Quote> {
+        let request = self.client.get(format!("{}/quote", self.api_url)).query(&[
+            ("inputMint", input_mint.to_string()),
+            ("outputMint", output_mint.to_string()),
+            ("amount", amount.to_string()),
+            ("slippageBps", slippage_basis_points.to_string()),
+            ("swapMode", "ExactIn".to_string()),
+        ]);
+
+        Quote::from_value(Self::send(self.request(request)).await?)
+    }
+
+    pub async fn get_swap_instructions(&self, quote: &Quote, user: &Pubkey) -> anyhow::Result<SwapInstructionsResponse> {
+        let request = self.client.post(format!("{}/swap-instructions", self.api_url)).json(&serde_json::json!({
+            "quoteResponse": quote.raw,
+            "userPublicKey": user.to_string(),
+            "wrapAndUnwrapSol": true,
+        }));
+
+        Ok(serde_json::from_value(Self::send(self.request(request)).await?)?)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn swap(
+        &self,
+        payer: &dyn TxSigner,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+        slippage_basis_points: u64,
+        post_instructions: Vec<Instruction>,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<Vec<Signature>> {
+        let (quote, blockhash) = tokio::try_join!(
+            self.quote(input_mint, output_mint, amount, slippage_basis_points),
+            self.endpoint.get_latest_blockhash()
+        )?;
+        let swap = self.get_swap_instructions(&quote, &payer.pubkey()).await?;
+        let lookup_tables = self.endpoint.get_lookup_tables(&swap.lookup_table_addresses()?).await?;
+
+        let mut instructions = swap.to_instructions()?;
+        instructions.extend(post_instructions);
+
+        self.endpoint
+            .build_and_broadcast_tx_with_lookup_tables(payer, instructions, blockhash, fee, tip, None, &lookup_tables)
+    }
+}
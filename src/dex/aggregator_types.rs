@@ -0,0 +1,122 @@
+use base64::{engine::general_purpose, Engine};
+use serde::Deserialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use std::str::FromStr;
+
+pub const DEFAULT_AGGREGATOR_API_URL: &str = "https://lite-api.jup.ag/swap/v1";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteInfo {
+    pub in_amount: String,
+    pub out_amount: String,
+    pub other_amount_threshold: String,
+    #[serde(default)]
+    pub price_impact_pct: Option<String>,
+}
+
+// The raw quote is posted back unchanged to the swap-instructions endpoint
+#[derive(Debug, Clone)]
+pub struct Quote {
+    pub raw: serde_json::Value,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub other_amount_threshold: u64,
+}
+
+impl Quote {
+    pub fn from_value(raw: serde_json::Value) -> anyhow::Result<Self> {
+        let info = serde_json::from_value::<QuoteInfo>(raw.clone())?;
+        Ok(Self {
+            in_amount: info.in_amount.parse()?,
+            out_amount: info.out_amount.parse()?,
+            other_amount_threshold: info.other_amount_threshold.parse()?,
+            raw,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMetaInfo {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstructionInfo {
+    pub program_id: String,
+    pub accounts: Vec<AccountMetaInfo>,
+    // Base64
+    pub data: String,
+}
+
+impl InstructionInfo {
+    pub fn to_instruction(&self) -> anyhow::Result<Instruction> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|account| {
+                Ok(AccountMeta {
+                    pubkey: Pubkey::from_str(&account.pubkey)?,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(Instruction {
+            program_id: Pubkey::from_str(&self.program_id)?,
+            accounts,
+            data: general_purpose::STANDARD.decode(&self.data)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapInstructionsResponse {
+    #[serde(default)]
+    pub compute_budget_instructions: Vec<InstructionInfo>,
+    #[serde(default)]
+    pub setup_instructions: Vec<InstructionInfo>,
+    pub swap_instruction: InstructionInfo,
+    pub cleanup_instruction: Option<InstructionInfo>,
+    #[serde(default)]
+    pub other_instructions: Vec<InstructionInfo>,
+    #[serde(default)]
+    pub address_lookup_table_addresses: Vec<String>,
+}
+
+impl SwapInstructionsResponse {
+    // Setup, swap and cleanup, compute budget and tips are added by our own transaction builder.
+    // Other instructions (such as the API's own tips) are never requested, so a route needing them is rejected rather than sent without them.
+    pub fn to_instructions(&self) -> anyhow::Result<Vec<Instruction>> {
+        if !self.other_instructions.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Aggregator route needs {} unsupported other instructions",
+                self.other_instructions.len()
+            ));
+        }
+
+        self.setup_instructions
+            .iter()
+            .chain(std::iter::once(&self.swap_instruction))
+            .chain(self.cleanup_instruction.iter())
+            .map(|instruction| instruction.to_instruction())
+            .collect()
+    }
+
+    pub fn lookup_table_addresses(&self) -> anyhow::Result<Vec<Pubkey>> {
+        Ok(self
+            .address_lookup_table_addresses
+            .iter()
+            .map(|address| Pubkey::from_str(address))
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+}
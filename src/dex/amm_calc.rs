@@ -42,6 +42,106 @@ pub fn amm_sell_get_sol_out(sol_reserve: u64, token_reserve: u64, token_in: u64)
     sol_out as u64
 }
 
+pub fn amm_convert_amount(amount: u64, from_reserve: u64, to_reserve: u64) -> u64 {
+    if amount == 0 || from_reserve == 0 || to_reserve == 0 {
+        return 0;
+    }
+
+    (amount as u128 * to_reserve as u128 / from_reserve as u128) as u64
+}
+
+pub fn amm_deposit_get_lp_out(base_in: u64, base_reserve: u64, lp_supply: u64) -> u64 {
+    if base_in == 0 || base_reserve == 0 || lp_supply == 0 {
+        return 0;
+    }
+
+    (base_in as u128 * lp_supply as u128 / base_reserve as u128) as u64
+}
+
+pub fn amm_deposit_get_amounts_in(lp_out: u64, base_reserve: u64, quote_reserve: u64, lp_supply: u64) -> (u64, u64) {
+    if lp_out == 0 || lp_supply == 0 {
+        return (0, 0);
+    }
+
+    let lp_out = lp_out as u128;
+    let lp_supply = lp_supply as u128;
+    let base_in = (lp_out * base_reserve as u128).div_ceil(lp_supply);
+    let quote_in = (lp_out * quote_reserve as u128).div_ceil(lp_supply);
+
+    (base_in as u64, quote_in as u64)
+}
+
+pub fn amm_withdraw_get_amounts_out(lp_in: u64, base_reserve: u64, quote_reserve: u64, lp_supply: u64) -> (u64, u64) {
+    if lp_in == 0 || lp_supply == 0 || lp_in > lp_supply {
+        return (0, 0);
+    }
+
+    let lp_in = lp_in as u128;
+    let lp_supply = lp_supply as u128;
+    let base_out = lp_in * base_reserve as u128 / lp_supply;
+    let quote_out = lp_in * quote_reserve as u128 / lp_supply;
+
+    (base_out as u64, quote_out as u64)
+}
+
+// Splits `sol_in` across constant-product pools of (sol_reserve, token_reserve) so that all used pools end at the same marginal price,
+// which maximises the total token output. Pools are filled cheapest first and a pool joins once the price of the others reaches its own.
+pub fn amm_split_buy(sol_in: u64, reserves: &[(u64, u64)]) -> Vec<u64> {
+    let mut amounts = vec![0; reserves.len()];
+    let mut pools = (0..reserves.len())
+        .filter(|index| reserves[*index].0 > 0 && reserves[*index].1 > 0)
+        .collect::<Vec<_>>();
+    if sol_in == 0 || pools.is_empty() {
+        return amounts;
+    }
+
+    let spot = |index: &usize| reserves[*index].1 as f64 / reserves[*index].0 as f64;
+    let root = |index: &usize| (reserves[*index].0 as f64 * reserves[*index].1 as f64).sqrt();
+    pools.sort_by(|a, b| spot(b).total_cmp(&spot(a)));
+
+    // With k = 1 / sqrt(marginal price), pool i takes k * sqrt(x_i * y_i) - x_i
+    let mut active = 0;
+    let mut k = 0.0;
+    while active < pools.len() {
+        let next = &pools[active];
+        if active > 0 && k * root(next) <= reserves[*next].0 as f64 {
+            break;
+        }
+        active += 1;
+        let sol_reserves = pools[..active].iter().map(|index| reserves[*index].0 as f64).sum::<f64>();
+        let roots = pools[..active].iter().map(root).sum::<f64>();
+        k = (sol_in as f64 + sol_reserves) / roots;
+    }
+
+    for index in &pools[1..active] {
+        amounts[*index] = (k * root(index) - reserves[*index].0 as f64).clamp(0.0, sol_in as f64) as u64;
+    }
+    // The cheapest pool takes the rest, absorbing rounding
+    amounts[pools[0]] = sol_in.saturating_sub(amounts.iter().sum());
+
+    amounts
+}
+
+// Splits `sol_in` across `venues` with arbitrary concave outputs: each of the `steps` chunks goes to the venue whose `quote` grows the most
+pub fn split_buy_numeric(sol_in: u64, venues: usize, steps: u64, quote: impl Fn(usize, u64) -> u64) -> Vec<u64> {
+    let mut amounts = vec![0; venues];
+    if sol_in == 0 || venues == 0 {
+        return amounts;
+    }
+
+    let steps = steps.clamp(1, sol_in);
+    let chunk = sol_in / steps;
+    for step in 0..steps {
+        let size = if step + 1 == steps { sol_in - chunk * (steps - 1) } else { chunk };
+        let best = (0..venues)
+            .max_by_key(|venue| quote(*venue, amounts[*venue] + size).saturating_sub(quote(*venue, amounts[*venue])))
+            .unwrap_or(0);
+        amounts[best] += size;
+    }
+
+    amounts
+}
+
 pub fn calculate_with_slippage_buy(amount: u64, basis_points: u64) -> u64 {
     amount + (amount * basis_points) / 10000
 }
@@ -49,3 +149,13 @@ pub fn calculate_with_slippage_buy(amount: u64, basis_points: u64) -> u64 {
 pub fn calculate_with_slippage_sell(amount: u64, basis_points: u64) -> u64 {
     amount - (amount * basis_points) / 10000
 }
+
+// Input left after an input-side swap fee of `fee_numerator / fee_denominator`, the fee being rounded up as the AMM programs do
+pub fn amm_amount_after_fee(amount: u64, fee_numerator: u64, fee_denominator: u64) -> u64 {
+    if fee_denominator == 0 {
+        return amount;
+    }
+
+    let fee = (amount as u128 * fee_numerator as u128).div_ceil(fee_denominator as u128);
+    amount.saturating_sub(fee as u64)
+}
@@ -1,16 +1,15 @@
 use super::{boopfun_types::*, dex_traits::DexTrait, types::Create};
 use crate::{
-    common::{accounts::PUBKEY_WSOL, trading_endpoint::TradingEndpoint},
+    common::{accounts::PUBKEY_WSOL, signer::TxSigner, trading_endpoint::TradingEndpoint},
     dex::types::{PoolInfo, SwapInfo},
     instruction::builder::PriorityFee,
 };
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
+    signature::Signature,
 };
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use std::sync::Arc;
 
 pub struct Boopfun {
@@ -39,7 +38,7 @@ impl DexTrait for Boopfun {
         let pool = Self::get_bonding_curve_pda(mint)?;
         let account = self.endpoint.rpc.get_account(&pool).await?;
         if account.data.is_empty() {
-            return Err(anyhow::anyhow!("Bonding curve not found: {}", mint.to_string()));
+            return Err(anyhow::anyhow!("Bonding curve not found: {}", mint));
         }
 
         let bonding_curve = bincode::deserialize::<BondingCurveAccount>(&account.data)?;
@@ -52,18 +51,20 @@ impl DexTrait for Boopfun {
             extra_address: None,
             token_reserves: bonding_curve.virtual_token_reserves,
             sol_reserves: bonding_curve.virtual_sol_reserves,
+            complete: bonding_curve.status != BondingCurveStatus::Trading,
         })
     }
 
-    async fn create(&self, _: Keypair, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
         Err(anyhow::anyhow!("Not supported"))
     }
 
-    fn build_buy_instruction(&self, payer: &Keypair, mint: &Pubkey, _: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, _: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
         let buy_info: BuyInfo = buy.into();
         let buffer = buy_info.to_buffer()?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
         let bonding_curve = Self::get_bonding_curve_pda(mint)?;
         let bonding_curve_vault = Self::get_bonding_curve_vault(mint)?;
         let bonding_curve_sol_vault = Self::get_bonding_curve_sol_vault(mint)?;
@@ -78,23 +79,24 @@ impl DexTrait for Boopfun {
                 AccountMeta::new(trading_fee_vault, false),
                 AccountMeta::new(bonding_curve_vault, false),
                 AccountMeta::new(bonding_curve_sol_vault, false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(PUBKEY_BOOPFUN_CONFIG, false),
                 AccountMeta::new_readonly(PUBKEY_BOOPFUN_VAULT_AUTHORITY, false),
                 AccountMeta::new_readonly(PUBKEY_WSOL, false),
                 AccountMeta::new_readonly(solana_program::system_program::ID, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(token_program, false),
                 AccountMeta::new_readonly(spl_associated_token_account::ID, false),
             ],
         ))
     }
 
-    fn build_sell_instruction(&self, payer: &Keypair, mint: &Pubkey, _: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, _: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
         let sell_info: SellInfo = sell.into();
         let buffer = sell_info.to_buffer()?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
         let bonding_curve = Self::get_bonding_curve_pda(mint)?;
         let bonding_curve_vault = Self::get_bonding_curve_vault(mint)?;
         let bonding_curve_sol_vault = Self::get_bonding_curve_sol_vault(mint)?;
@@ -109,12 +111,12 @@ impl DexTrait for Boopfun {
                 AccountMeta::new(trading_fee_vault, false),
                 AccountMeta::new(bonding_curve_vault, false),
                 AccountMeta::new(bonding_curve_sol_vault, false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(PUBKEY_BOOPFUN_CONFIG, false),
                 AccountMeta::new_readonly(solana_program::system_program::ID, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(token_program, false),
                 AccountMeta::new_readonly(spl_associated_token_account::ID, false),
             ],
         ))
@@ -1,18 +1,21 @@
 use super::{
     amm_calc::{amm_buy_get_token_out, amm_sell_get_sol_out, calculate_with_slippage_buy, calculate_with_slippage_sell},
-    types::{BatchBuyParam, BatchSellParam, Create, CreateATA, PoolInfo, SwapInfo, TokenAmountType},
+    types::{BatchBuyParam, BatchSellParam, Create, CreateATA, PoolInfo, SwapInfo, SwapQuote, TokenAmountType},
 };
 use crate::{
-    common::trading_endpoint::{BatchTxItem, TradingEndpoint},
-    instruction::builder::{build_sol_buy_instructions, build_sol_sell_instructions, build_wsol_buy_instructions, build_wsol_sell_instructions, PriorityFee},
-};
-use solana_sdk::{
-    hash::Hash,
-    instruction::Instruction,
-    pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
+    common::{
+        accounts::PUBKEY_WSOL,
+        mint_info::MintInfo,
+        signer::TxSigner,
+        trading_endpoint::{BatchTxItem, TradingEndpoint},
+        wallet_set::{Allocation, WalletSet},
+    },
+    instruction::builder::{
+        build_quote_sell_instructions, build_sol_buy_instructions, build_sol_sell_instructions, build_wsol_buy_instructions, build_wsol_sell_instructions,
+        PriorityFee,
+    },
 };
+use solana_sdk::{hash::Hash, instruction::Instruction, pubkey::Pubkey, signature::Signature, signer::Signer};
 use std::{any::Any, sync::Arc};
 
 #[async_trait::async_trait]
@@ -21,13 +24,83 @@ pub trait DexTrait: Send + Sync + Any {
     fn initialized(&self) -> anyhow::Result<()>;
     fn use_wsol(&self) -> bool;
     fn get_trading_endpoint(&self) -> Arc<TradingEndpoint>;
+    fn get_quote_mint(&self, _mint: &Pubkey, _extra_address: Option<&Pubkey>) -> Pubkey {
+        PUBKEY_WSOL
+    }
     async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo>;
-    async fn create(&self, payer: Keypair, create: Create, fee: Option<PriorityFee>, tip: Option<u64>) -> anyhow::Result<Vec<Signature>>;
-    fn build_buy_instruction(&self, payer: &Keypair, mint: &Pubkey, creator_vault: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction>;
-    fn build_sell_instruction(&self, payer: &Keypair, mint: &Pubkey, creator_vault: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction>;
+    // Whether `get_pool` has to scan program accounts instead of deriving the pool address
+    fn get_pool_scans(&self) -> bool {
+        false
+    }
+    // Whether `quote_buy` follows x * y = k on the pool reserves, enabling the closed-form split in `amm_split_buy`
+    fn is_constant_product(&self) -> bool {
+        true
+    }
+    // Whether the buy instruction spends exactly the SOL given for a minimum of tokens, instead of buying the tokens for a maximum of SOL
+    fn is_exact_in_buy(&self) -> bool {
+        false
+    }
+    async fn create(&self, payer: &dyn TxSigner, create: Create, fee: Option<PriorityFee>, tip: Option<u64>) -> anyhow::Result<Vec<Signature>>;
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, creator_vault: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction>;
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, creator_vault: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction>;
+    fn quote_buy(&self, pool_info: &PoolInfo, _mint_info: &MintInfo, sol_amount: u64) -> SwapQuote {
+        SwapQuote {
+            token_amount: amm_buy_get_token_out(pool_info.sol_reserves, pool_info.token_reserves, sol_amount),
+            sol_amount,
+        }
+    }
+    fn quote_sell(&self, pool_info: &PoolInfo, mint_info: &MintInfo, token_amount: u64) -> SwapQuote {
+        let token_amount = mint_info.amount_after_transfer_fee(token_amount);
+        SwapQuote {
+            token_amount,
+            sol_amount: amm_sell_get_sol_out(pool_info.sol_reserves, pool_info.token_reserves, token_amount),
+        }
+    }
+    // Fee charged on top of the SOL reaching the pool on buys, in basis points. Weighs the venues in `amm_split_buy`.
+    fn get_buy_fee_basis_points(&self, _pool_info: &PoolInfo) -> u64 {
+        0
+    }
+    // Moves the pool past a quoted swap, so that consecutive items are quoted against the pool left by the previous ones
+    fn advance_pool(&self, pool_info: &mut PoolInfo, quote: &SwapQuote, is_buy: bool) {
+        if is_buy {
+            pool_info.sol_reserves += quote.sol_amount;
+            pool_info.token_reserves = pool_info.token_reserves.saturating_sub(quote.token_amount);
+        } else {
+            pool_info.sol_reserves = pool_info.sol_reserves.saturating_sub(quote.sol_amount);
+            pool_info.token_reserves += quote.token_amount;
+        }
+    }
+    // Quotes one buy and advances the pool
+    fn quote_buy_item(&self, pool_info: &mut PoolInfo, mint_info: &MintInfo, sol_amount: u64, slippage_basis_points: u64) -> SwapInfo {
+        let quote = self.quote_buy(pool_info, mint_info, sol_amount);
+        self.advance_pool(pool_info, &quote, true);
+        let token_amount = mint_info.amount_after_transfer_fee(quote.token_amount);
+
+        if self.is_exact_in_buy() {
+            SwapInfo {
+                token_amount: calculate_with_slippage_sell(token_amount, slippage_basis_points),
+                sol_amount,
+            }
+        } else {
+            SwapInfo {
+                token_amount,
+                sol_amount: calculate_with_slippage_buy(sol_amount, slippage_basis_points),
+            }
+        }
+    }
+    fn quote_sell_item(&self, pool_info: &mut PoolInfo, mint_info: &MintInfo, token_amount: u64, slippage_basis_points: u64) -> SwapInfo {
+        let quote = self.quote_sell(pool_info, mint_info, token_amount);
+        self.advance_pool(pool_info, &quote, false);
+
+        SwapInfo {
+            token_amount,
+            sol_amount: calculate_with_slippage_sell(quote.sol_amount, slippage_basis_points),
+        }
+    }
+    #[allow(clippy::too_many_arguments)]
     async fn buy(
         &self,
-        payer: &Keypair,
+        payer: &dyn TxSigner,
         mint: &Pubkey,
         sol_amount: u64,
         slippage_basis_points: u64,
@@ -35,25 +108,30 @@ pub trait DexTrait: Send + Sync + Any {
         tip: Option<u64>,
     ) -> anyhow::Result<Vec<Signature>> {
         let trading_endpoint = self.get_trading_endpoint();
-        let (pool_info, blockhash) = tokio::try_join!(self.get_pool(mint), trading_endpoint.get_latest_blockhash(),)?;
-        let buy_token_amount = amm_buy_get_token_out(pool_info.sol_reserves, pool_info.token_reserves, sol_amount);
-        let sol_lamports_with_slippage = calculate_with_slippage_buy(sol_amount, slippage_basis_points);
+        trading_endpoint.risk.check_slippage(slippage_basis_points)?;
+        let (mut pool_info, blockhash, mint_info) = tokio::try_join!(
+            self.get_pool(mint),
+            trading_endpoint.get_latest_blockhash(),
+            trading_endpoint.get_mint_info(mint)
+        )?;
+        let swap = self.quote_buy_item(&mut pool_info, &mint_info, sol_amount, slippage_basis_points);
 
         self.buy_immediately(
             payer,
             mint,
             pool_info.extra_address.as_ref(),
-            sol_lamports_with_slippage,
-            buy_token_amount,
+            swap.sol_amount,
+            swap.token_amount,
             blockhash,
             CreateATA::Idempotent,
             fee,
             tip,
         )
     }
+    #[allow(clippy::too_many_arguments)]
     fn buy_immediately(
         &self,
-        payer: &Keypair,
+        payer: &dyn TxSigner,
         mint: &Pubkey,
         extra_address: Option<&Pubkey>,
         sol_amount: u64,
@@ -63,21 +141,58 @@ pub trait DexTrait: Send + Sync + Any {
         fee: Option<PriorityFee>,
         tip: Option<u64>,
     ) -> anyhow::Result<Vec<Signature>> {
-        let instruction = self.build_buy_instruction(payer, mint, extra_address, SwapInfo { token_amount, sol_amount })?;
-        let instructions = if self.use_wsol() {
-            build_wsol_buy_instructions(payer, mint, sol_amount, instruction, create_ata)?
+        let trading_endpoint = self.get_trading_endpoint();
+        let reservation = trading_endpoint.risk.check_trade(&payer.pubkey(), mint, sol_amount)?;
+
+        let result = self
+            .build_buy_instruction(payer, mint, extra_address, SwapInfo { token_amount, sol_amount })
+            .and_then(|instruction| self.wrap_buy_instruction(payer, mint, extra_address, sol_amount, instruction, create_ata))
+            .and_then(|instructions| trading_endpoint.build_and_broadcast_tx(payer, instructions, blockhash, fee, tip, None));
+
+        trading_endpoint.risk.settle(reservation, result)
+    }
+    fn wrap_buy_instruction(
+        &self,
+        payer: &dyn TxSigner,
+        mint: &Pubkey,
+        extra_address: Option<&Pubkey>,
+        sol_amount: u64,
+        instruction: Instruction,
+        create_ata: CreateATA,
+    ) -> anyhow::Result<Vec<Instruction>> {
+        let token_program = self.get_trading_endpoint().get_cached_token_program(mint)?;
+        if self.use_wsol() && self.get_quote_mint(mint, extra_address) == PUBKEY_WSOL {
+            build_wsol_buy_instructions(payer, mint, &token_program, sol_amount, instruction, create_ata)
         } else {
-            build_sol_buy_instructions(payer, mint, instruction, create_ata)?
-        };
-        let signatures = self
-            .get_trading_endpoint()
-            .build_and_broadcast_tx(payer, instructions, blockhash, fee, tip, None)?;
+            build_sol_buy_instructions(payer, mint, &token_program, instruction, create_ata)
+        }
+    }
+    fn wrap_sell_instruction(
+        &self,
+        payer: &dyn TxSigner,
+        mint: &Pubkey,
+        extra_address: Option<&Pubkey>,
+        instruction: Instruction,
+        close_mint_ata: bool,
+    ) -> anyhow::Result<Vec<Instruction>> {
+        let trading_endpoint = self.get_trading_endpoint();
+        let token_program = trading_endpoint.get_cached_token_program(mint)?;
+        if !self.use_wsol() {
+            return build_sol_sell_instructions(payer, mint, &token_program, instruction, close_mint_ata);
+        }
 
-        Ok(signatures)
+        let quote_mint = self.get_quote_mint(mint, extra_address);
+        if quote_mint == PUBKEY_WSOL {
+            build_wsol_sell_instructions(payer, mint, &token_program, instruction, close_mint_ata)
+        } else {
+            let quote_token_program = trading_endpoint.get_cached_token_program(&quote_mint)?;
+            build_quote_sell_instructions(payer, mint, &token_program, &quote_mint, &quote_token_program, instruction, close_mint_ata)
+        }
     }
+    #[allow(clippy::too_many_arguments)]
     async fn sell(
         &self,
-        payer: &Keypair,
+        payer: &dyn TxSigner,
         mint: &Pubkey,
         token_amount: TokenAmountType,
         slippage_basis_points: u64,
@@ -86,30 +201,32 @@ pub trait DexTrait: Send + Sync + Any {
         tip: Option<u64>,
     ) -> anyhow::Result<Vec<Signature>> {
         let trading_endpoint = self.get_trading_endpoint();
+        trading_endpoint.risk.check_slippage(slippage_basis_points)?;
         let payer_pubkey = payer.pubkey();
-        let (pool_info, blockhash, token_amount) = tokio::try_join!(
-            self.get_pool(&mint),
+        let mint_info = trading_endpoint.get_mint_info(mint).await?;
+        let (mut pool_info, blockhash, token_amount) = tokio::try_join!(
+            self.get_pool(mint),
             trading_endpoint.get_latest_blockhash(),
-            token_amount.to_amount(trading_endpoint.rpc.clone(), &payer_pubkey, mint)
+            token_amount.to_amount(trading_endpoint.rpc.clone(), &payer_pubkey, mint, &mint_info.token_program)
         )?;
-        let sol_lamports = amm_sell_get_sol_out(pool_info.sol_reserves, pool_info.token_reserves, token_amount);
-        let sol_lamports_with_slippage = calculate_with_slippage_sell(sol_lamports, slippage_basis_points);
+        let swap = self.quote_sell_item(&mut pool_info, &mint_info, token_amount, slippage_basis_points);
 
         self.sell_immediately(
             payer,
             mint,
             pool_info.extra_address.as_ref(),
-            token_amount,
-            sol_lamports_with_slippage,
+            swap.token_amount,
+            swap.sol_amount,
             close_mint_ata,
             blockhash,
             fee,
             tip,
         )
     }
+    #[allow(clippy::too_many_arguments)]
     fn sell_immediately(
         &self,
-        payer: &Keypair,
+        payer: &dyn TxSigner,
         mint: &Pubkey,
         extra_address: Option<&Pubkey>,
         token_amount: u64,
@@ -119,15 +236,12 @@ pub trait DexTrait: Send + Sync + Any {
         fee: Option<PriorityFee>,
         tip: Option<u64>,
     ) -> anyhow::Result<Vec<Signature>> {
+        let trading_endpoint = self.get_trading_endpoint();
+        trading_endpoint.risk.check_trade(&payer.pubkey(), mint, 0)?;
+
         let instruction = self.build_sell_instruction(payer, mint, extra_address, SwapInfo { token_amount, sol_amount })?;
-        let instructions = if self.use_wsol() {
-            build_wsol_sell_instructions(payer, mint, instruction, close_mint_ata)?
-        } else {
-            build_sol_sell_instructions(payer, mint, instruction, close_mint_ata)?
-        };
-        let signatures = self
-            .get_trading_endpoint()
-            .build_and_broadcast_tx(payer, instructions, blockhash, fee, tip, None)?;
+        let instructions = self.wrap_sell_instruction(payer, mint, extra_address, instruction, close_mint_ata)?;
+        let signatures = trading_endpoint.build_and_broadcast_tx(payer, instructions, blockhash, fee, tip, None)?;
 
         Ok(signatures)
     }
@@ -140,39 +254,32 @@ pub trait DexTrait: Send + Sync + Any {
         items: Vec<BatchBuyParam>,
     ) -> anyhow::Result<Vec<Signature>> {
         let trading_endpoint = self.get_trading_endpoint();
-        let (pool_info, blockhash) = tokio::try_join!(self.get_pool(&mint), trading_endpoint.get_latest_blockhash(),)?;
-        let mut pool_token_amount = pool_info.token_reserves;
-        let mut pool_sol_amount = pool_info.sol_reserves;
+        trading_endpoint.risk.check_slippage(slippage_basis_points)?;
+        let (mut pool_info, blockhash, mint_info) = tokio::try_join!(
+            self.get_pool(mint),
+            trading_endpoint.get_latest_blockhash(),
+            trading_endpoint.get_mint_info(mint)
+        )?;
+        let extra_address = pool_info.extra_address;
         let mut batch_items = vec![];
+        let mut trades = vec![];
 
         for item in items {
-            let sol_lamports_with_slippage = calculate_with_slippage_buy(item.sol_amount, slippage_basis_points);
-            let buy_token_amount = amm_buy_get_token_out(pool_sol_amount, pool_token_amount, item.sol_amount);
-            let instruction = self.build_buy_instruction(
-                &item.payer,
-                &mint,
-                pool_info.creator_vault.as_ref(),
-                SwapInfo {
-                    token_amount: buy_token_amount,
-                    sol_amount: sol_lamports_with_slippage,
-                },
-            )?;
-            let instructions = if self.use_wsol() {
-                build_wsol_buy_instructions(&item.payer, mint, sol_lamports_with_slippage, instruction, CreateATA::Idempotent)?
-            } else {
-                build_sol_buy_instructions(&item.payer, mint, instruction, CreateATA::Idempotent)?
-            };
+            let swap = self.quote_buy_item(&mut pool_info, &mint_info, item.sol_amount, slippage_basis_points);
+            trades.push((item.payer.pubkey(), swap.sol_amount));
+            let instruction = self.build_buy_instruction(&item.payer, mint, extra_address.as_ref(), swap)?;
+            let instructions = self.wrap_buy_instruction(&item.payer, mint, extra_address.as_ref(), swap.sol_amount, instruction, CreateATA::Idempotent)?;
             batch_items.push(BatchTxItem {
                 payer: item.payer,
                 instructions,
+                other_signers: vec![],
             });
-            pool_sol_amount += item.sol_amount;
-            pool_token_amount -= buy_token_amount;
         }
 
-        let signatures = trading_endpoint.build_and_broadcast_batch_txs(batch_items, blockhash, fee, tip).await?;
+        let reservation = trading_endpoint.risk.check_trades(mint, &trades)?;
+        let result = trading_endpoint.build_and_broadcast_batch_txs(batch_items, blockhash, fee, tip).await;
 
-        Ok(signatures)
+        trading_endpoint.risk.settle(reservation, result)
     }
     async fn batch_sell(
         &self,
@@ -183,38 +290,60 @@ pub trait DexTrait: Send + Sync + Any {
         items: Vec<BatchSellParam>,
     ) -> anyhow::Result<Vec<Signature>> {
         let trading_endpoint = self.get_trading_endpoint();
-        let (pool_info, blockhash) = tokio::try_join!(self.get_pool(&mint), trading_endpoint.get_latest_blockhash(),)?;
-        let mut pool_token_amount = pool_info.token_reserves;
-        let mut pool_sol_amount = pool_info.sol_reserves;
+        trading_endpoint.risk.check_slippage(slippage_basis_points)?;
+        let mint_info = trading_endpoint.get_mint_info(mint).await?;
+        let payers = items.iter().map(|item| item.payer.pubkey()).collect::<Vec<_>>();
+        let trades = payers.iter().map(|payer| (*payer, 0)).collect::<Vec<_>>();
+        let token_amounts = futures::future::try_join_all(
+            items
+                .iter()
+                .zip(payers.iter())
+                .map(|(item, payer)| item.token_amount.to_amount(trading_endpoint.rpc.clone(), payer, mint, &mint_info.token_program)),
+        );
+        let (mut pool_info, blockhash, token_amounts) = tokio::try_join!(self.get_pool(mint), trading_endpoint.get_latest_blockhash(), token_amounts)?;
+        let extra_address = pool_info.extra_address;
         let mut batch_items = vec![];
 
-        for item in items {
-            let sol_amount = amm_sell_get_sol_out(pool_sol_amount, pool_token_amount, item.token_amount);
-            let sol_lamports_with_slippage = calculate_with_slippage_sell(sol_amount, slippage_basis_points);
-            let instruction = self.build_sell_instruction(
-                &item.payer,
-                &mint,
-                pool_info.creator_vault.as_ref(),
-                SwapInfo {
-                    token_amount: sol_amount,
-                    sol_amount: sol_lamports_with_slippage,
-                },
-            )?;
-            let instructions = if self.use_wsol() {
-                build_wsol_sell_instructions(&item.payer, mint, instruction, item.close_mint_ata)?
-            } else {
-                build_sol_sell_instructions(&item.payer, mint, instruction, item.close_mint_ata)?
-            };
+        for (item, token_amount) in items.into_iter().zip(token_amounts) {
+            let swap = self.quote_sell_item(&mut pool_info, &mint_info, token_amount, slippage_basis_points);
+            let instruction = self.build_sell_instruction(&item.payer, mint, extra_address.as_ref(), swap)?;
+            let instructions = self.wrap_sell_instruction(&item.payer, mint, extra_address.as_ref(), instruction, item.close_mint_ata)?;
             batch_items.push(BatchTxItem {
                 payer: item.payer,
                 instructions,
+                other_signers: vec![],
             });
-            pool_sol_amount -= sol_amount;
-            pool_token_amount += item.token_amount;
         }
 
-        let signatures = trading_endpoint.build_and_broadcast_batch_txs(batch_items, blockhash, fee, tip).await?;
+        let reservation = trading_endpoint.risk.check_trades(mint, &trades)?;
+        let result = trading_endpoint.build_and_broadcast_batch_txs(batch_items, blockhash, fee, tip).await;
 
-        Ok(signatures)
+        trading_endpoint.risk.settle(reservation, result)
+    }
+    async fn batch_buy_wallets(
+        &self,
+        mint: &Pubkey,
+        slippage_basis_points: u64,
+        fee: PriorityFee,
+        tip: u64,
+        wallets: &WalletSet,
+        allocation: &Allocation,
+    ) -> anyhow::Result<Vec<Signature>> {
+        let items = wallets.batch_buy_params(allocation)?;
+        self.batch_buy(mint, slippage_basis_points, fee, tip, items).await
+    }
+    #[allow(clippy::too_many_arguments)]
+    async fn batch_sell_wallets(
+        &self,
+        mint: &Pubkey,
+        slippage_basis_points: u64,
+        fee: PriorityFee,
+        tip: u64,
+        wallets: &WalletSet,
+        token_amount: TokenAmountType,
+        close_mint_ata: bool,
+    ) -> anyhow::Result<Vec<Signature>> {
+        let items = wallets.batch_sell_params(token_amount, close_mint_ata);
+        self.batch_sell(mint, slippage_basis_points, fee, tip, items).await
     }
 }
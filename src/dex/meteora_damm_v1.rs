@@ -0,0 +1,337 @@
+use super::{
+    amm_calc::{amm_amount_after_fee, amm_buy_get_token_out, amm_sell_get_sol_out},
+    dex_traits::DexTrait,
+    meteora_damm_v1_types::*,
+    meteora_damm_v2_types::SwapParameters,
+    meteora_dbc_types::DBC_DAMM_V1_MIGRATION_CONFIGS,
+    types::{Create, PoolInfo, SwapInfo, SwapQuote},
+};
+use crate::{
+    common::{accounts::PUBKEY_WSOL, mint_info::MintInfo, signer::TxSigner, trading_endpoint::TradingEndpoint},
+    instruction::builder::PriorityFee,
+};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signature,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    state::{Account as TokenAccount, Mint},
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Meteora DAMM v1 (dynamic AMM) pools, where older DBC tokens migrate to. Liquidity sits in Meteora vaults,
+// the pool holding vault LP shares, so reserves are read through the vaults. Only pools quoted in WSOL are traded.
+pub struct MeteoraDammV1 {
+    pub endpoint: Arc<TradingEndpoint>,
+    pub pools: RwLock<HashMap<Pubkey, Arc<DammV1Pool>>>,
+}
+
+#[async_trait::async_trait]
+impl DexTrait for MeteoraDammV1 {
+    async fn initialize(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn initialized(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn use_wsol(&self) -> bool {
+        true
+    }
+
+    fn get_trading_endpoint(&self) -> Arc<TradingEndpoint> {
+        self.endpoint.clone()
+    }
+
+    // The deepest WSOL pool of `mint`
+    async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo> {
+        let (pools, _) = tokio::try_join!(self.get_pools_by_mint(mint), self.endpoint.get_mint_info(mint))?;
+        let pool = pools
+            .into_iter()
+            .max_by_key(|pool| pool.sol_reserve)
+            .ok_or_else(|| anyhow::anyhow!("Pool not found: {}", mint))?;
+
+        let pool_info = PoolInfo {
+            pool: pool.pool,
+            creator: None,
+            creator_vault: None,
+            config: None,
+            extra_address: Some(pool.pool),
+            token_reserves: pool.token_reserve,
+            sol_reserves: pool.sol_reserve,
+            complete: !pool.state.enabled,
+        };
+        self.pools.write().unwrap().insert(pool.pool, Arc::new(pool));
+
+        Ok(pool_info)
+    }
+
+    // Pools that did not come from a DBC migration are found by scanning
+    fn get_pool_scans(&self) -> bool {
+        true
+    }
+
+    // The swap fee is taken from the input, so the closed-form split does not apply
+    fn is_constant_product(&self) -> bool {
+        false
+    }
+
+    fn is_exact_in_buy(&self) -> bool {
+        true
+    }
+
+    fn quote_buy(&self, pool_info: &PoolInfo, _mint_info: &MintInfo, sol_amount: u64) -> SwapQuote {
+        let (fee_numerator, fee_denominator) = self.get_trade_fee(&pool_info.pool);
+        let sol_in = amm_amount_after_fee(sol_amount, fee_numerator, fee_denominator);
+        SwapQuote {
+            token_amount: amm_buy_get_token_out(pool_info.sol_reserves, pool_info.token_reserves, sol_in),
+            sol_amount,
+        }
+    }
+
+    fn quote_sell(&self, pool_info: &PoolInfo, mint_info: &MintInfo, token_amount: u64) -> SwapQuote {
+        let (fee_numerator, fee_denominator) = self.get_trade_fee(&pool_info.pool);
+        let token_amount = mint_info.amount_after_transfer_fee(token_amount);
+        let token_in = amm_amount_after_fee(token_amount, fee_numerator, fee_denominator);
+        SwapQuote {
+            token_amount,
+            sol_amount: amm_sell_get_sol_out(pool_info.sol_reserves, pool_info.token_reserves, token_in),
+        }
+    }
+
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+        Err(anyhow::anyhow!("Not supported"))
+    }
+
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let buffer = SwapParameters::from_swap_info(&buy, true).to_buffer()?;
+        let pool = self.get_cached_pool(mint, pool)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_METEORA_DAMM_V1,
+            &buffer,
+            Self::build_swap_accounts(payer, &pool, mint, false),
+        ))
+    }
+
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let buffer = SwapParameters::from_swap_info(&sell, false).to_buffer()?;
+        let pool = self.get_cached_pool(mint, pool)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_METEORA_DAMM_V1,
+            &buffer,
+            Self::build_swap_accounts(payer, &pool, mint, true),
+        ))
+    }
+}
+
+impl MeteoraDammV1 {
+    pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
+        Self {
+            endpoint,
+            pools: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn get_cached_pool(&self, mint: &Pubkey, pool: Option<&Pubkey>) -> anyhow::Result<Arc<DammV1Pool>> {
+        let pool = pool.ok_or_else(|| anyhow::anyhow!("Pool address required: {}", mint))?;
+        self.pools
+            .read()
+            .unwrap()
+            .get(pool)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Pool not loaded, call get_pool first: {}", pool))
+    }
+
+    fn get_trade_fee(&self, pool: &Pubkey) -> (u64, u64) {
+        self.pools
+            .read()
+            .unwrap()
+            .get(pool)
+            .map(|pool| (pool.state.fees.trade_fee_numerator, pool.state.fees.trade_fee_denominator))
+            .unwrap_or((0, 1))
+    }
+
+    // The protocol fee account is the one of the input token
+    fn build_swap_accounts(payer: &dyn TxSigner, pool: &DammV1Pool, mint: &Pubkey, sell: bool) -> Vec<AccountMeta> {
+        let mint_ata = get_associated_token_address(&payer.pubkey(), mint);
+        let wsol_ata = get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL);
+        let (source, destination) = if sell { (mint_ata, wsol_ata) } else { (wsol_ata, mint_ata) };
+        let protocol_token_fee = if sell == pool.mint_is_a {
+            pool.state.protocol_token_a_fee
+        } else {
+            pool.state.protocol_token_b_fee
+        };
+
+        vec![
+            AccountMeta::new(pool.pool, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new(pool.state.a_vault, false),
+            AccountMeta::new(pool.state.b_vault, false),
+            AccountMeta::new(pool.a_vault.token_vault, false),
+            AccountMeta::new(pool.b_vault.token_vault, false),
+            AccountMeta::new(pool.a_vault.lp_mint, false),
+            AccountMeta::new(pool.b_vault.lp_mint, false),
+            AccountMeta::new(pool.state.a_vault_lp, false),
+            AccountMeta::new(pool.state.b_vault_lp, false),
+            AccountMeta::new(protocol_token_fee, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+            AccountMeta::new_readonly(PUBKEY_METEORA_VAULT, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+        ]
+    }
+
+    async fn get_pool_accounts(&self, token_a_mint: &Pubkey, token_b_mint: &Pubkey) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        Ok(self
+            .endpoint
+            .rpc
+            .get_program_accounts_with_config(
+                &PUBKEY_METEORA_DAMM_V1,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Bytes(POOL_ACCOUNT_DISCRIMINATOR.to_vec()))),
+                        RpcFilterType::Memcmp(Memcmp::new(
+                            POOL_TOKEN_A_MINT_OFFSET,
+                            MemcmpEncodedBytes::Bytes(token_a_mint.to_bytes().to_vec()),
+                        )),
+                        RpcFilterType::Memcmp(Memcmp::new(
+                            POOL_TOKEN_B_MINT_OFFSET,
+                            MemcmpEncodedBytes::Bytes(token_b_mint.to_bytes().to_vec()),
+                        )),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                        commitment: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    },
+                    with_context: None,
+                    sort_results: None,
+                },
+            )
+            .await?)
+    }
+
+    async fn get_multiple_accounts(&self, keys: &[Pubkey]) -> anyhow::Result<Vec<Account>> {
+        let mut accounts = vec![];
+        for chunk in keys.chunks(100) {
+            accounts.extend(self.endpoint.rpc.get_multiple_accounts(chunk).await?);
+        }
+
+        accounts
+            .into_iter()
+            .zip(keys)
+            .map(|(account, key)| account.ok_or_else(|| anyhow::anyhow!("Account not found: {}", key)))
+            .collect()
+    }
+
+    pub fn get_pool_pda(config: &Pubkey, token_a_mint: &Pubkey, token_b_mint: &Pubkey) -> anyhow::Result<Pubkey> {
+        let (first, second) = if token_a_mint > token_b_mint {
+            (token_a_mint, token_b_mint)
+        } else {
+            (token_b_mint, token_a_mint)
+        };
+        let seeds = &[first.as_ref(), second.as_ref(), config.as_ref()];
+        let pda = Pubkey::try_find_program_address(seeds, &PUBKEY_METEORA_DAMM_V1).ok_or_else(|| anyhow::anyhow!("Failed to find pool PDA"))?;
+        Ok(pda.0)
+    }
+
+    // Pools a DBC graduation of `mint` creates, derived from the DAMM configs DBC migrates with
+    async fn get_migrated_pool_accounts(&self, mint: &Pubkey) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        let pools = DBC_DAMM_V1_MIGRATION_CONFIGS
+            .iter()
+            .map(|config| Self::get_pool_pda(config, mint, &PUBKEY_WSOL))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let accounts = self.endpoint.rpc.get_multiple_accounts(&pools).await?;
+
+        Ok(pools
+            .into_iter()
+            .zip(accounts)
+            .filter_map(|(pool, account)| Some((pool, account?)))
+            .filter(|(_, account)| account.owner == PUBKEY_METEORA_DAMM_V1 && !account.data.is_empty())
+            .collect())
+    }
+
+    // The WSOL pools `mint` migrated to from DBC, else every WSOL pool of `mint` on either side found by scanning
+    pub async fn get_pools_by_mint(&self, mint: &Pubkey) -> anyhow::Result<Vec<DammV1Pool>> {
+        let mut accounts = self.get_migrated_pool_accounts(mint).await?;
+        if accounts.is_empty() {
+            let (a_pools, b_pools) = tokio::try_join!(self.get_pool_accounts(mint, &PUBKEY_WSOL), self.get_pool_accounts(&PUBKEY_WSOL, mint))?;
+            accounts = a_pools.into_iter().chain(b_pools).collect();
+        }
+        let states = accounts
+            .into_iter()
+            .map(|(pool, account)| {
+                let state = bincode::deserialize::<Pool>(&account.data)?;
+                let mint_is_a = state.token_a_mint == *mint;
+                Ok((pool, state, mint_is_a))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // Both vaults and the LP shares the pool holds in them, then the vault LP mints for their supply
+        let keys = states
+            .iter()
+            .flat_map(|(_, state, _)| [state.a_vault, state.b_vault, state.a_vault_lp, state.b_vault_lp])
+            .collect::<Vec<_>>();
+        let accounts = self.get_multiple_accounts(&keys).await?;
+        let vaults = accounts
+            .chunks(4)
+            .map(|accounts| {
+                Ok((
+                    bincode::deserialize::<Vault>(&accounts[0].data)?,
+                    bincode::deserialize::<Vault>(&accounts[1].data)?,
+                    StateWithExtensions::<TokenAccount>::unpack(&accounts[2].data)?.base.amount,
+                    StateWithExtensions::<TokenAccount>::unpack(&accounts[3].data)?.base.amount,
+                ))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let lp_mints = vaults
+            .iter()
+            .flat_map(|(a_vault, b_vault, _, _)| [a_vault.lp_mint, b_vault.lp_mint])
+            .collect::<Vec<_>>();
+        let lp_mint_accounts = self.get_multiple_accounts(&lp_mints).await?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+        states
+            .into_iter()
+            .zip(vaults)
+            .zip(lp_mint_accounts.chunks(2))
+            .map(|(((pool, state, mint_is_a), (a_vault, b_vault, a_vault_lp, b_vault_lp)), lp_mints)| {
+                let a_lp_supply = StateWithExtensions::<Mint>::unpack(&lp_mints[0].data)?.base.supply;
+                let b_lp_supply = StateWithExtensions::<Mint>::unpack(&lp_mints[1].data)?.base.supply;
+                let reserve_a = a_vault.get_amount_by_share(now, a_vault_lp, a_lp_supply);
+                let reserve_b = b_vault.get_amount_by_share(now, b_vault_lp, b_lp_supply);
+                let (token_reserve, sol_reserve) = if mint_is_a { (reserve_a, reserve_b) } else { (reserve_b, reserve_a) };
+
+                Ok(DammV1Pool {
+                    pool,
+                    state,
+                    a_vault,
+                    b_vault,
+                    mint_is_a,
+                    token_reserve,
+                    sol_reserve,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const PUBKEY_METEORA_DAMM_V1: Pubkey = pubkey!("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB");
+pub const PUBKEY_METEORA_VAULT: Pubkey = pubkey!("24Uqj9JCLxUeoC3hGfh5W3s9FM9uCHDS2SG3LYwBpyTi");
+
+pub const POOL_ACCOUNT_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+pub const POOL_TOKEN_A_MINT_OFFSET: usize = 40;
+pub const POOL_TOKEN_B_MINT_OFFSET: usize = 72;
+pub const LOCKED_PROFIT_DEGRADATION_DENOMINATOR: u128 = 1_000_000_000_000;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolFees {
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub protocol_trade_fee_numerator: u64,
+    pub protocol_trade_fee_denominator: u64,
+}
+
+// Leading fields of the pool account, the curve type is not read as DBC migrations only create constant-product pools
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pool {
+    pub discriminator: [u8; 8],
+    pub lp_mint: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub a_vault: Pubkey,
+    pub b_vault: Pubkey,
+    pub a_vault_lp: Pubkey,
+    pub b_vault_lp: Pubkey,
+    pub a_vault_lp_bump: u8,
+    pub enabled: bool,
+    pub protocol_token_a_fee: Pubkey,
+    pub protocol_token_b_fee: Pubkey,
+    pub fee_last_updated_at: u64,
+    pub padding0: [u8; 24],
+    pub fees: PoolFees,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct VaultBumps {
+    pub vault_bump: u8,
+    pub token_vault_bump: u8,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LockedProfitTracker {
+    pub last_updated_locked_profit: u64,
+    pub last_report: u64,
+    pub locked_profit_degradation: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vault {
+    pub discriminator: [u8; 8],
+    pub enabled: u8,
+    pub bumps: VaultBumps,
+    pub total_amount: u64,
+    pub token_vault: Pubkey,
+    pub fee_vault: Pubkey,
+    pub token_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub strategies: [Pubkey; 30],
+    pub base: Pubkey,
+    pub admin: Pubkey,
+    pub operator: Pubkey,
+    pub locked_profit_tracker: LockedProfitTracker,
+}
+
+impl Vault {
+    // Total amount less the profit still being unlocked, which is what vault LP shares are redeemable against
+    pub fn unlocked_amount(&self, now: u64) -> u64 {
+        let tracker = &self.locked_profit_tracker;
+        let ratio = now.saturating_sub(tracker.last_report) as u128 * tracker.locked_profit_degradation as u128;
+        let locked = if ratio > LOCKED_PROFIT_DEGRADATION_DENOMINATOR {
+            0
+        } else {
+            tracker.last_updated_locked_profit as u128 * (LOCKED_PROFIT_DEGRADATION_DENOMINATOR - ratio) / LOCKED_PROFIT_DEGRADATION_DENOMINATOR
+        };
+
+        self.total_amount.saturating_sub(locked as u64)
+    }
+
+    // Underlying tokens redeemable for `lp_amount` vault LP shares
+    pub fn get_amount_by_share(&self, now: u64, lp_amount: u64, lp_supply: u64) -> u64 {
+        if lp_supply == 0 {
+            return 0;
+        }
+
+        (self.unlocked_amount(now) as u128 * lp_amount as u128 / lp_supply as u128) as u64
+    }
+}
+
+// A pool of `mint` against WSOL, `mint_is_a` telling which side of the pool the mint is on
+#[derive(Debug, Clone)]
+pub struct DammV1Pool {
+    pub pool: Pubkey,
+    pub state: Pool,
+    pub a_vault: Vault,
+    pub b_vault: Vault,
+    pub mint_is_a: bool,
+    pub token_reserve: u64,
+    pub sol_reserve: u64,
+}
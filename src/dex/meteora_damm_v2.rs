@@ -0,0 +1,290 @@
+use super::{
+    amm_calc::{amm_amount_after_fee, amm_buy_get_token_out, amm_sell_get_sol_out},
+    dex_traits::DexTrait,
+    meteora_damm_v2_types::*,
+    meteora_dbc_types::DBC_DAMM_V2_MIGRATION_CONFIGS,
+    types::{Create, PoolInfo, SwapInfo, SwapQuote},
+};
+use crate::{
+    common::{accounts::PUBKEY_WSOL, mint_info::MintInfo, signer::TxSigner, trading_endpoint::TradingEndpoint},
+    instruction::builder::PriorityFee,
+};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signature,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Meteora DAMM v2 pools, where DBC tokens migrate to. Only pools quoted in WSOL are traded.
+pub struct MeteoraDammV2 {
+    pub endpoint: Arc<TradingEndpoint>,
+    pub pools: RwLock<HashMap<Pubkey, Arc<DammV2Pool>>>,
+}
+
+#[async_trait::async_trait]
+impl DexTrait for MeteoraDammV2 {
+    async fn initialize(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn initialized(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn use_wsol(&self) -> bool {
+        true
+    }
+
+    fn get_trading_endpoint(&self) -> Arc<TradingEndpoint> {
+        self.endpoint.clone()
+    }
+
+    // The deepest WSOL pool of `mint`, with virtual reserves derived from its liquidity and price
+    async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo> {
+        let (pools, _) = tokio::try_join!(self.get_pools_by_mint(mint), self.endpoint.get_mint_info(mint))?;
+        let reserves = |pool: &DammV2Pool| {
+            let (reserve_a, reserve_b) = pool.state.virtual_reserves();
+            if pool.mint_is_a {
+                (reserve_a, reserve_b)
+            } else {
+                (reserve_b, reserve_a)
+            }
+        };
+        let pool = pools
+            .into_iter()
+            .max_by_key(|pool| reserves(pool).1)
+            .ok_or_else(|| anyhow::anyhow!("Pool not found: {}", mint))?;
+        let (token_reserves, sol_reserves) = reserves(&pool);
+
+        let pool_info = PoolInfo {
+            pool: pool.pool,
+            creator: None,
+            creator_vault: None,
+            config: None,
+            extra_address: Some(pool.pool),
+            token_reserves,
+            sol_reserves,
+            complete: false,
+        };
+        self.pools.write().unwrap().insert(pool.pool, Arc::new(pool));
+
+        Ok(pool_info)
+    }
+
+    // Pools that did not come from a DBC migration are found by scanning
+    fn get_pool_scans(&self) -> bool {
+        true
+    }
+
+    // Fees are taken from the input or the output depending on the pool, so the closed-form split does not apply
+    fn is_constant_product(&self) -> bool {
+        false
+    }
+
+    fn is_exact_in_buy(&self) -> bool {
+        true
+    }
+
+    fn quote_buy(&self, pool_info: &PoolInfo, _mint_info: &MintInfo, sol_amount: u64) -> SwapQuote {
+        let pool = self.pools.read().unwrap().get(&pool_info.pool).cloned();
+        let (fee_numerator, fee_on_input) = pool.map(|pool| (pool.fee_numerator, pool.fee_on_input(!pool.mint_is_a))).unwrap_or((0, false));
+
+        let token_amount = if fee_on_input {
+            let sol_in = amm_amount_after_fee(sol_amount, fee_numerator, FEE_DENOMINATOR);
+            amm_buy_get_token_out(pool_info.sol_reserves, pool_info.token_reserves, sol_in)
+        } else {
+            let token_out = amm_buy_get_token_out(pool_info.sol_reserves, pool_info.token_reserves, sol_amount);
+            amm_amount_after_fee(token_out, fee_numerator, FEE_DENOMINATOR)
+        };
+
+        SwapQuote { token_amount, sol_amount }
+    }
+
+    fn quote_sell(&self, pool_info: &PoolInfo, mint_info: &MintInfo, token_amount: u64) -> SwapQuote {
+        let pool = self.pools.read().unwrap().get(&pool_info.pool).cloned();
+        let (fee_numerator, fee_on_input) = pool.map(|pool| (pool.fee_numerator, pool.fee_on_input(pool.mint_is_a))).unwrap_or((0, false));
+        let token_amount = mint_info.amount_after_transfer_fee(token_amount);
+
+        let sol_amount = if fee_on_input {
+            let token_in = amm_amount_after_fee(token_amount, fee_numerator, FEE_DENOMINATOR);
+            amm_sell_get_sol_out(pool_info.sol_reserves, pool_info.token_reserves, token_in)
+        } else {
+            let sol_out = amm_sell_get_sol_out(pool_info.sol_reserves, pool_info.token_reserves, token_amount);
+            amm_amount_after_fee(sol_out, fee_numerator, FEE_DENOMINATOR)
+        };
+
+        SwapQuote { token_amount, sol_amount }
+    }
+
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+        Err(anyhow::anyhow!("Not supported"))
+    }
+
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let buffer = SwapParameters::from_swap_info(&buy, true).to_buffer()?;
+        let pool = self.get_cached_pool(mint, pool)?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_METEORA_DAMM_V2,
+            &buffer,
+            Self::build_swap_accounts(payer, &pool, mint, &token_program, false),
+        ))
+    }
+
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let buffer = SwapParameters::from_swap_info(&sell, false).to_buffer()?;
+        let pool = self.get_cached_pool(mint, pool)?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_METEORA_DAMM_V2,
+            &buffer,
+            Self::build_swap_accounts(payer, &pool, mint, &token_program, true),
+        ))
+    }
+}
+
+impl MeteoraDammV2 {
+    pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
+        Self {
+            endpoint,
+            pools: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn get_cached_pool(&self, mint: &Pubkey, pool: Option<&Pubkey>) -> anyhow::Result<Arc<DammV2Pool>> {
+        let pool = pool.ok_or_else(|| anyhow::anyhow!("Pool address required: {}", mint))?;
+        self.pools
+            .read()
+            .unwrap()
+            .get(pool)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Pool not loaded, call get_pool first: {}", pool))
+    }
+
+    fn build_swap_accounts(payer: &dyn TxSigner, pool: &DammV2Pool, mint: &Pubkey, token_program: &Pubkey, sell: bool) -> Vec<AccountMeta> {
+        let mint_ata = get_associated_token_address_with_program_id(&payer.pubkey(), mint, token_program);
+        let wsol_ata = get_associated_token_address_with_program_id(&payer.pubkey(), &PUBKEY_WSOL, &spl_token::ID);
+        let (input, output) = if sell { (mint_ata, wsol_ata) } else { (wsol_ata, mint_ata) };
+        let (token_a_program, token_b_program) = if pool.mint_is_a {
+            (*token_program, spl_token::ID)
+        } else {
+            (spl_token::ID, *token_program)
+        };
+
+        vec![
+            AccountMeta::new_readonly(PUBKEY_METEORA_DAMM_V2_POOL_AUTHORITY, false),
+            AccountMeta::new(pool.pool, false),
+            AccountMeta::new(input, false),
+            AccountMeta::new(output, false),
+            AccountMeta::new(pool.state.token_a_vault, false),
+            AccountMeta::new(pool.state.token_b_vault, false),
+            AccountMeta::new_readonly(pool.state.token_a_mint, false),
+            AccountMeta::new_readonly(pool.state.token_b_mint, false),
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(token_a_program, false),
+            AccountMeta::new_readonly(token_b_program, false),
+            // No referral account
+            AccountMeta::new_readonly(PUBKEY_METEORA_DAMM_V2, false),
+            AccountMeta::new_readonly(PUBKEY_METEORA_DAMM_V2_EVENT_AUTHORITY, false),
+            AccountMeta::new_readonly(PUBKEY_METEORA_DAMM_V2, false),
+        ]
+    }
+
+    async fn get_pool_accounts(&self, token_a_mint: &Pubkey, token_b_mint: &Pubkey) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        Ok(self
+            .endpoint
+            .rpc
+            .get_program_accounts_with_config(
+                &PUBKEY_METEORA_DAMM_V2,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Bytes(POOL_ACCOUNT_DISCRIMINATOR.to_vec()))),
+                        RpcFilterType::Memcmp(Memcmp::new(
+                            POOL_TOKEN_A_MINT_OFFSET,
+                            MemcmpEncodedBytes::Bytes(token_a_mint.to_bytes().to_vec()),
+                        )),
+                        RpcFilterType::Memcmp(Memcmp::new(
+                            POOL_TOKEN_B_MINT_OFFSET,
+                            MemcmpEncodedBytes::Bytes(token_b_mint.to_bytes().to_vec()),
+                        )),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                        commitment: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    },
+                    with_context: None,
+                    sort_results: None,
+                },
+            )
+            .await?)
+    }
+
+    pub fn get_pool_pda(config: &Pubkey, token_a_mint: &Pubkey, token_b_mint: &Pubkey) -> anyhow::Result<Pubkey> {
+        let (first, second) = if token_a_mint > token_b_mint {
+            (token_a_mint, token_b_mint)
+        } else {
+            (token_b_mint, token_a_mint)
+        };
+        let seeds = &[POOL_SEED, config.as_ref(), first.as_ref(), second.as_ref()];
+        let pda = Pubkey::try_find_program_address(seeds, &PUBKEY_METEORA_DAMM_V2).ok_or_else(|| anyhow::anyhow!("Failed to find pool PDA"))?;
+        Ok(pda.0)
+    }
+
+    // Pools a DBC graduation of `mint` creates, derived from the DAMM configs DBC migrates with
+    async fn get_migrated_pool_accounts(&self, mint: &Pubkey) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        let pools = DBC_DAMM_V2_MIGRATION_CONFIGS
+            .iter()
+            .map(|config| Self::get_pool_pda(config, mint, &PUBKEY_WSOL))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let accounts = self.endpoint.rpc.get_multiple_accounts(&pools).await?;
+
+        Ok(pools
+            .into_iter()
+            .zip(accounts)
+            .filter_map(|(pool, account)| Some((pool, account?)))
+            .filter(|(_, account)| account.owner == PUBKEY_METEORA_DAMM_V2 && !account.data.is_empty())
+            .collect())
+    }
+
+    // The WSOL pools `mint` migrated to from DBC, else every WSOL pool of `mint` on either side found by scanning
+    pub async fn get_pools_by_mint(&self, mint: &Pubkey) -> anyhow::Result<Vec<DammV2Pool>> {
+        let mut accounts = self.get_migrated_pool_accounts(mint).await?;
+        if accounts.is_empty() {
+            let (a_pools, b_pools) = tokio::try_join!(self.get_pool_accounts(mint, &PUBKEY_WSOL), self.get_pool_accounts(&PUBKEY_WSOL, mint))?;
+            accounts = a_pools.into_iter().chain(b_pools).collect();
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+        accounts
+            .into_iter()
+            .map(|(pool, account)| {
+                let state = bincode::deserialize::<Pool>(&account.data)?;
+                Ok(DammV2Pool {
+                    pool,
+                    fee_numerator: state.fee_numerator(now),
+                    mint_is_a: state.token_a_mint == *mint,
+                    state,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,162 @@
+use super::types::SwapInfo;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Deserialize;
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const PUBKEY_METEORA_DAMM_V2: Pubkey = pubkey!("cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG");
+pub const PUBKEY_METEORA_DAMM_V2_POOL_AUTHORITY: Pubkey = pubkey!("HLnpSz9h2S4hiLQ43rnSD9XkcUThA7B8hQMKmDaiTLcC");
+pub const PUBKEY_METEORA_DAMM_V2_EVENT_AUTHORITY: Pubkey = pubkey!("3rmHSu74h1ZcmAisVcWerTCiRDQbUrBKmcwptYGjHfet");
+
+pub const POOL_SEED: &[u8] = b"pool";
+pub const POOL_ACCOUNT_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+pub const POOL_TOKEN_A_MINT_OFFSET: usize = 168;
+pub const POOL_TOKEN_B_MINT_OFFSET: usize = 200;
+pub const FEE_DENOMINATOR: u64 = 1_000_000_000;
+pub const BASIS_POINT_MAX: u64 = 10_000;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BaseFeeStruct {
+    pub cliff_fee_numerator: u64,
+    pub fee_scheduler_mode: u8,
+    pub padding_0: [u8; 5],
+    pub number_of_period: u16,
+    pub period_frequency: u64,
+    pub reduction_factor: u64,
+    pub padding_1: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DynamicFeeStruct {
+    pub initialized: u8,
+    pub padding: [u8; 7],
+    pub max_volatility_accumulator: u32,
+    pub variable_fee_control: u32,
+    pub bin_step: u16,
+    pub filter_period: u16,
+    pub decay_period: u16,
+    pub reduction_factor: u16,
+    pub last_update_timestamp: u64,
+    pub bin_step_u128: u128,
+    pub sqrt_price_reference: u128,
+    pub volatility_accumulator: u128,
+    pub volatility_reference: u128,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolFeesStruct {
+    pub base_fee: BaseFeeStruct,
+    pub protocol_fee_percent: u8,
+    pub partner_fee_percent: u8,
+    pub referral_fee_percent: u8,
+    pub padding_0: [u8; 5],
+    pub dynamic_fee: DynamicFeeStruct,
+    pub padding_1: [u64; 2],
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pool {
+    pub discriminator: [u8; 8],
+    pub pool_fees: PoolFeesStruct,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub whitelisted_vault: Pubkey,
+    pub partner: Pubkey,
+    pub liquidity: u128,
+    pub padding: u128,
+    pub protocol_a_fee: u64,
+    pub protocol_b_fee: u64,
+    pub partner_a_fee: u64,
+    pub partner_b_fee: u64,
+    pub sqrt_min_price: u128,
+    pub sqrt_max_price: u128,
+    pub sqrt_price: u128,
+    pub activation_point: u64,
+    // 0: slot, 1: timestamp
+    pub activation_type: u8,
+    pub pool_status: u8,
+    pub token_a_flag: u8,
+    pub token_b_flag: u8,
+    // 0: fees in both tokens, taken from the output, 1: fees in token B only
+    pub collect_fee_mode: u8,
+    pub pool_type: u8,
+}
+
+impl Pool {
+    // Base fee numerator at `now`, following the fee scheduler for timestamp-activated pools.
+    // Slot-activated pools are quoted at the cliff fee, the upper bound of the schedule.
+    pub fn fee_numerator(&self, now: u64) -> u64 {
+        let base_fee = &self.pool_fees.base_fee;
+        if base_fee.number_of_period == 0 || base_fee.period_frequency == 0 || self.activation_type != 1 {
+            return base_fee.cliff_fee_numerator;
+        }
+
+        let periods = (now.saturating_sub(self.activation_point) / base_fee.period_frequency).min(base_fee.number_of_period as u64);
+        match base_fee.fee_scheduler_mode {
+            0 => base_fee.cliff_fee_numerator.saturating_sub(periods * base_fee.reduction_factor),
+            1 => {
+                let rate = 1.0 - base_fee.reduction_factor as f64 / BASIS_POINT_MAX as f64;
+                (base_fee.cliff_fee_numerator as f64 * rate.powi(periods as i32)) as u64
+            }
+            _ => base_fee.cliff_fee_numerator,
+        }
+    }
+
+    // Constant-product reserves equivalent to the liquidity at the current price: L / sqrt(P) of token A and L * sqrt(P) of token B
+    pub fn virtual_reserves(&self) -> (u64, u64) {
+        let sqrt_price = self.sqrt_price as f64 / 2f64.powi(64);
+        let liquidity = self.liquidity as f64 / 2f64.powi(64);
+        if sqrt_price == 0.0 {
+            return (0, 0);
+        }
+
+        ((liquidity / sqrt_price) as u64, (liquidity * sqrt_price) as u64)
+    }
+}
+
+// A pool of `mint` against WSOL, `mint_is_a` telling which side of the pool the mint is on
+#[derive(Debug, Clone)]
+pub struct DammV2Pool {
+    pub pool: Pubkey,
+    pub state: Pool,
+    pub mint_is_a: bool,
+    // Out of `FEE_DENOMINATOR`
+    pub fee_numerator: u64,
+}
+
+impl DammV2Pool {
+    // Whether the fee of a swap from `input_is_a` is taken from its input rather than its output
+    pub fn fee_on_input(&self, input_is_a: bool) -> bool {
+        self.state.collect_fee_mode == 1 && !input_is_a
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SwapParameters {
+    pub discriminator: u64,
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+}
+
+impl SwapParameters {
+    pub fn from_swap_info(swap_info: &SwapInfo, is_buy: bool) -> Self {
+        let (amount_in, minimum_amount_out) = if is_buy {
+            (swap_info.sol_amount, swap_info.token_amount)
+        } else {
+            (swap_info.token_amount, swap_info.sol_amount)
+        };
+
+        Self {
+            discriminator: 14449647541112719096,
+            amount_in,
+            minimum_amount_out,
+        }
+    }
+
+    pub fn to_buffer(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer)?;
+        Ok(buffer)
+    }
+}
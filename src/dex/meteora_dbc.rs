@@ -1,6 +1,11 @@
-use super::{dex_traits::DexTrait, meteora_dbc_types::*, types::Create};
+use super::{
+    amm_calc::{amm_buy_get_token_out, amm_sell_get_sol_out},
+    dex_traits::DexTrait,
+    meteora_dbc_types::*,
+    types::{Create, SwapQuote},
+};
 use crate::{
-    common::{accounts::PUBKEY_WSOL, trading_endpoint::TradingEndpoint},
+    common::{accounts::PUBKEY_WSOL, mint_info::MintInfo, signer::TxSigner, trading_endpoint::TradingEndpoint},
     dex::types::{PoolInfo, SwapInfo},
     instruction::builder::PriorityFee,
 };
@@ -11,14 +16,20 @@ use solana_client::{
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
+    signature::Signature,
+};
+use spl_associated_token_account::{get_associated_token_address, get_associated_token_address_with_program_id};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
 };
-use spl_associated_token_account::get_associated_token_address;
-use std::sync::Arc;
+
+const Q64: f64 = 18446744073709551616.0;
 
 pub struct MeteoraDBC {
     pub endpoint: Arc<TradingEndpoint>,
+    // Liquidity curve and fees of each pool config, which do not change once created
+    pub configs: RwLock<HashMap<Pubkey, Arc<CurveConfig>>>,
 }
 
 #[async_trait::async_trait]
@@ -40,11 +51,10 @@ impl DexTrait for MeteoraDBC {
     }
 
     async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo> {
-        let bonding_curve = self.get_pool_by_base_mint(mint).await?;
+        let (bonding_curve, _) = tokio::try_join!(self.get_pool_by_base_mint(mint), self.endpoint.get_mint_info(mint))?;
+        self.get_config(&bonding_curve.config).await?;
         let pool = Self::get_virtual_pool_pda(mint, &bonding_curve.config)?;
-        let sqrt_price = (bonding_curve.sqrt_price as f64) / (2u128.pow(64) as f64);
-        let price = sqrt_price * sqrt_price;
-        let virtual_sol_reserve = price * bonding_curve.base_reserve as f64;
+        let virtual_sol_reserve = Self::get_virtual_sol_reserves(bonding_curve.sqrt_price, bonding_curve.base_reserve);
 
         Ok(PoolInfo {
             pool,
@@ -53,19 +63,67 @@ impl DexTrait for MeteoraDBC {
             config: Some(bonding_curve.config),
             extra_address: Some(bonding_curve.config),
             token_reserves: bonding_curve.base_reserve,
-            sol_reserves: virtual_sol_reserve as u64,
+            sol_reserves: virtual_sol_reserve,
+            complete: bonding_curve.is_migrated != 0,
         })
     }
 
-    async fn create(&self, _: Keypair, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+    fn get_pool_scans(&self) -> bool {
+        true
+    }
+
+    // Prices follow the piecewise liquidity curve of the pool config
+    fn is_constant_product(&self) -> bool {
+        false
+    }
+
+    fn is_exact_in_buy(&self) -> bool {
+        true
+    }
+
+    fn quote_buy(&self, pool_info: &PoolInfo, _mint_info: &MintInfo, sol_amount: u64) -> SwapQuote {
+        let token_amount = match self.get_cached_config(pool_info) {
+            Some(config) => config.quote_buy(Self::get_sqrt_price(pool_info), sol_amount).0,
+            None => amm_buy_get_token_out(pool_info.sol_reserves, pool_info.token_reserves, sol_amount),
+        };
+        SwapQuote { token_amount, sol_amount }
+    }
+
+    fn quote_sell(&self, pool_info: &PoolInfo, mint_info: &MintInfo, token_amount: u64) -> SwapQuote {
+        let token_amount = mint_info.amount_after_transfer_fee(token_amount);
+        let sol_amount = match self.get_cached_config(pool_info) {
+            Some(config) => config.quote_sell(Self::get_sqrt_price(pool_info), token_amount).0,
+            None => amm_sell_get_sol_out(pool_info.sol_reserves, pool_info.token_reserves, token_amount),
+        };
+        SwapQuote { token_amount, sol_amount }
+    }
+
+    // The virtual SOL reserves are re-derived from the price the swap moves the curve to
+    fn advance_pool(&self, pool_info: &mut PoolInfo, quote: &SwapQuote, is_buy: bool) {
+        let Some(config) = self.get_cached_config(pool_info) else {
+            return;
+        };
+        let sqrt_price = Self::get_sqrt_price(pool_info);
+        let next_sqrt_price = if is_buy {
+            pool_info.token_reserves = pool_info.token_reserves.saturating_sub(quote.token_amount);
+            config.quote_buy(sqrt_price, quote.sol_amount).1
+        } else {
+            pool_info.token_reserves += quote.token_amount;
+            config.quote_sell(sqrt_price, quote.token_amount).1
+        };
+        pool_info.sol_reserves = Self::get_virtual_sol_reserves(next_sqrt_price, pool_info.token_reserves);
+    }
+
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
         Err(anyhow::anyhow!("Not supported"))
     }
 
-    fn build_buy_instruction(&self, payer: &Keypair, mint: &Pubkey, config: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, config: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
         let buy_info = SwapInstruction::from_swap_info(&buy, true);
         let buffer = buy_info.to_buffer()?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
         let config = config.ok_or_else(|| anyhow::anyhow!("Config must be provided for buy instruction"))?;
         let bonding_curve = Self::get_virtual_pool_pda(mint, config)?;
         let bonding_curve_vault = Self::get_bonding_curve_vault(&bonding_curve, mint)?;
@@ -79,13 +137,13 @@ impl DexTrait for MeteoraDBC {
                 AccountMeta::new_readonly(*config, false),
                 AccountMeta::new(bonding_curve, false),
                 AccountMeta::new(get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL), false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
                 AccountMeta::new(bonding_curve_vault, false),
                 AccountMeta::new(bonding_curve_sol_vault, false),
                 AccountMeta::new_readonly(*mint, false),
                 AccountMeta::new_readonly(PUBKEY_WSOL, false),
                 AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(token_program, false),
                 AccountMeta::new_readonly(spl_token::ID, false),
                 AccountMeta::new_readonly(PUBKEY_METEORA_DBC, false),
                 AccountMeta::new_readonly(PUBKEY_METEORA_DBC_EVENT_AUTHORITY, false),
@@ -94,11 +152,12 @@ impl DexTrait for MeteoraDBC {
         ))
     }
 
-    fn build_sell_instruction(&self, payer: &Keypair, mint: &Pubkey, config: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, config: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
         let sell_info = SwapInstruction::from_swap_info(&sell, false);
         let buffer = sell_info.to_buffer()?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
         let config = config.ok_or_else(|| anyhow::anyhow!("Config must be provided for sell instruction"))?;
         let bonding_curve = Self::get_virtual_pool_pda(mint, config)?;
         let bonding_curve_vault = Self::get_bonding_curve_vault(&bonding_curve, mint)?;
@@ -111,14 +170,14 @@ impl DexTrait for MeteoraDBC {
                 AccountMeta::new_readonly(PUBKEY_METEORA_DBC_POOL_AUTHORITY, false),
                 AccountMeta::new_readonly(*config, false),
                 AccountMeta::new(bonding_curve, false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
                 AccountMeta::new(get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL), false),
                 AccountMeta::new(bonding_curve_vault, false),
                 AccountMeta::new(bonding_curve_sol_vault, false),
                 AccountMeta::new_readonly(*mint, false),
                 AccountMeta::new_readonly(PUBKEY_WSOL, false),
                 AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(token_program, false),
                 AccountMeta::new_readonly(spl_token::ID, false),
                 AccountMeta::new_readonly(PUBKEY_METEORA_DBC, false),
                 AccountMeta::new_readonly(PUBKEY_METEORA_DBC_EVENT_AUTHORITY, false),
@@ -130,7 +189,39 @@ impl DexTrait for MeteoraDBC {
 
 impl MeteoraDBC {
     pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
-        Self { endpoint }
+        Self {
+            endpoint,
+            configs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get_config(&self, config: &Pubkey) -> anyhow::Result<Arc<CurveConfig>> {
+        if let Some(curve) = self.configs.read().unwrap().get(config) {
+            return Ok(curve.clone());
+        }
+
+        let data = self.endpoint.rpc.get_account_data(config).await?;
+        let curve = Arc::new(CurveConfig::from_account_data(&data)?);
+        self.configs.write().unwrap().insert(*config, curve.clone());
+        Ok(curve)
+    }
+
+    fn get_cached_config(&self, pool_info: &PoolInfo) -> Option<Arc<CurveConfig>> {
+        self.configs.read().unwrap().get(pool_info.config.as_ref()?).cloned()
+    }
+
+    // Price times the base reserve, the SOL side `PoolInfo` reports for the curve
+    fn get_virtual_sol_reserves(sqrt_price: u128, base_reserve: u64) -> u64 {
+        let sqrt_price = sqrt_price as f64 / Q64;
+        (sqrt_price * sqrt_price * base_reserve as f64) as u64
+    }
+
+    // Inverse of `get_virtual_sol_reserves`, in Q64.64
+    fn get_sqrt_price(pool_info: &PoolInfo) -> u128 {
+        if pool_info.token_reserves == 0 {
+            return 0;
+        }
+        ((pool_info.sol_reserves as f64 / pool_info.token_reserves as f64).sqrt() * Q64) as u128
     }
 
     pub fn get_virtual_pool_pda(mint: &Pubkey, config: &Pubkey) -> anyhow::Result<Pubkey> {
@@ -168,7 +259,7 @@ impl MeteoraDBC {
             )
             .await?;
         if accounts.is_empty() {
-            return Err(anyhow::anyhow!("No bonding curve found for base mint: {}", base_mint.to_string()));
+            return Err(anyhow::anyhow!("No bonding curve found for base mint: {}", base_mint));
         }
         let bonding_curve = bincode::deserialize::<VirtualPool>(&accounts[0].1.data)?;
 
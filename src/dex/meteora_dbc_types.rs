@@ -7,6 +7,25 @@ pub const PUBKEY_METEORA_DBC: Pubkey = pubkey!("dbcij3LWUppWqq96dh6gJWwBifmcGfLS
 pub const PUBKEY_METEORA_DBC_POOL_AUTHORITY: Pubkey = pubkey!("FhVo3mqL8PW5pH5U2CN4XE33DokiyZnUwuGpH2hmHLuM");
 pub const PUBKEY_METEORA_DBC_EVENT_AUTHORITY: Pubkey = pubkey!("8Ks12pbrD6PXxfty1hVQiE9sc289zgU1zHkvXhrSdriF");
 
+// DAMM configs graduated pools are created with, indexed by the pool config's `migration_fee_option`
+pub const DBC_DAMM_V1_MIGRATION_CONFIGS: [Pubkey; 6] = [
+    pubkey!("8f848CEy8eY6PhJ3VcemtBDzPPSD4Vq7aJczLZ3o8MmX"),
+    pubkey!("HBxB8Lf14Yj8pqeJ8C4qDb5ryHL7xwpuykz31BLNYr7S"),
+    pubkey!("7v5vBdUQHTNeqk1HnduiXcgbvCyVEZ612HLmYkQoAkik"),
+    pubkey!("EkvP7d5yKxovj884d2DwmBQbrHUWRLGK6bympzrkXGja"),
+    pubkey!("9EZYAJrcqNWNQzP2trzZesP7XKMHA1jEomHzbRsdX8R2"),
+    pubkey!("8cdKo87jZU2R12KY1BUjjRPwyjgdNjLGqSGQyrDshhRU"),
+];
+pub const DBC_DAMM_V2_MIGRATION_CONFIGS: [Pubkey; 7] = [
+    pubkey!("7F6dnUcRuyM2TwR8myT1dYypFXpPSxqwKNSFNkxyNESd"),
+    pubkey!("2nHK1kju6XjphBLbNxpM5XRGFj7p9U8vvNzyZiha1z6k"),
+    pubkey!("Hv8Lmzmnju6m7kcokVKvwqz7QPmdX9XfKjJsXz8RXcjp"),
+    pubkey!("2c4cYd4reUYVRAB9kUUkrq55VPyy2FNQ3FDL4o12JXmq"),
+    pubkey!("AkmQWebAwFvWk55wBoCr5D62C6VVDTzi84NJuD9H7cFD"),
+    pubkey!("DbCRBj8McvPYHJG1ukj8RE15h2dCNUdTAESG49XpQ44u"),
+    pubkey!("A8gMrEPJkacWkcb3DGwtJwTe16HktSEfvwtuDh2MCtck"),
+];
+
 pub const VIRTUAL_POOL_SEED: &[u8] = b"pool";
 pub const VIRTUAL_POOL_VAULT_SEED: &[u8] = b"token_vault";
 
@@ -116,3 +135,234 @@ impl SwapInstruction {
         Ok(buffer)
     }
 }
+
+pub const DBC_FEE_DENOMINATOR: u64 = 1_000_000_000;
+pub const DBC_MAX_CURVE_POINTS: usize = 20;
+// `PoolConfig` offsets: the base fee follows the discriminator and three pubkeys, the collect fee mode follows the 128-byte fee config
+const POOL_CONFIG_CLIFF_FEE_NUMERATOR_OFFSET: usize = 104;
+const POOL_CONFIG_COLLECT_FEE_MODE_OFFSET: usize = 232;
+// The start price and the liquidity curve are the last fields of `PoolConfig`
+const POOL_CONFIG_CURVE_LEN: usize = 16 + DBC_MAX_CURVE_POINTS * 32;
+
+// Pricing part of a DBC `PoolConfig`. Segment i of the curve runs from the previous point (or `sqrt_start_price`)
+// up to `curve[i].0` with liquidity `curve[i].1`, sqrt prices being Q64.64.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurveConfig {
+    // Starting fee of the base fee schedule, the highest one it charges
+    pub cliff_fee_numerator: u64,
+    // Fees are taken in the quote token on both sides, else from the output token
+    pub fee_in_quote: bool,
+    pub sqrt_start_price: u128,
+    pub curve: Vec<(u128, u128)>,
+}
+
+impl CurveConfig {
+    pub fn from_account_data(data: &[u8]) -> anyhow::Result<Self> {
+        if data.len() < POOL_CONFIG_COLLECT_FEE_MODE_OFFSET + POOL_CONFIG_CURVE_LEN {
+            return Err(anyhow::anyhow!("Invalid DBC pool config length: {}", data.len()));
+        }
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        let u128_at = |offset: usize| u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+
+        let start = data.len() - POOL_CONFIG_CURVE_LEN;
+        let curve = (0..DBC_MAX_CURVE_POINTS)
+            .map(|index| (u128_at(start + 16 + index * 32), u128_at(start + 32 + index * 32)))
+            .take_while(|(sqrt_price, liquidity)| *sqrt_price != 0 && *liquidity != 0)
+            .collect();
+
+        Ok(Self {
+            cliff_fee_numerator: u64_at(POOL_CONFIG_CLIFF_FEE_NUMERATOR_OFFSET),
+            fee_in_quote: data[POOL_CONFIG_COLLECT_FEE_MODE_OFFSET] == 0,
+            sqrt_start_price: u128_at(start),
+            curve,
+        })
+    }
+
+    fn fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.cliff_fee_numerator as u128).div_ceil(DBC_FEE_DENOMINATOR as u128) as u64
+    }
+
+    // Base tokens out for `quote_in`, and the sqrt price after the swap. Input past the last point is not filled.
+    pub fn quote_buy(&self, sqrt_price: u128, quote_in: u64) -> (u64, u128) {
+        let quote_in = if self.fee_in_quote {
+            quote_in.saturating_sub(self.fee(quote_in))
+        } else {
+            quote_in
+        };
+        let mut amount_left = quote_in as u128;
+        let mut current = sqrt_price;
+        let mut base_out = 0u128;
+
+        for (upper, liquidity) in self.curve.iter().copied() {
+            if upper <= current {
+                continue;
+            }
+            let max_in = get_delta_quote(current, upper, liquidity, true);
+            if amount_left < max_in {
+                let next = current + U256::from_parts(amount_left, 0).div(U256::from(liquidity)).low_u128();
+                base_out += get_delta_base(current, next, liquidity, false);
+                current = next;
+                break;
+            }
+            base_out += get_delta_base(current, upper, liquidity, false);
+            amount_left -= max_in;
+            current = upper;
+        }
+
+        let base_out = base_out.min(u64::MAX as u128) as u64;
+        let base_out = if self.fee_in_quote {
+            base_out
+        } else {
+            base_out.saturating_sub(self.fee(base_out))
+        };
+        (base_out, current)
+    }
+
+    // Quote tokens out for `base_in`, and the sqrt price after the swap
+    pub fn quote_sell(&self, sqrt_price: u128, base_in: u64) -> (u64, u128) {
+        let mut amount_left = base_in as u128;
+        let mut current = sqrt_price;
+        let mut quote_out = 0u128;
+
+        for index in (0..self.curve.len()).rev() {
+            if amount_left == 0 {
+                break;
+            }
+            let lower = if index == 0 { self.sqrt_start_price } else { self.curve[index - 1].0 };
+            let liquidity = self.curve[index].1;
+            if lower >= current {
+                continue;
+            }
+            let max_in = get_delta_base(lower, current, liquidity, true);
+            let next = if amount_left < max_in {
+                get_next_sqrt_price_from_base(current, liquidity, amount_left)
+            } else {
+                lower
+            };
+            quote_out += get_delta_quote(next, current, liquidity, false);
+            amount_left = amount_left.saturating_sub(max_in);
+            current = next;
+        }
+
+        let quote_out = quote_out.min(u64::MAX as u128) as u64;
+        (quote_out.saturating_sub(self.fee(quote_out)), current)
+    }
+}
+
+// L * (upper - lower) / 2^128
+fn get_delta_quote(lower: u128, upper: u128, liquidity: u128, round_up: bool) -> u128 {
+    let product = U256::mul(liquidity, upper - lower);
+    let quote = product.hi;
+    if round_up && product.lo != 0 {
+        quote + 1
+    } else {
+        quote
+    }
+}
+
+// L * (upper - lower) / (lower * upper)
+fn get_delta_base(lower: u128, upper: u128, liquidity: u128, round_up: bool) -> u128 {
+    if lower == 0 {
+        return 0;
+    }
+    let numerator = U256::mul(liquidity, upper - lower);
+    let denominator = U256::mul(lower, upper);
+    let (quotient, remainder) = numerator.div_rem(denominator);
+    if round_up && !remainder.is_zero() {
+        quotient.low_u128() + 1
+    } else {
+        quotient.low_u128()
+    }
+}
+
+// L * sqrt_price / (L + amount * sqrt_price), rounded up
+fn get_next_sqrt_price_from_base(sqrt_price: u128, liquidity: u128, amount: u128) -> u128 {
+    let numerator = U256::mul(liquidity, sqrt_price);
+    let denominator = U256::mul(amount, sqrt_price).add(U256::from(liquidity));
+    let (quotient, remainder) = numerator.div_rem(denominator);
+    quotient.low_u128() + !remainder.is_zero() as u128
+}
+
+// Unsigned 256-bit integer, just enough for the Q64.64 curve math
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct U256 {
+    hi: u128,
+    lo: u128,
+}
+
+impl U256 {
+    fn from(value: u128) -> Self {
+        Self { hi: 0, lo: value }
+    }
+
+    fn from_parts(hi: u128, lo: u128) -> Self {
+        Self { hi, lo }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.hi == 0 && self.lo == 0
+    }
+
+    fn low_u128(&self) -> u128 {
+        if self.hi == 0 {
+            self.lo
+        } else {
+            u128::MAX
+        }
+    }
+
+    fn mul(a: u128, b: u128) -> Self {
+        let (a_hi, a_lo) = (a >> 64, a & u64::MAX as u128);
+        let (b_hi, b_lo) = (b >> 64, b & u64::MAX as u128);
+        let lo_lo = a_lo * b_lo;
+        let mid = (lo_lo >> 64) + ((a_hi * b_lo) & u64::MAX as u128) + ((a_lo * b_hi) & u64::MAX as u128);
+        let hi = a_hi * b_hi + ((a_hi * b_lo) >> 64) + ((a_lo * b_hi) >> 64) + (mid >> 64);
+        Self {
+            hi,
+            lo: (mid << 64) | (lo_lo & u64::MAX as u128),
+        }
+    }
+
+    fn add(self, other: Self) -> Self {
+        let (lo, carry) = self.lo.overflowing_add(other.lo);
+        Self {
+            hi: self.hi.saturating_add(other.hi).saturating_add(carry as u128),
+            lo,
+        }
+    }
+
+    fn sub(self, other: Self) -> Self {
+        let (lo, borrow) = self.lo.overflowing_sub(other.lo);
+        Self {
+            hi: self.hi.wrapping_sub(other.hi).wrapping_sub(borrow as u128),
+            lo,
+        }
+    }
+
+    fn div(self, divisor: Self) -> Self {
+        self.div_rem(divisor).0
+    }
+
+    // Binary long division
+    fn div_rem(self, divisor: Self) -> (Self, Self) {
+        if divisor.is_zero() {
+            return (Self::from_parts(u128::MAX, u128::MAX), Self::from(0));
+        }
+        let mut quotient = Self::from(0);
+        let mut remainder = Self::from(0);
+        for bit in (0..256).rev() {
+            let overflow = remainder.hi >> 127 == 1;
+            let next_bit = if bit >= 128 { self.hi >> (bit - 128) } else { self.lo >> bit } & 1;
+            remainder = Self::from_parts((remainder.hi << 1) | (remainder.lo >> 127), (remainder.lo << 1) | next_bit);
+            if overflow || remainder >= divisor {
+                remainder = remainder.sub(divisor);
+                if bit >= 128 {
+                    quotient.hi |= 1 << (bit - 128);
+                } else {
+                    quotient.lo |= 1 << bit;
+                }
+            }
+        }
+        (quotient, remainder)
+    }
+}
@@ -1,8 +1,14 @@
+pub mod aggregator;
+pub mod aggregator_types;
 pub mod amm_calc;
 pub mod believe;
 pub mod boopfun;
 pub mod boopfun_types;
 pub mod dex_traits;
+pub mod meteora_damm_v1;
+pub mod meteora_damm_v1_types;
+pub mod meteora_damm_v2;
+pub mod meteora_damm_v2_types;
 pub mod meteora_dbc;
 pub mod meteora_dbc_types;
 pub mod moonit;
@@ -12,6 +18,10 @@ pub mod pumpfun_common_types;
 pub mod pumpfun_types;
 pub mod pumpswap;
 pub mod pumpswap_types;
+pub mod raydium_amm_v4;
+pub mod raydium_amm_v4_types;
 pub mod raydium_bonk;
 pub mod raydium_bonk_types;
+pub mod raydium_cpmm;
+pub mod raydium_cpmm_types;
 pub mod types;
@@ -1,6 +1,6 @@
 use super::{dex_traits::DexTrait, moonit_types::*, types::Create};
 use crate::{
-    common::trading_endpoint::TradingEndpoint,
+    common::{signer::TxSigner, trading_endpoint::TradingEndpoint},
     dex::types::{PoolInfo, SwapInfo},
     instruction::builder::PriorityFee,
 };
@@ -8,10 +8,9 @@ use borsh::BorshDeserialize;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
+    signature::Signature,
 };
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use std::sync::Arc;
 
 pub struct Moonit {
@@ -40,7 +39,7 @@ impl DexTrait for Moonit {
         let bonding_curve_pda = Self::get_bonding_curve_pda(mint).unwrap();
         let account = self.endpoint.rpc.get_account(&bonding_curve_pda).await?;
         if account.data.is_empty() {
-            return Err(anyhow::anyhow!("Bonding curve not found: {}", mint.to_string()));
+            return Err(anyhow::anyhow!("Bonding curve not found: {}", mint));
         }
 
         let bonding_curve = CurveAccount::deserialize(&mut account.data.as_slice())?;
@@ -53,14 +52,15 @@ impl DexTrait for Moonit {
             extra_address: None,
             token_reserves: bonding_curve.curve_amount,
             sol_reserves: INITIAL_VIRTUAL_SOL_RESERVES + account.lamports,
+            complete: bonding_curve.curve_amount == 0,
         })
     }
 
-    async fn create(&self, _: Keypair, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
         Err(anyhow::anyhow!("Not supported"))
     }
 
-    fn build_buy_instruction(&self, payer: &Keypair, mint: &Pubkey, _: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, _: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
         let trade_info: TradeParams = TradeParams {
@@ -72,6 +72,7 @@ impl DexTrait for Moonit {
         };
 
         let buffer = trade_info.to_buffer()?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
         let bonding_curve = Self::get_bonding_curve_pda(mint)?;
 
         Ok(Instruction::new_with_bytes(
@@ -79,21 +80,21 @@ impl DexTrait for Moonit {
             &buffer,
             vec![
                 AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
                 AccountMeta::new(bonding_curve, false),
-                AccountMeta::new(get_associated_token_address(&bonding_curve, mint), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&bonding_curve, mint, &token_program), false),
                 AccountMeta::new(PUBKEY_MOONIT_DEX_FEE, false),
                 AccountMeta::new(PUBKEY_MOONIT_HELIO_FEE, false),
                 AccountMeta::new_readonly(*mint, false),
                 AccountMeta::new_readonly(PUBKEY_MOONIT_CONFIG, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(token_program, false),
                 AccountMeta::new_readonly(spl_associated_token_account::ID, false),
                 AccountMeta::new_readonly(solana_program::system_program::ID, false),
             ],
         ))
     }
 
-    fn build_sell_instruction(&self, payer: &Keypair, mint: &Pubkey, _: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, _: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
         let trade_info: TradeParams = TradeParams {
@@ -105,6 +106,7 @@ impl DexTrait for Moonit {
         };
 
         let buffer = trade_info.to_buffer()?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
         let bonding_curve = Self::get_bonding_curve_pda(mint)?;
 
         Ok(Instruction::new_with_bytes(
@@ -112,14 +114,14 @@ impl DexTrait for Moonit {
             &buffer,
             vec![
                 AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
                 AccountMeta::new(bonding_curve, false),
-                AccountMeta::new(get_associated_token_address(&bonding_curve, mint), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&bonding_curve, mint, &token_program), false),
                 AccountMeta::new(PUBKEY_MOONIT_DEX_FEE, false),
                 AccountMeta::new(PUBKEY_MOONIT_HELIO_FEE, false),
                 AccountMeta::new_readonly(*mint, false),
                 AccountMeta::new_readonly(PUBKEY_MOONIT_CONFIG, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(token_program, false),
                 AccountMeta::new_readonly(spl_associated_token_account::ID, false),
                 AccountMeta::new_readonly(solana_program::system_program::ID, false),
             ],
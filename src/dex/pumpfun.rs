@@ -3,18 +3,26 @@ use super::{
     dex_traits::DexTrait,
     pumpfun_common_types::{BuyInfo, SellInfo},
     pumpfun_types::*,
-    types::{Create, PoolInfo, SwapInfo},
+    types::{BatchBuyParam, Create, CreateATA, CreateType, PoolInfo, SwapInfo},
+};
+use crate::{
+    common::{
+        risk_policy::RiskReservation,
+        signer::TxSigner,
+        trading_endpoint::{BatchTxItem, TipPlacement, TradingEndpoint},
+    },
+    instruction::builder::PriorityFee,
 };
-use crate::{common::trading_endpoint::TradingEndpoint, instruction::builder::PriorityFee};
 use borsh::BorshSerialize;
+use futures::TryFutureExt;
 use once_cell::sync::OnceCell;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::{Keypair, Signature},
+    signature::Signature,
     signer::Signer,
 };
-use spl_associated_token_account::{get_associated_token_address, instruction::create_associated_token_account};
+use spl_associated_token_account::{get_associated_token_address, get_associated_token_address_with_program_id, instruction::create_associated_token_account};
 use std::sync::Arc;
 
 pub struct Pumpfun {
@@ -48,11 +56,18 @@ impl DexTrait for Pumpfun {
         false
     }
 
+    fn get_buy_fee_basis_points(&self, _pool_info: &PoolInfo) -> u64 {
+        self.fee_basis_points()
+    }
+
     async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo> {
         let bonding_curve_pda = Self::get_bonding_curve_pda(mint).unwrap();
-        let account = self.endpoint.rpc.get_account(&bonding_curve_pda).await?;
+        let (account, _) = tokio::try_join!(
+            self.endpoint.rpc.get_account(&bonding_curve_pda).err_into(),
+            self.endpoint.get_token_program(mint)
+        )?;
         if account.data.is_empty() {
-            return Err(anyhow::anyhow!("Bonding curve not found: {}", mint.to_string()));
+            return Err(anyhow::anyhow!("Bonding curve not found: {}", mint));
         }
 
         let bonding_curve = bincode::deserialize::<BondingCurveAccount>(&account.data)?;
@@ -66,54 +81,124 @@ impl DexTrait for Pumpfun {
             extra_address: Some(creator_vault),
             token_reserves: bonding_curve.virtual_token_reserves,
             sol_reserves: bonding_curve.virtual_sol_reserves,
+            complete: bonding_curve.complete,
         })
     }
 
-    async fn create(&self, payer: Keypair, create: Create, fee: Option<PriorityFee>, tip: Option<u64>) -> anyhow::Result<Vec<Signature>> {
-        let mint = create.mint_private_key.pubkey();
-        let buy_sol_amount = create.buy_sol_amount;
-        let slippage_basis_points = create.slippage_basis_points.unwrap_or(0);
+    async fn create(&self, payer: &dyn TxSigner, create: Create, fee: Option<PriorityFee>, tip: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+        let trades = create
+            .buy_sol_amount
+            .map(|buy_sol_amount| (payer.pubkey(), buy_sol_amount))
+            .into_iter()
+            .collect();
+        let reservation = self.check_launch_buys(&create, trades)?;
 
-        let create_info = CreateInfo::from_create(&create, payer.pubkey());
-        let mut buffer = Vec::new();
-        create_info.serialize(&mut buffer)?;
+        let result = async {
+            let blockhash = self.endpoint.get_latest_blockhash().await?;
+            let (instructions, _) = self.build_create_instructions(&payer, &create)?;
+            self.endpoint
+                .build_and_broadcast_tx(&payer, instructions, blockhash, fee, tip, Some(vec![&create.mint_private_key]))
+        }
+        .await;
 
-        let blockhash = self.endpoint.rpc.get_latest_blockhash().await?;
-        let bonding_curve = Self::get_bonding_curve_pda(&mint)?;
+        self.endpoint.risk.settle(reservation, result)
+    }
 
-        let mut instructions = vec![];
-        let create_instruction = Instruction::new_with_bytes(
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, creator_vault: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let buy_info: BuyInfo = buy.into();
+        let buffer = buy_info.to_buffer()?;
+        let bonding_curve = Self::get_bonding_curve_pda(mint)?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+
+        Ok(Instruction::new_with_bytes(
             PUBKEY_PUMPFUN,
             &buffer,
             vec![
-                AccountMeta::new(mint, true),
-                AccountMeta::new(*PUBKEY_MINT_AUTHORITY_PDA, false),
+                AccountMeta::new_readonly(PUBKEY_GLOBAL_ACCOUNT, false),
+                AccountMeta::new(PUBKEY_FEE_RECIPIENT, false),
+                AccountMeta::new_readonly(*mint, false),
                 AccountMeta::new(bonding_curve, false),
-                AccountMeta::new(get_associated_token_address(&bonding_curve, &mint), false),
-                AccountMeta::new_readonly(*PUBKEY_GLOBAL_PDA, false),
-                AccountMeta::new_readonly(mpl_token_metadata::ID, false),
-                AccountMeta::new(mpl_token_metadata::accounts::Metadata::find_pda(&mint).0, false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&bonding_curve, mint, &token_program), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(solana_program::system_program::ID, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
-                AccountMeta::new_readonly(spl_associated_token_account::ID, false),
-                AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new(*creator_vault.ok_or(anyhow::anyhow!("Creator vault not provided"))?, false),
                 AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
                 AccountMeta::new_readonly(PUBKEY_PUMPFUN, false),
             ],
-        );
+        ))
+    }
 
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, creator_vault: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let sell_info: SellInfo = sell.into();
+        let buffer = sell_info.to_buffer()?;
+        let bonding_curve = Self::get_bonding_curve_pda(mint)?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_PUMPFUN,
+            &buffer,
+            vec![
+                AccountMeta::new_readonly(PUBKEY_GLOBAL_ACCOUNT, false),
+                AccountMeta::new(PUBKEY_FEE_RECIPIENT, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(bonding_curve, false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&bonding_curve, mint, &token_program), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(solana_program::system_program::ID, false),
+                AccountMeta::new(*creator_vault.ok_or(anyhow::anyhow!("Creator vault not provided"))?, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
+                AccountMeta::new_readonly(PUBKEY_PUMPFUN, false),
+            ],
+        ))
+    }
+}
+
+impl Pumpfun {
+    pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
+        Self {
+            endpoint,
+            global_account: OnceCell::new(),
+        }
+    }
+
+    // Fee charged on top of the SOL reaching the bonding curve, the same for every curve
+    pub fn fee_basis_points(&self) -> u64 {
+        self.global_account.get().map_or(0, |global_account| global_account.fee_basis_points)
+    }
+
+    // Create instruction followed by the optional dev buy, with the dev buy token amount projected from the initial curve
+    pub fn build_create_instructions(&self, payer: &dyn TxSigner, create: &Create) -> anyhow::Result<(Vec<Instruction>, u64)> {
+        let mint = create.mint_private_key.pubkey();
+        let token_program = create.create_type.token_program();
+        let slippage_basis_points = create.slippage_basis_points.unwrap_or(0);
+
+        let mut instructions = vec![];
+        let create_instruction = match create.create_type {
+            CreateType::Legacy => Self::build_create_instruction(payer, create)?,
+            CreateType::Token2022 => Self::build_create_v2_instruction(payer, create, false)?,
+            CreateType::Token2022Mayhem => Self::build_create_v2_instruction(payer, create, true)?,
+        };
         instructions.push(create_instruction);
+        self.endpoint.set_token_program(&mint, &token_program);
 
-        if let Some(buy_sol_amount) = buy_sol_amount {
-            let create_ata = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint, &spl_token::ID);
+        let mut buy_token_amount = 0;
+        if let Some(buy_sol_amount) = create.buy_sol_amount {
+            let create_ata = create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint, &token_program);
             instructions.push(create_ata);
 
-            let buy_token_amount = amm_buy_get_token_out(INITIAL_VIRTUAL_SOL_RESERVES, INITIAL_VIRTUAL_TOKEN_RESERVES, buy_sol_amount);
+            buy_token_amount = self.project_launch_buys(&[buy_sol_amount])[0];
             let sol_lamports_with_slippage = calculate_with_slippage_buy(buy_sol_amount, slippage_basis_points);
-            let creator_vault = Self::get_creator_vault_pda(&payer.pubkey()).unwrap();
+            let creator_vault = Self::get_creator_vault_pda(&payer.pubkey())?;
             let buy_instruction = self.build_buy_instruction(
-                &payer,
+                payer,
                 &mint,
                 Some(&creator_vault),
                 SwapInfo {
@@ -124,74 +209,221 @@ impl DexTrait for Pumpfun {
             instructions.push(buy_instruction);
         }
 
-        let signatures = self
-            .endpoint
-            .build_and_broadcast_tx(&payer, instructions, blockhash, fee, tip, Some(vec![&create.mint_private_key]))?;
+        Ok((instructions, buy_token_amount))
+    }
+
+    // Risk checks of the dev buy and bundled buys, spending their amounts with slippage
+    fn check_launch_buys(&self, create: &Create, trades: Vec<(Pubkey, u64)>) -> anyhow::Result<RiskReservation> {
+        let slippage_basis_points = create.slippage_basis_points.unwrap_or(0);
+        let trades = trades
+            .into_iter()
+            .map(|(wallet, sol_amount)| (wallet, calculate_with_slippage_buy(sol_amount, slippage_basis_points)))
+            .collect::<Vec<_>>();
 
-        Ok(signatures)
+        self.endpoint.risk.check_slippage(slippage_basis_points)?;
+        Ok(self.endpoint.risk.check_trades(&create.mint_private_key.pubkey(), &trades)?)
     }
 
-    fn build_buy_instruction(&self, payer: &Keypair, mint: &Pubkey, creator_vault: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
-        self.initialized()?;
+    // Token amounts of consecutive buys on a fresh curve, the first one being the dev buy if any.
+    // Each buy fee is taken out of its SOL amount first, since it is paid on top of the SOL reaching the curve.
+    pub fn project_launch_buys(&self, sol_amounts: &[u64]) -> Vec<u64> {
+        let fee_basis_points = self.fee_basis_points();
+        let mut sol_reserves = INITIAL_VIRTUAL_SOL_RESERVES;
+        let mut token_reserves = INITIAL_VIRTUAL_TOKEN_RESERVES;
 
-        let buy_info: BuyInfo = buy.into();
-        let buffer = buy_info.to_buffer()?;
-        let bonding_curve = Self::get_bonding_curve_pda(mint)?;
+        sol_amounts
+            .iter()
+            .map(|sol_amount| {
+                let sol_in = (*sol_amount as u128 * 10_000 / (10_000 + fee_basis_points) as u128) as u64;
+                let token_amount = amm_buy_get_token_out(sol_reserves, token_reserves, sol_in);
+                sol_reserves += sol_in;
+                token_reserves -= token_amount;
+                token_amount
+            })
+            .collect()
+    }
+
+    // Creates the token and buys from every wallet in one Jito bundle, all signed against the same blockhash
+    pub async fn launch_with_bundle(
+        &self,
+        payer: Arc<dyn TxSigner>,
+        create: Create,
+        buys: Vec<BatchBuyParam>,
+        fee: Option<PriorityFee>,
+        tip: u64,
+        tip_placement: TipPlacement,
+    ) -> anyhow::Result<LaunchBundle> {
+        let mint = create.mint_private_key.pubkey();
+        let slippage_basis_points = create.slippage_basis_points.unwrap_or(0);
+        let creator_vault = Self::get_creator_vault_pda(&payer.pubkey())?;
+        let trades = create
+            .buy_sol_amount
+            .map(|buy_sol_amount| (payer.pubkey(), buy_sol_amount))
+            .into_iter()
+            .chain(buys.iter().map(|buy| (buy.payer.pubkey(), buy.sol_amount)))
+            .collect();
+        let reservation = self.check_launch_buys(&create, trades)?;
+
+        let result = async move {
+            let blockhash = self.endpoint.get_latest_blockhash().await?;
+
+            let (instructions, dev_token_amount) = self.build_create_instructions(&payer, &create)?;
+            let sol_amounts = create
+                .buy_sol_amount
+                .into_iter()
+                .chain(buys.iter().map(|buy| buy.sol_amount))
+                .collect::<Vec<_>>();
+            let mut projected = self.project_launch_buys(&sol_amounts);
+            if create.buy_sol_amount.is_some() {
+                projected.remove(0);
+            }
+
+            let mut token_amounts = vec![];
+            let mut items = vec![BatchTxItem {
+                payer,
+                instructions,
+                other_signers: vec![Arc::new(create.mint_private_key)],
+            }];
+            for (buy, token_amount) in buys.into_iter().zip(projected) {
+                let sol_lamports_with_slippage = calculate_with_slippage_buy(buy.sol_amount, slippage_basis_points);
+                let instruction = self.build_buy_instruction(
+                    &buy.payer,
+                    &mint,
+                    Some(&creator_vault),
+                    SwapInfo {
+                        token_amount,
+                        sol_amount: sol_lamports_with_slippage,
+                    },
+                )?;
+                let instructions = self.wrap_buy_instruction(
+                    &buy.payer,
+                    &mint,
+                    Some(&creator_vault),
+                    sol_lamports_with_slippage,
+                    instruction,
+                    CreateATA::Idempotent,
+                )?;
+                token_amounts.push((buy.payer.pubkey(), token_amount));
+                items.push(BatchTxItem {
+                    payer: buy.payer,
+                    instructions,
+                    other_signers: vec![],
+                });
+            }
+
+            let submissions = self.endpoint.build_and_broadcast_bundle(items, blockhash, fee, tip, tip_placement).await?;
+
+            Ok(LaunchBundle {
+                mint,
+                dev_token_amount,
+                token_amounts,
+                submissions,
+            })
+        }
+        .await;
+
+        self.endpoint.risk.settle(reservation, result)
+    }
+
+    pub fn build_create_instruction(payer: &dyn TxSigner, create: &Create) -> anyhow::Result<Instruction> {
+        let mint = create.mint_private_key.pubkey();
+        let create_info = CreateInfo::from_create(create, payer.pubkey());
+        let mut buffer = Vec::new();
+        create_info.serialize(&mut buffer)?;
+
+        let bonding_curve = Self::get_bonding_curve_pda(&mint)?;
 
         Ok(Instruction::new_with_bytes(
             PUBKEY_PUMPFUN,
             &buffer,
             vec![
-                AccountMeta::new_readonly(PUBKEY_GLOBAL_ACCOUNT, false),
-                AccountMeta::new(PUBKEY_FEE_RECIPIENT, false),
-                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(mint, true),
+                AccountMeta::new(*PUBKEY_MINT_AUTHORITY_PDA, false),
                 AccountMeta::new(bonding_curve, false),
-                AccountMeta::new(get_associated_token_address(&bonding_curve, mint), false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+                AccountMeta::new(get_associated_token_address(&bonding_curve, &mint), false),
+                AccountMeta::new_readonly(*PUBKEY_GLOBAL_PDA, false),
+                AccountMeta::new_readonly(mpl_token_metadata::ID, false),
+                AccountMeta::new(mpl_token_metadata::accounts::Metadata::find_pda(&mint).0, false),
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(solana_program::system_program::ID, false),
                 AccountMeta::new_readonly(spl_token::ID, false),
-                AccountMeta::new(*creator_vault.ok_or(anyhow::anyhow!("Creator vault not provided"))?, false),
+                AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+                AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
                 AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
                 AccountMeta::new_readonly(PUBKEY_PUMPFUN, false),
             ],
         ))
     }
 
-    fn build_sell_instruction(&self, payer: &Keypair, mint: &Pubkey, creator_vault: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
-        self.initialized()?;
+    pub fn build_create_v2_instruction(payer: &dyn TxSigner, create: &Create, is_mayhem_mode: bool) -> anyhow::Result<Instruction> {
+        let mint = create.mint_private_key.pubkey();
+        let create_info = CreateV2Info::from_create(create, payer.pubkey(), is_mayhem_mode);
+        let mut buffer = Vec::new();
+        create_info.serialize(&mut buffer)?;
 
-        let sell_info: SellInfo = sell.into();
-        let buffer = sell_info.to_buffer()?;
-        let bonding_curve = Self::get_bonding_curve_pda(mint)?;
+        let bonding_curve = Self::get_bonding_curve_pda(&mint)?;
+        let mayhem_state = Self::get_mayhem_state_pda(&mint)?;
 
         Ok(Instruction::new_with_bytes(
             PUBKEY_PUMPFUN,
             &buffer,
             vec![
-                AccountMeta::new_readonly(PUBKEY_GLOBAL_ACCOUNT, false),
-                AccountMeta::new(PUBKEY_FEE_RECIPIENT, false),
-                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(mint, true),
+                AccountMeta::new_readonly(*PUBKEY_MINT_AUTHORITY_PDA, false),
                 AccountMeta::new(bonding_curve, false),
-                AccountMeta::new(get_associated_token_address(&bonding_curve, mint), false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&bonding_curve, &mint, &spl_token_2022::ID), false),
+                AccountMeta::new_readonly(*PUBKEY_GLOBAL_PDA, false),
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(solana_program::system_program::ID, false),
-                AccountMeta::new(*creator_vault.ok_or(anyhow::anyhow!("Creator vault not provided"))?, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(spl_token_2022::ID, false),
+                AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+                AccountMeta::new(PUBKEY_MAYHEM_PROGRAM, false),
+                AccountMeta::new_readonly(*PUBKEY_MAYHEM_GLOBAL_PARAMS_PDA, false),
+                AccountMeta::new(*PUBKEY_MAYHEM_SOL_VAULT_PDA, false),
+                AccountMeta::new(mayhem_state, false),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(&PUBKEY_MAYHEM_SOL_VAULT_PDA, &mint, &spl_token_2022::ID),
+                    false,
+                ),
                 AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
                 AccountMeta::new_readonly(PUBKEY_PUMPFUN, false),
             ],
         ))
     }
-}
 
-impl Pumpfun {
-    pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
-        Self {
-            endpoint,
-            global_account: OnceCell::new(),
+    // Lamports accrued in the creator vault above its rent-exempt minimum
+    pub async fn get_creator_fees(&self, creator: &Pubkey) -> anyhow::Result<u64> {
+        let creator_vault = Self::get_creator_vault_pda(creator)?;
+        let (balance, rent) = tokio::try_join!(
+            self.endpoint.rpc.get_balance(&creator_vault),
+            self.endpoint.rpc.get_minimum_balance_for_rent_exemption(0),
+        )?;
+        Ok(balance.saturating_sub(rent))
+    }
+
+    pub async fn claim_creator_fees(&self, creator: &dyn TxSigner, fee: Option<PriorityFee>, tip: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+        let creator_pubkey = creator.pubkey();
+        let (fees, blockhash) = tokio::try_join!(self.get_creator_fees(&creator_pubkey), self.endpoint.get_latest_blockhash())?;
+        if fees == 0 {
+            return Err(anyhow::anyhow!("No creator fees to claim: {}", creator_pubkey));
         }
+
+        let instruction = Self::build_collect_creator_fee_instruction(&creator_pubkey)?;
+        self.endpoint.build_and_broadcast_tx(creator, vec![instruction], blockhash, fee, tip, None)
+    }
+
+    pub fn build_collect_creator_fee_instruction(creator: &Pubkey) -> anyhow::Result<Instruction> {
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_PUMPFUN,
+            &COLLECT_CREATOR_FEE_DISCRIMINATOR.to_le_bytes(),
+            vec![
+                AccountMeta::new(*creator, true),
+                AccountMeta::new(Self::get_creator_vault_pda(creator)?, false),
+                AccountMeta::new_readonly(solana_program::system_program::ID, false),
+                AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
+                AccountMeta::new_readonly(PUBKEY_PUMPFUN, false),
+            ],
+        ))
     }
 
     pub fn get_bonding_curve_pda(mint: &Pubkey) -> anyhow::Result<Pubkey> {
@@ -207,4 +439,10 @@ impl Pumpfun {
         let pda = Pubkey::try_find_program_address(seeds, program_id).ok_or_else(|| anyhow::anyhow!("Failed to find creator vault PDA"))?;
         Ok(pda.0)
     }
+
+    pub fn get_mayhem_state_pda(mint: &Pubkey) -> anyhow::Result<Pubkey> {
+        let seeds: &[&[u8]; 2] = &[MAYHEM_STATE_SEED, mint.as_ref()];
+        let pda = Pubkey::try_find_program_address(seeds, &PUBKEY_MAYHEM_PROGRAM).ok_or_else(|| anyhow::anyhow!("Failed to find mayhem state PDA"))?;
+        Ok(pda.0)
+    }
 }
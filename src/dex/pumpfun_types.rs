@@ -1,4 +1,5 @@
 use super::types::Create;
+use crate::common::trading_endpoint::BundleSubmission;
 use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -8,18 +9,26 @@ pub const PUBKEY_PUMPFUN: Pubkey = pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5
 pub const PUBKEY_GLOBAL_ACCOUNT: Pubkey = pubkey!("4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf");
 pub const PUBKEY_EVENT_AUTHORITY: Pubkey = pubkey!("Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1");
 pub const PUBKEY_FEE_RECIPIENT: Pubkey = pubkey!("62qc2CNXwrYqQScmEdiZFFAnJR262PxWEuNQtxfafNgV");
+pub const PUBKEY_MAYHEM_PROGRAM: Pubkey = pubkey!("MAyhSmzXzV1pTf7LsNkrNwkWKTo4ougAJ1PPg47MD4e");
 
 pub const GLOBAL_SEED: &[u8] = b"global";
 pub const MINT_AUTHORITY_SEED: &[u8] = b"mint-authority";
 pub const BONDING_CURVE_SEED: &[u8] = b"bonding-curve";
 pub const CREATOR_VAULT_SEED: &[u8] = b"creator-vault";
 pub const METADATA_SEED: &[u8] = b"metadata";
+pub const MAYHEM_GLOBAL_PARAMS_SEED: &[u8] = b"global-params";
+pub const MAYHEM_SOL_VAULT_SEED: &[u8] = b"sol-vault";
+pub const MAYHEM_STATE_SEED: &[u8] = b"mayhem-state";
+
+pub const COLLECT_CREATOR_FEE_DISCRIMINATOR: u64 = 9573277071704462868;
 
 pub const INITIAL_VIRTUAL_TOKEN_RESERVES: u64 = 1_073_000_000_000_000;
 pub const INITIAL_VIRTUAL_SOL_RESERVES: u64 = 30_000_000_000;
 
 pub static PUBKEY_MINT_AUTHORITY_PDA: Lazy<Pubkey> = Lazy::new(|| Pubkey::find_program_address(&[MINT_AUTHORITY_SEED], &PUBKEY_PUMPFUN).0);
 pub static PUBKEY_GLOBAL_PDA: Lazy<Pubkey> = Lazy::new(|| Pubkey::find_program_address(&[GLOBAL_SEED], &PUBKEY_PUMPFUN).0);
+pub static PUBKEY_MAYHEM_GLOBAL_PARAMS_PDA: Lazy<Pubkey> = Lazy::new(|| Pubkey::find_program_address(&[MAYHEM_GLOBAL_PARAMS_SEED], &PUBKEY_MAYHEM_PROGRAM).0);
+pub static PUBKEY_MAYHEM_SOL_VAULT_PDA: Lazy<Pubkey> = Lazy::new(|| Pubkey::find_program_address(&[MAYHEM_SOL_VAULT_SEED], &PUBKEY_MAYHEM_PROGRAM).0);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlobalAccount {
@@ -66,3 +75,33 @@ impl CreateInfo {
         }
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CreateV2Info {
+    pub discriminator: u64,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub creator: Pubkey,
+    pub is_mayhem_mode: bool,
+}
+
+impl CreateV2Info {
+    pub fn from_create(create: &Create, creator: Pubkey, is_mayhem_mode: bool) -> Self {
+        Self {
+            discriminator: 12984312444788445398,
+            name: create.name.to_string(),
+            symbol: create.symbol.to_string(),
+            uri: create.uri.to_string(),
+            creator,
+            is_mayhem_mode,
+        }
+    }
+}
+
+pub struct LaunchBundle {
+    pub mint: Pubkey,
+    pub dev_token_amount: u64,
+    pub token_amounts: Vec<(Pubkey, u64)>,
+    pub submissions: Vec<BundleSubmission>,
+}
@@ -1,28 +1,47 @@
 use super::{
+    amm_calc::{amm_deposit_get_amounts_in, amm_deposit_get_lp_out, amm_withdraw_get_amounts_out, calculate_with_slippage_buy, calculate_with_slippage_sell},
     dex_traits::DexTrait,
     pumpfun_common_types::{BuyInfo, SellInfo},
     pumpfun_types::PUBKEY_PUMPFUN,
     pumpswap_types::*,
-    types::{Create, SwapInfo},
+    types::{Create, SwapInfo, TokenAmountType},
 };
 use crate::{
-    common::{accounts::PUBKEY_WSOL, trading_endpoint::TradingEndpoint},
-    instruction::builder::PriorityFee,
+    common::{accounts::PUBKEY_WSOL, signer::TxSigner, trading_endpoint::TradingEndpoint},
+    instruction::builder::{build_close_wsol_instruction, build_wrap_sol_instructions, PriorityFee},
 };
 use once_cell::sync::OnceCell;
 use rand::seq::IndexedRandom;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
 use solana_sdk::{
+    account::Account,
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
+    signature::Signature,
+};
+use spl_associated_token_account::{get_associated_token_address_with_program_id, instruction::create_associated_token_account_idempotent};
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
 };
-use spl_associated_token_account::get_associated_token_address;
-use std::{str::FromStr, sync::Arc};
+
+// How long a mint without any PumpSwap pool is answered from `pool_misses` before being scanned again
+pub const POOL_MISS_TTL: Duration = Duration::from_secs(60);
 
 pub struct PumpSwap {
     pub endpoint: Arc<TradingEndpoint>,
     pub global_account: OnceCell<Arc<GlobalAccount>>,
+    // Loaded pools by pool address, instructions are built against the pool of the quoted `PoolInfo::extra_address`
+    pub pools: RwLock<HashMap<Pubkey, Arc<PoolInfo>>>,
+    // Pools chosen with `get_pool_by_address`, which `get_pool` keeps using for their base mint
+    pub selected_pools: RwLock<HashMap<Pubkey, Pubkey>>,
+    // Mints whose pool scan found no pool, with the time of the scan
+    pub pool_misses: RwLock<HashMap<Pubkey, Instant>>,
 }
 
 #[async_trait::async_trait]
@@ -51,113 +70,135 @@ impl DexTrait for PumpSwap {
         true
     }
 
-    async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<super::types::PoolInfo> {
-        let pool = Self::get_pool_address(mint)?;
-        let pool_base = get_associated_token_address(&pool, &mint);
-        let pool_quote = get_associated_token_address(&pool, &PUBKEY_WSOL);
-        let (pool_account, pool_base_account, pool_quote_account) = tokio::try_join!(
-            self.endpoint.rpc.get_account(&pool),
-            self.endpoint.rpc.get_token_account(&pool_base),
-            self.endpoint.rpc.get_token_account(&pool_quote),
-        )?;
+    fn get_buy_fee_basis_points(&self, _pool_info: &super::types::PoolInfo) -> u64 {
+        self.global_account.get().map_or(0, |global_account| {
+            global_account.lp_fee_basis_points + global_account.protocol_fee_basis_points
+        })
+    }
 
-        if pool_account.data.is_empty() {
-            return Err(anyhow::anyhow!("Pool account not found: {}", mint.to_string()));
-        }
+    fn get_quote_mint(&self, mint: &Pubkey, pool: Option<&Pubkey>) -> Pubkey {
+        self.get_pool_keys(mint, pool).map(|keys| keys.quote_mint).unwrap_or(PUBKEY_WSOL)
+    }
 
-        let pool_account = bincode::deserialize::<PoolAccount>(&pool_account.data)?;
-        let pool_base_account = pool_base_account.ok_or_else(|| anyhow::anyhow!("Pool base account not found: {}", mint.to_string()))?;
-        let pool_quote_account = pool_quote_account.ok_or_else(|| anyhow::anyhow!("Pool quote account not found: {}", mint.to_string()))?;
+    // The selected pool of `mint`, else its derived canonical pool. Only mints without one are scanned for their deepest pool,
+    // and a scan finding nothing is not repeated within `POOL_MISS_TTL`.
+    async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<super::types::PoolInfo> {
+        let selected = self.selected_pools.read().unwrap().get(mint).copied();
+        if let Some(pool) = selected {
+            let pool_info = self.load_pool(&pool).await?;
+            return self.select_pool(pool_info).await;
+        }
 
-        let pool_base_reserve = u64::from_str(&pool_base_account.token_amount.amount)?;
-        let pool_quote_reserve = u64::from_str(&pool_quote_account.token_amount.amount)?;
-        let creator_vault = Self::get_creator_vault(&pool_account.coin_creator)?;
+        if let Some(pool_info) = self.get_canonical_pool(mint).await? {
+            return self.select_pool(pool_info).await;
+        }
 
-        Ok(super::types::PoolInfo {
-            pool,
-            creator: Some(pool_account.coin_creator),
-            creator_vault: Some(creator_vault),
-            config: None,
-            extra_address: Some(creator_vault),
-            token_reserves: pool_base_reserve,
-            sol_reserves: pool_quote_reserve,
-        })
+        let missed = self
+            .pool_misses
+            .read()
+            .unwrap()
+            .get(mint)
+            .is_some_and(|scanned_at| scanned_at.elapsed() < POOL_MISS_TTL);
+        if missed {
+            return Err(anyhow::anyhow!("Pool account not found: {}", mint));
+        }
+        // Only a scan that succeeded is cached, a failed RPC call is tried again on the next lookup
+        let Some(pool_info) = self.get_deepest_pool(mint, None).await? else {
+            self.pool_misses.write().unwrap().insert(*mint, Instant::now());
+            return Err(anyhow::anyhow!("Pool account not found: {}", mint));
+        };
+        self.pool_misses.write().unwrap().remove(mint);
+        self.select_pool(pool_info).await
     }
 
-    async fn create(&self, _: Keypair, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
         Err(anyhow::anyhow!("Not supported"))
     }
 
-    fn build_buy_instruction(&self, payer: &Keypair, mint: &Pubkey, creator_vault: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
         let buy_info: BuyInfo = buy.into();
         let buffer = buy_info.to_buffer()?;
-        let pool = Self::get_pool_address(&mint)?;
-        let creator_vault = creator_vault.ok_or(anyhow::anyhow!("Creator vault is required for buy instruction"))?;
-        let creator_vault_ata = get_associated_token_address(creator_vault, &PUBKEY_WSOL);
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+        let keys = self.get_pool_keys(mint, pool)?;
+        let quote_token_program = self.endpoint.get_cached_token_program(&keys.quote_mint)?;
+        let creator_vault_ata = get_associated_token_address_with_program_id(&keys.creator_vault, &keys.quote_mint, &quote_token_program);
         let fee_recipient = self.global_account.get().unwrap().protocol_fee_recipients.choose(&mut rand::rng()).unwrap();
 
         Ok(Instruction::new_with_bytes(
             PUBKEY_PUMPSWAP,
             &buffer,
             vec![
-                AccountMeta::new_readonly(pool, false),
+                AccountMeta::new_readonly(keys.pool, false),
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(PUBKEY_GLOBAL_ACCOUNT, false),
                 AccountMeta::new_readonly(*mint, false),
-                AccountMeta::new_readonly(PUBKEY_WSOL, false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL), false),
-                AccountMeta::new(get_associated_token_address(&pool, mint), false),
-                AccountMeta::new(get_associated_token_address(&pool, &PUBKEY_WSOL), false),
+                AccountMeta::new_readonly(keys.quote_mint, false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(&payer.pubkey(), &keys.quote_mint, &quote_token_program),
+                    false,
+                ),
+                AccountMeta::new(keys.pool_base_token_account, false),
+                AccountMeta::new(keys.pool_quote_token_account, false),
                 AccountMeta::new_readonly(*fee_recipient, false),
-                AccountMeta::new(get_associated_token_address(fee_recipient, &PUBKEY_WSOL), false),
-                AccountMeta::new_readonly(spl_token::ID, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(fee_recipient, &keys.quote_mint, &quote_token_program),
+                    false,
+                ),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(quote_token_program, false),
                 AccountMeta::new_readonly(solana_program::system_program::ID, false),
                 AccountMeta::new_readonly(spl_associated_token_account::ID, false),
                 AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
                 AccountMeta::new_readonly(PUBKEY_PUMPSWAP, false),
                 AccountMeta::new(creator_vault_ata, false),
-                AccountMeta::new_readonly(*creator_vault, false),
+                AccountMeta::new_readonly(keys.creator_vault, false),
             ],
         ))
     }
 
-    fn build_sell_instruction(&self, payer: &Keypair, mint: &Pubkey, creator_vault: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
         let sell_info: SellInfo = sell.into();
         let buffer = sell_info.to_buffer()?;
-        let pool = Self::get_pool_address(&mint)?;
-        let creator_vault = creator_vault.ok_or(anyhow::anyhow!("Creator vault is required for buy instruction"))?;
-        let creator_vault_ata = get_associated_token_address(creator_vault, &PUBKEY_WSOL);
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+        let keys = self.get_pool_keys(mint, pool)?;
+        let quote_token_program = self.endpoint.get_cached_token_program(&keys.quote_mint)?;
+        let creator_vault_ata = get_associated_token_address_with_program_id(&keys.creator_vault, &keys.quote_mint, &quote_token_program);
         let fee_recipient = self.global_account.get().unwrap().protocol_fee_recipients.choose(&mut rand::rng()).unwrap();
 
         Ok(Instruction::new_with_bytes(
             PUBKEY_PUMPSWAP,
             &buffer,
             vec![
-                AccountMeta::new_readonly(pool, false),
+                AccountMeta::new_readonly(keys.pool, false),
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(PUBKEY_GLOBAL_ACCOUNT, false),
                 AccountMeta::new_readonly(*mint, false),
-                AccountMeta::new_readonly(PUBKEY_WSOL, false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL), false),
-                AccountMeta::new(get_associated_token_address(&pool, mint), false),
-                AccountMeta::new(get_associated_token_address(&pool, &PUBKEY_WSOL), false),
+                AccountMeta::new_readonly(keys.quote_mint, false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(&payer.pubkey(), &keys.quote_mint, &quote_token_program),
+                    false,
+                ),
+                AccountMeta::new(keys.pool_base_token_account, false),
+                AccountMeta::new(keys.pool_quote_token_account, false),
                 AccountMeta::new_readonly(*fee_recipient, false),
-                AccountMeta::new(get_associated_token_address(fee_recipient, &PUBKEY_WSOL), false),
-                AccountMeta::new_readonly(spl_token::ID, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(fee_recipient, &keys.quote_mint, &quote_token_program),
+                    false,
+                ),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(quote_token_program, false),
                 AccountMeta::new_readonly(solana_program::system_program::ID, false),
                 AccountMeta::new_readonly(spl_associated_token_account::ID, false),
                 AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
                 AccountMeta::new_readonly(PUBKEY_PUMPSWAP, false),
                 AccountMeta::new(creator_vault_ata, false),
-                AccountMeta::new_readonly(*creator_vault, false),
+                AccountMeta::new_readonly(keys.creator_vault, false),
             ],
         ))
     }
@@ -168,15 +209,242 @@ impl PumpSwap {
         Self {
             endpoint,
             global_account: OnceCell::new(),
+            pools: RwLock::new(HashMap::new()),
+            selected_pools: RwLock::new(HashMap::new()),
+            pool_misses: RwLock::new(HashMap::new()),
         }
     }
 
+    // Pool accounts taken from the loaded `pool`, the canonical pool of `mint` when `pool` is not given
+    pub fn get_pool_keys(&self, mint: &Pubkey, pool: Option<&Pubkey>) -> anyhow::Result<PoolKeys> {
+        let pool = match pool {
+            Some(pool) => *pool,
+            None => Self::get_pool_address(mint)?,
+        };
+
+        let pool_info = self.get_loaded_pool(&pool)?;
+        let pool_account = &pool_info.pool_account;
+        Ok(PoolKeys {
+            pool,
+            quote_mint: pool_account.quote_mint,
+            pool_base_token_account: pool_account.pool_base_token_account,
+            pool_quote_token_account: pool_account.pool_quote_token_account,
+            creator_vault: Self::get_creator_vault(&pool_account.coin_creator)?,
+        })
+    }
+
+    // Fetches any PumpSwap pool and makes `get_pool` trade it for its base mint, until `clear_selected_pool`
+    pub async fn get_pool_by_address(&self, pool: &Pubkey) -> anyhow::Result<super::types::PoolInfo> {
+        let pool_info = self.load_pool(pool).await?;
+        self.selected_pools.write().unwrap().insert(pool_info.pool_account.base_mint, *pool);
+        self.select_pool(pool_info).await
+    }
+
+    pub fn clear_selected_pool(&self, mint: &Pubkey) {
+        self.selected_pools.write().unwrap().remove(mint);
+    }
+
+    // Loads the pool picked by `selector` for building instructions, without changing what `get_pool` picks next time
+    pub async fn get_pool_with_selector(&self, mint: &Pubkey, selector: PoolSelector) -> anyhow::Result<super::types::PoolInfo> {
+        let pool_info = match selector {
+            PoolSelector::Canonical => self
+                .get_canonical_pool(mint)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Pool account not found: {}", mint))?,
+            PoolSelector::DeepestLiquidity => self
+                .get_deepest_pool(mint, None)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Pool account not found: {}", mint))?,
+            PoolSelector::DeepestLiquidityWithQuote(quote_mint) => self
+                .get_deepest_pool(mint, Some(&quote_mint))
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Pool account not found: {} / {}", mint, quote_mint))?,
+        };
+
+        self.select_pool(pool_info).await
+    }
+
+    // The pool of `mint` with the most base reserve, optionally against `quote_mint` only. `None` when the scan finds none.
+    async fn get_deepest_pool(&self, mint: &Pubkey, quote_mint: Option<&Pubkey>) -> anyhow::Result<Option<PoolInfo>> {
+        Ok(self
+            .get_pools_by_mint(mint)
+            .await?
+            .into_iter()
+            .filter(|pool_info| quote_mint.is_none_or(|quote_mint| pool_info.pool_account.quote_mint == *quote_mint))
+            .max_by_key(|pool_info| pool_info.pool_base_reserve))
+    }
+
+    async fn load_pool(&self, pool: &Pubkey) -> anyhow::Result<PoolInfo> {
+        let account = self.endpoint.rpc.get_account(pool).await?;
+        let pool_account = bincode::deserialize::<PoolAccount>(&account.data)?;
+        Ok(self.load_pools(vec![(*pool, pool_account)]).await?.remove(0))
+    }
+
+    // The Pumpfun-migrated pool of `mint`, `None` when that account does not exist
+    async fn get_canonical_pool(&self, mint: &Pubkey) -> anyhow::Result<Option<PoolInfo>> {
+        let pool = Self::get_pool_address(mint)?;
+        let account = self
+            .endpoint
+            .rpc
+            .get_account_with_commitment(&pool, self.endpoint.rpc.commitment())
+            .await?
+            .value;
+        let Some(account) = account.filter(|account| !account.data.is_empty()) else {
+            return Ok(None);
+        };
+
+        let pool_account = bincode::deserialize::<PoolAccount>(&account.data)?;
+        Ok(Some(self.load_pools(vec![(pool, pool_account)]).await?.remove(0)))
+    }
+
+    // Every pool where `mint` is the base side, with their current reserves
+    pub async fn get_pools_by_mint(&self, mint: &Pubkey) -> anyhow::Result<Vec<PoolInfo>> {
+        let accounts = self
+            .endpoint
+            .rpc
+            .get_program_accounts_with_config(
+                &PUBKEY_PUMPSWAP,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Bytes(POOL_ACCOUNT_DISCRIMINATOR.to_vec()))),
+                        RpcFilterType::Memcmp(Memcmp::new(POOL_BASE_MINT_OFFSET, MemcmpEncodedBytes::Bytes(mint.to_bytes().to_vec()))),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                        commitment: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    },
+                    with_context: None,
+                    sort_results: None,
+                },
+            )
+            .await?;
+
+        let pools = accounts
+            .into_iter()
+            .map(|(pool, account)| Ok((pool, bincode::deserialize::<PoolAccount>(&account.data)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        self.load_pools(pools).await
+    }
+
+    async fn load_pools(&self, pools: Vec<(Pubkey, PoolAccount)>) -> anyhow::Result<Vec<PoolInfo>> {
+        let vaults = pools
+            .iter()
+            .flat_map(|(_, pool_account)| [pool_account.pool_base_token_account, pool_account.pool_quote_token_account])
+            .collect::<Vec<_>>();
+        let mut vault_accounts = vec![];
+        for chunk in vaults.chunks(100) {
+            vault_accounts.extend(self.endpoint.rpc.get_multiple_accounts(chunk).await?);
+        }
+
+        pools
+            .into_iter()
+            .zip(vault_accounts.chunks(2))
+            .map(|((pool_address, pool_account), vaults)| {
+                let reserve = |vault: &Option<Account>| -> anyhow::Result<u64> {
+                    let vault = vault.as_ref().ok_or_else(|| anyhow::anyhow!("Pool vault not found: {}", pool_address))?;
+                    Ok(StateWithExtensions::<TokenAccount>::unpack(&vault.data)?.base.amount)
+                };
+                Ok(PoolInfo {
+                    pool_base_reserve: reserve(&vaults[0])?,
+                    pool_quote_reserve: reserve(&vaults[1])?,
+                    pool_address,
+                    pool_account,
+                })
+            })
+            .collect()
+    }
+
+    async fn select_pool(&self, pool_info: PoolInfo) -> anyhow::Result<super::types::PoolInfo> {
+        let pool_account = &pool_info.pool_account;
+        tokio::try_join!(
+            self.endpoint.get_mint_info(&pool_account.base_mint),
+            self.endpoint.get_mint_info(&pool_account.quote_mint),
+        )?;
+        let creator_vault = Self::get_creator_vault(&pool_account.coin_creator)?;
+        let result = super::types::PoolInfo {
+            pool: pool_info.pool_address,
+            creator: Some(pool_account.coin_creator),
+            creator_vault: Some(creator_vault),
+            config: None,
+            extra_address: Some(pool_info.pool_address),
+            token_reserves: pool_info.pool_base_reserve,
+            sol_reserves: pool_info.pool_quote_reserve,
+            complete: false,
+        };
+        self.pools.write().unwrap().insert(pool_info.pool_address, Arc::new(pool_info));
+
+        Ok(result)
+    }
+
     pub fn get_creator_vault(creator: &Pubkey) -> anyhow::Result<Pubkey> {
         let pda = Pubkey::try_find_program_address(&[b"creator_vault", creator.as_ref()], &PUBKEY_PUMPSWAP)
             .ok_or_else(|| anyhow::anyhow!("Failed to find creator vault PDA"))?;
         Ok(pda.0)
     }
 
+    // `quote_mint` accrued in the creator vault ATA, the vault holds one per quote mint of the creator's pools
+    pub async fn get_creator_fees(&self, creator: &Pubkey, quote_mint: &Pubkey) -> anyhow::Result<u64> {
+        let quote_token_program = self.endpoint.get_token_program(quote_mint).await?;
+        let creator_vault_ata = get_associated_token_address_with_program_id(&Self::get_creator_vault(creator)?, quote_mint, &quote_token_program);
+        match self.endpoint.rpc.get_token_account(&creator_vault_ata).await? {
+            Some(account) => Ok(account.token_amount.amount.parse::<u64>()?),
+            None => Ok(0),
+        }
+    }
+
+    // Claims the `quote_mint` creator fees, WSOL is unwrapped to SOL
+    pub async fn claim_creator_fees(
+        &self,
+        creator: &dyn TxSigner,
+        quote_mint: &Pubkey,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<Vec<Signature>> {
+        let creator_pubkey = creator.pubkey();
+        let (fees, blockhash, quote_token_program) = tokio::try_join!(
+            self.get_creator_fees(&creator_pubkey, quote_mint),
+            self.endpoint.get_latest_blockhash(),
+            self.endpoint.get_token_program(quote_mint)
+        )?;
+        if fees == 0 {
+            return Err(anyhow::anyhow!("No creator fees to claim: {} / {}", creator_pubkey, quote_mint));
+        }
+
+        let mut instructions = vec![
+            create_associated_token_account_idempotent(&creator_pubkey, &creator_pubkey, quote_mint, &quote_token_program),
+            Self::build_collect_coin_creator_fee_instruction(&creator_pubkey, quote_mint, &quote_token_program)?,
+        ];
+        if *quote_mint == PUBKEY_WSOL {
+            instructions.push(build_close_wsol_instruction(creator)?);
+        }
+        self.endpoint.build_and_broadcast_tx(creator, instructions, blockhash, fee, tip, None)
+    }
+
+    pub fn build_collect_coin_creator_fee_instruction(creator: &Pubkey, quote_mint: &Pubkey, quote_token_program: &Pubkey) -> anyhow::Result<Instruction> {
+        let creator_vault = Self::get_creator_vault(creator)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_PUMPSWAP,
+            &COLLECT_COIN_CREATOR_FEE_DISCRIMINATOR.to_le_bytes(),
+            vec![
+                AccountMeta::new_readonly(*quote_mint, false),
+                AccountMeta::new_readonly(*quote_token_program, false),
+                AccountMeta::new(*creator, true),
+                AccountMeta::new_readonly(creator_vault, false),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(&creator_vault, quote_mint, quote_token_program),
+                    false,
+                ),
+                AccountMeta::new(get_associated_token_address_with_program_id(creator, quote_mint, quote_token_program), false),
+                AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
+                AccountMeta::new_readonly(PUBKEY_PUMPSWAP, false),
+            ],
+        ))
+    }
+
     pub fn get_pool_authority_pda(mint: &Pubkey) -> anyhow::Result<Pubkey> {
         let pda = Pubkey::try_find_program_address(&[b"pool-authority", mint.as_ref()], &PUBKEY_PUMPFUN)
             .ok_or_else(|| anyhow::anyhow!("Failed to find pool authority PDA"))?;
@@ -184,17 +452,285 @@ impl PumpSwap {
     }
 
     pub fn get_pool_address(mint: &Pubkey) -> anyhow::Result<Pubkey> {
+        Self::get_pool_pda(0, &Self::get_pool_authority_pda(mint)?, mint, &PUBKEY_WSOL)
+    }
+
+    pub fn get_pool_pda(index: u16, creator: &Pubkey, base_mint: &Pubkey, quote_mint: &Pubkey) -> anyhow::Result<Pubkey> {
         let pda = Pubkey::try_find_program_address(
-            &[
-                b"pool",
-                &0u16.to_le_bytes(),
-                Self::get_pool_authority_pda(mint)?.as_ref(),
-                mint.as_ref(),
-                PUBKEY_WSOL.as_ref(),
-            ],
+            &[b"pool", &index.to_le_bytes(), creator.as_ref(), base_mint.as_ref(), quote_mint.as_ref()],
             &PUBKEY_PUMPSWAP,
         )
         .ok_or_else(|| anyhow::anyhow!("Failed to find pool address PDA"))?;
         Ok(pda.0)
     }
+
+    pub fn get_lp_mint_pda(pool: &Pubkey) -> anyhow::Result<Pubkey> {
+        let pda = Pubkey::try_find_program_address(&[b"pool_lp_mint", pool.as_ref()], &PUBKEY_PUMPSWAP)
+            .ok_or_else(|| anyhow::anyhow!("Failed to find pool lp mint PDA"))?;
+        Ok(pda.0)
+    }
+
+    pub fn get_loaded_pool(&self, pool: &Pubkey) -> anyhow::Result<Arc<PoolInfo>> {
+        self.pools
+            .read()
+            .unwrap()
+            .get(pool)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Pool not loaded, call get_pool first: {}", pool))
+    }
+
+    // LP tokens minted for `base_amount` at the current pool ratio, with the base and quote amounts it takes
+    pub fn quote_deposit(pool_info: &PoolInfo, base_amount: u64) -> (u64, u64, u64) {
+        let lp_amount = amm_deposit_get_lp_out(base_amount, pool_info.pool_base_reserve, pool_info.pool_account.lp_supply);
+        let (base_amount, quote_amount) = amm_deposit_get_amounts_in(
+            lp_amount,
+            pool_info.pool_base_reserve,
+            pool_info.pool_quote_reserve,
+            pool_info.pool_account.lp_supply,
+        );
+        (lp_amount, base_amount, quote_amount)
+    }
+
+    pub fn quote_withdraw(pool_info: &PoolInfo, lp_amount: u64) -> (u64, u64) {
+        amm_withdraw_get_amounts_out(
+            lp_amount,
+            pool_info.pool_base_reserve,
+            pool_info.pool_quote_reserve,
+            pool_info.pool_account.lp_supply,
+        )
+    }
+
+    pub async fn deposit(
+        &self,
+        payer: &dyn TxSigner,
+        mint: &Pubkey,
+        base_amount: u64,
+        slippage_basis_points: u64,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<Vec<Signature>> {
+        self.initialized()?;
+
+        let (pool, blockhash) = tokio::try_join!(self.get_pool(mint), self.endpoint.get_latest_blockhash())?;
+        let pool_info = self.get_loaded_pool(&pool.pool)?;
+        let (lp_amount, base_amount, quote_amount) = Self::quote_deposit(&pool_info, base_amount);
+        if lp_amount == 0 {
+            return Err(anyhow::anyhow!("Deposit amount too small: {}", mint));
+        }
+
+        let max_base_amount = calculate_with_slippage_buy(base_amount, slippage_basis_points);
+        let max_quote_amount = calculate_with_slippage_buy(quote_amount, slippage_basis_points);
+        let sol_amount = if pool_info.pool_account.quote_mint == PUBKEY_WSOL {
+            max_quote_amount
+        } else {
+            0
+        };
+        self.endpoint.risk.check_slippage(slippage_basis_points)?;
+        let instruction = self.build_deposit_instruction(payer, &pool_info, DepositInfo::new(lp_amount, max_base_amount, max_quote_amount))?;
+        let pool_account = &pool_info.pool_account;
+
+        let mut instructions = vec![create_associated_token_account_idempotent(
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &pool_account.lp_mint,
+            &spl_token_2022::ID,
+        )];
+        if pool_account.quote_mint == PUBKEY_WSOL {
+            instructions.extend(build_wrap_sol_instructions(payer, max_quote_amount)?);
+            instructions.push(instruction);
+            instructions.push(build_close_wsol_instruction(payer)?);
+        } else {
+            instructions.push(instruction);
+        }
+
+        let reservation = self.endpoint.risk.check_trade(&payer.pubkey(), mint, sol_amount)?;
+        let result = self.endpoint.build_and_broadcast_tx(payer, instructions, blockhash, fee, tip, None);
+        self.endpoint.risk.settle(reservation, result)
+    }
+
+    pub async fn withdraw(
+        &self,
+        payer: &dyn TxSigner,
+        mint: &Pubkey,
+        lp_amount: TokenAmountType,
+        slippage_basis_points: u64,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<Vec<Signature>> {
+        self.initialized()?;
+
+        let (pool, blockhash) = tokio::try_join!(self.get_pool(mint), self.endpoint.get_latest_blockhash())?;
+        let pool_info = self.get_loaded_pool(&pool.pool)?;
+        let pool_account = &pool_info.pool_account;
+        let lp_amount = lp_amount
+            .to_amount(self.endpoint.rpc.clone(), &payer.pubkey(), &pool_account.lp_mint, &spl_token_2022::ID)
+            .await?;
+        let (base_amount, quote_amount) = Self::quote_withdraw(&pool_info, lp_amount);
+        if base_amount == 0 && quote_amount == 0 {
+            return Err(anyhow::anyhow!("Withdraw amount too small: {}", mint));
+        }
+
+        let min_base_amount = calculate_with_slippage_sell(base_amount, slippage_basis_points);
+        let min_quote_amount = calculate_with_slippage_sell(quote_amount, slippage_basis_points);
+        self.endpoint.risk.check_slippage(slippage_basis_points)?;
+        let instruction = self.build_withdraw_instruction(payer, &pool_info, WithdrawInfo::new(lp_amount, min_base_amount, min_quote_amount))?;
+        let base_token_program = self.endpoint.get_cached_token_program(&pool_account.base_mint)?;
+        let quote_token_program = self.endpoint.get_cached_token_program(&pool_account.quote_mint)?;
+
+        let mut instructions = vec![
+            create_associated_token_account_idempotent(&payer.pubkey(), &payer.pubkey(), &pool_account.base_mint, &base_token_program),
+            create_associated_token_account_idempotent(&payer.pubkey(), &payer.pubkey(), &pool_account.quote_mint, &quote_token_program),
+            instruction,
+        ];
+        if pool_account.quote_mint == PUBKEY_WSOL {
+            instructions.push(build_close_wsol_instruction(payer)?);
+        }
+
+        let reservation = self.endpoint.risk.check_trade(&payer.pubkey(), mint, 0)?;
+        let result = self.endpoint.build_and_broadcast_tx(payer, instructions, blockhash, fee, tip, None);
+        self.endpoint.risk.settle(reservation, result)
+    }
+
+    // Creates the pool with `payer` as both pool creator and coin creator
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_pool(
+        &self,
+        payer: &dyn TxSigner,
+        index: u16,
+        base_mint: &Pubkey,
+        quote_mint: &Pubkey,
+        base_amount: u64,
+        quote_amount: u64,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<Vec<Signature>> {
+        let sol_amount = if *quote_mint == PUBKEY_WSOL { quote_amount } else { 0 };
+        let reservation = self.endpoint.risk.check_trade(&payer.pubkey(), base_mint, sol_amount)?;
+
+        let result = self
+            .build_create_pool_tx(payer, index, base_mint, quote_mint, base_amount, quote_amount, fee, tip)
+            .await;
+        self.endpoint.risk.settle(reservation, result)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn build_create_pool_tx(
+        &self,
+        payer: &dyn TxSigner,
+        index: u16,
+        base_mint: &Pubkey,
+        quote_mint: &Pubkey,
+        base_amount: u64,
+        quote_amount: u64,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<Vec<Signature>> {
+        let (blockhash, base_mint_info, quote_mint_info) = tokio::try_join!(
+            self.endpoint.get_latest_blockhash(),
+            self.endpoint.get_mint_info(base_mint),
+            self.endpoint.get_mint_info(quote_mint),
+        )?;
+
+        let buffer = CreatePoolInfo::new(index, base_amount, quote_amount, payer.pubkey()).to_buffer()?;
+        let base_token_program = base_mint_info.token_program;
+        let quote_token_program = quote_mint_info.token_program;
+        let pool = Self::get_pool_pda(index, &payer.pubkey(), base_mint, quote_mint)?;
+        let lp_mint = Self::get_lp_mint_pda(&pool)?;
+
+        let instruction = Instruction::new_with_bytes(
+            PUBKEY_PUMPSWAP,
+            &buffer,
+            vec![
+                AccountMeta::new(pool, false),
+                AccountMeta::new_readonly(PUBKEY_GLOBAL_ACCOUNT, false),
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(*base_mint, false),
+                AccountMeta::new_readonly(*quote_mint, false),
+                AccountMeta::new(lp_mint, false),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(&payer.pubkey(), base_mint, &base_token_program),
+                    false,
+                ),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(&payer.pubkey(), quote_mint, &quote_token_program),
+                    false,
+                ),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(&payer.pubkey(), &lp_mint, &spl_token_2022::ID),
+                    false,
+                ),
+                AccountMeta::new(get_associated_token_address_with_program_id(&pool, base_mint, &base_token_program), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&pool, quote_mint, &quote_token_program), false),
+                AccountMeta::new_readonly(solana_program::system_program::ID, false),
+                AccountMeta::new_readonly(spl_token_2022::ID, false),
+                AccountMeta::new_readonly(base_token_program, false),
+                AccountMeta::new_readonly(quote_token_program, false),
+                AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+                AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
+                AccountMeta::new_readonly(PUBKEY_PUMPSWAP, false),
+            ],
+        );
+
+        let instructions = if *quote_mint == PUBKEY_WSOL {
+            let mut instructions = build_wrap_sol_instructions(payer, quote_amount)?;
+            instructions.push(instruction);
+            instructions.push(build_close_wsol_instruction(payer)?);
+            instructions
+        } else {
+            vec![instruction]
+        };
+
+        self.endpoint.build_and_broadcast_tx(payer, instructions, blockhash, fee, tip, None)
+    }
+
+    pub fn build_deposit_instruction(&self, payer: &dyn TxSigner, pool_info: &PoolInfo, deposit: DepositInfo) -> anyhow::Result<Instruction> {
+        let buffer = deposit.to_buffer()?;
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_PUMPSWAP,
+            &buffer,
+            self.build_liquidity_accounts(payer, pool_info)?,
+        ))
+    }
+
+    pub fn build_withdraw_instruction(&self, payer: &dyn TxSigner, pool_info: &PoolInfo, withdraw: WithdrawInfo) -> anyhow::Result<Instruction> {
+        let buffer = withdraw.to_buffer()?;
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_PUMPSWAP,
+            &buffer,
+            self.build_liquidity_accounts(payer, pool_info)?,
+        ))
+    }
+
+    fn build_liquidity_accounts(&self, payer: &dyn TxSigner, pool_info: &PoolInfo) -> anyhow::Result<Vec<AccountMeta>> {
+        let pool_account = &pool_info.pool_account;
+        let base_token_program = self.endpoint.get_cached_token_program(&pool_account.base_mint)?;
+        let quote_token_program = self.endpoint.get_cached_token_program(&pool_account.quote_mint)?;
+
+        Ok(vec![
+            AccountMeta::new(pool_info.pool_address, false),
+            AccountMeta::new_readonly(PUBKEY_GLOBAL_ACCOUNT, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+            AccountMeta::new_readonly(pool_account.base_mint, false),
+            AccountMeta::new_readonly(pool_account.quote_mint, false),
+            AccountMeta::new(pool_account.lp_mint, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(&payer.pubkey(), &pool_account.base_mint, &base_token_program),
+                false,
+            ),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(&payer.pubkey(), &pool_account.quote_mint, &quote_token_program),
+                false,
+            ),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(&payer.pubkey(), &pool_account.lp_mint, &spl_token_2022::ID),
+                false,
+            ),
+            AccountMeta::new(pool_account.pool_base_token_account, false),
+            AccountMeta::new(pool_account.pool_quote_token_account, false),
+            AccountMeta::new_readonly(base_token_program, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+            AccountMeta::new_readonly(PUBKEY_EVENT_AUTHORITY, false),
+            AccountMeta::new_readonly(PUBKEY_PUMPSWAP, false),
+        ])
+    }
 }
@@ -6,6 +6,11 @@ pub const PUBKEY_PUMPSWAP: Pubkey = pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn
 pub const PUBKEY_GLOBAL_ACCOUNT: Pubkey = pubkey!("ADyA8hdefvWN2dbGGWFotbzWxrAvLW83WG6QCVXvJKqw");
 pub const PUBKEY_EVENT_AUTHORITY: Pubkey = pubkey!("GS4CU59F31iL7aR2Q8zVS8DRrcRnXX1yjQ66TqNVQnaR");
 
+pub const COLLECT_COIN_CREATOR_FEE_DISCRIMINATOR: u64 = 4768058240717633952;
+pub const POOL_ACCOUNT_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+pub const POOL_BASE_MINT_OFFSET: usize = 43;
+pub const POOL_QUOTE_MINT_OFFSET: usize = 75;
+
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub struct GlobalAccount {
     pub discriminator: u64,
@@ -30,9 +35,108 @@ pub struct PoolAccount {
     pub lp_supply: u64,
     pub coin_creator: Pubkey,
 }
+
+#[derive(Debug, Clone)]
 pub struct PoolInfo {
     pub pool_address: Pubkey,
     pub pool_account: PoolAccount,
     pub pool_base_reserve: u64,
     pub pool_quote_reserve: u64,
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct PoolKeys {
+    pub pool: Pubkey,
+    pub quote_mint: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    pub creator_vault: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PoolSelector {
+    /// Index-0 pool created by the Pumpfun migration, quoted in WSOL
+    #[default]
+    Canonical,
+    /// Pool holding the largest base reserve among every pool of the mint
+    DeepestLiquidity,
+    /// Pool holding the largest base reserve among the pools quoted in the given mint
+    DeepestLiquidityWithQuote(Pubkey),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct DepositInfo {
+    pub discriminator: u64,
+    pub lp_token_amount_out: u64,
+    pub max_base_amount_in: u64,
+    pub max_quote_amount_in: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct WithdrawInfo {
+    pub discriminator: u64,
+    pub lp_token_amount_in: u64,
+    pub min_base_amount_out: u64,
+    pub min_quote_amount_out: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CreatePoolInfo {
+    pub discriminator: u64,
+    pub index: u16,
+    pub base_amount_in: u64,
+    pub quote_amount_in: u64,
+    pub coin_creator: Pubkey,
+}
+
+impl DepositInfo {
+    pub fn new(lp_token_amount_out: u64, max_base_amount_in: u64, max_quote_amount_in: u64) -> Self {
+        Self {
+            discriminator: 13182846803881894898,
+            lp_token_amount_out,
+            max_base_amount_in,
+            max_quote_amount_in,
+        }
+    }
+
+    pub fn to_buffer(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+impl WithdrawInfo {
+    pub fn new(lp_token_amount_in: u64, min_base_amount_out: u64, min_quote_amount_out: u64) -> Self {
+        Self {
+            discriminator: 2495396153584390839,
+            lp_token_amount_in,
+            min_base_amount_out,
+            min_quote_amount_out,
+        }
+    }
+
+    pub fn to_buffer(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+impl CreatePoolInfo {
+    pub fn new(index: u16, base_amount_in: u64, quote_amount_in: u64, coin_creator: Pubkey) -> Self {
+        Self {
+            discriminator: 13564957318303552233,
+            index,
+            base_amount_in,
+            quote_amount_in,
+            coin_creator,
+        }
+    }
+
+    pub fn to_buffer(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer)?;
+        Ok(buffer)
+    }
+}
@@ -0,0 +1,254 @@
+use super::{
+    amm_calc::{amm_amount_after_fee, amm_buy_get_token_out, amm_sell_get_sol_out},
+    dex_traits::DexTrait,
+    raydium_amm_v4_types::*,
+    types::{Create, PoolInfo, SwapInfo, SwapQuote},
+};
+use crate::{
+    common::{accounts::PUBKEY_WSOL, mint_info::MintInfo, signer::TxSigner, trading_endpoint::TradingEndpoint},
+    instruction::builder::PriorityFee,
+};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signature,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Raydium AMM v4 pools, swapped through the v2 instructions which need no OpenBook market accounts.
+// Only pools quoted in WSOL are traded, AMM v4 supports legacy SPL tokens only.
+pub struct RaydiumAmmV4 {
+    pub endpoint: Arc<TradingEndpoint>,
+    pub pools: RwLock<HashMap<Pubkey, Arc<AmmPool>>>,
+}
+
+#[async_trait::async_trait]
+impl DexTrait for RaydiumAmmV4 {
+    async fn initialize(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn initialized(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn use_wsol(&self) -> bool {
+        true
+    }
+
+    fn get_trading_endpoint(&self) -> Arc<TradingEndpoint> {
+        self.endpoint.clone()
+    }
+
+    // The deepest WSOL pool of `mint` that accepts swaps now
+    async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo> {
+        let (pools, _) = tokio::try_join!(self.get_pools_by_mint(mint), self.endpoint.get_mint_info(mint))?;
+        if pools.is_empty() {
+            return Err(anyhow::anyhow!("Pool not found: {}", mint));
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let pool = pools
+            .into_iter()
+            .filter(|pool| pool.amm.is_tradable(now))
+            .max_by_key(|pool| pool.sol_reserve)
+            .ok_or_else(|| anyhow::anyhow!("Pool swaps disabled or not open yet: {}", mint))?;
+
+        let pool_info = PoolInfo {
+            pool: pool.pool,
+            creator: None,
+            creator_vault: None,
+            config: None,
+            extra_address: Some(pool.pool),
+            token_reserves: pool.token_reserve,
+            sol_reserves: pool.sol_reserve,
+            complete: false,
+        };
+        self.pools.write().unwrap().insert(pool.pool, Arc::new(pool));
+
+        Ok(pool_info)
+    }
+
+    fn get_pool_scans(&self) -> bool {
+        true
+    }
+
+    // The swap fee is taken from the input, so the closed-form split does not apply
+    fn is_constant_product(&self) -> bool {
+        false
+    }
+
+    fn quote_buy(&self, pool_info: &PoolInfo, _mint_info: &MintInfo, sol_amount: u64) -> SwapQuote {
+        let (fee_numerator, fee_denominator) = self.get_swap_fee(&pool_info.pool);
+        let sol_in = amm_amount_after_fee(sol_amount, fee_numerator, fee_denominator);
+        SwapQuote {
+            token_amount: amm_buy_get_token_out(pool_info.sol_reserves, pool_info.token_reserves, sol_in),
+            sol_amount,
+        }
+    }
+
+    fn quote_sell(&self, pool_info: &PoolInfo, mint_info: &MintInfo, token_amount: u64) -> SwapQuote {
+        let (fee_numerator, fee_denominator) = self.get_swap_fee(&pool_info.pool);
+        let token_amount = mint_info.amount_after_transfer_fee(token_amount);
+        let token_in = amm_amount_after_fee(token_amount, fee_numerator, fee_denominator);
+        SwapQuote {
+            token_amount,
+            sol_amount: amm_sell_get_sol_out(pool_info.sol_reserves, pool_info.token_reserves, token_in),
+        }
+    }
+
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+        Err(anyhow::anyhow!("Not supported"))
+    }
+
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let swap_info: SwapBaseOutInfo = buy.into();
+        let buffer = swap_info.to_buffer()?;
+        let pool = self.get_cached_pool(mint, pool)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_RAYDIUM_AMM_V4,
+            &buffer,
+            Self::build_swap_accounts(payer, &pool, mint, false),
+        ))
+    }
+
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let swap_info: SwapBaseInInfo = sell.into();
+        let buffer = swap_info.to_buffer()?;
+        let pool = self.get_cached_pool(mint, pool)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_RAYDIUM_AMM_V4,
+            &buffer,
+            Self::build_swap_accounts(payer, &pool, mint, true),
+        ))
+    }
+}
+
+impl RaydiumAmmV4 {
+    pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
+        Self {
+            endpoint,
+            pools: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn get_cached_pool(&self, mint: &Pubkey, pool: Option<&Pubkey>) -> anyhow::Result<Arc<AmmPool>> {
+        let pool = pool.ok_or_else(|| anyhow::anyhow!("Pool address required: {}", mint))?;
+        self.pools
+            .read()
+            .unwrap()
+            .get(pool)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Pool not loaded, call get_pool first: {}", pool))
+    }
+
+    fn get_swap_fee(&self, pool: &Pubkey) -> (u64, u64) {
+        self.pools
+            .read()
+            .unwrap()
+            .get(pool)
+            .map(|pool| (pool.amm.fees.swap_fee_numerator, pool.amm.fees.swap_fee_denominator))
+            .unwrap_or((0, 1))
+    }
+
+    // Source and destination follow the swap direction: WSOL to `mint` on buys, `mint` to WSOL on sells
+    fn build_swap_accounts(payer: &dyn TxSigner, pool: &AmmPool, mint: &Pubkey, sell: bool) -> Vec<AccountMeta> {
+        let mint_ata = get_associated_token_address(&payer.pubkey(), mint);
+        let wsol_ata = get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL);
+        let (source, destination) = if sell { (mint_ata, wsol_ata) } else { (wsol_ata, mint_ata) };
+
+        vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new(pool.pool, false),
+            AccountMeta::new_readonly(PUBKEY_RAYDIUM_AMM_V4_AUTHORITY, false),
+            AccountMeta::new(pool.amm.coin_vault, false),
+            AccountMeta::new(pool.amm.pc_vault, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+        ]
+    }
+
+    async fn get_amm_accounts(&self, coin_mint: &Pubkey, pc_mint: &Pubkey) -> anyhow::Result<Vec<(Pubkey, Account)>> {
+        Ok(self
+            .endpoint
+            .rpc
+            .get_program_accounts_with_config(
+                &PUBKEY_RAYDIUM_AMM_V4,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(AMM_ACCOUNT_SIZE),
+                        RpcFilterType::Memcmp(Memcmp::new(AMM_COIN_MINT_OFFSET, MemcmpEncodedBytes::Bytes(coin_mint.to_bytes().to_vec()))),
+                        RpcFilterType::Memcmp(Memcmp::new(AMM_PC_MINT_OFFSET, MemcmpEncodedBytes::Bytes(pc_mint.to_bytes().to_vec()))),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                        commitment: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    },
+                    with_context: None,
+                    sort_results: None,
+                },
+            )
+            .await?)
+    }
+
+    // Every WSOL pool of `mint` on either side, with reserves read from the vaults less the pnl not yet taken
+    pub async fn get_pools_by_mint(&self, mint: &Pubkey) -> anyhow::Result<Vec<AmmPool>> {
+        let (coin_pools, pc_pools) = tokio::try_join!(self.get_amm_accounts(mint, &PUBKEY_WSOL), self.get_amm_accounts(&PUBKEY_WSOL, mint))?;
+        let amms = coin_pools
+            .into_iter()
+            .map(|pool| (pool, true))
+            .chain(pc_pools.into_iter().map(|pool| (pool, false)))
+            .map(|((pool, account), mint_is_coin)| Ok((pool, bincode::deserialize::<AmmInfo>(&account.data)?, mint_is_coin)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let vaults = amms.iter().flat_map(|(_, amm, _)| [amm.coin_vault, amm.pc_vault]).collect::<Vec<_>>();
+        let mut vault_accounts = vec![];
+        for chunk in vaults.chunks(100) {
+            vault_accounts.extend(self.endpoint.rpc.get_multiple_accounts(chunk).await?);
+        }
+
+        amms.into_iter()
+            .zip(vault_accounts.chunks(2))
+            .map(|((pool, amm, mint_is_coin), vaults)| {
+                let reserve = |vault: &Option<Account>| -> anyhow::Result<u64> {
+                    let vault = vault.as_ref().ok_or_else(|| anyhow::anyhow!("Pool vault not found: {}", pool))?;
+                    Ok(StateWithExtensions::<TokenAccount>::unpack(&vault.data)?.base.amount)
+                };
+                let coin_reserve = reserve(&vaults[0])?.saturating_sub(amm.state_data.need_take_pnl_coin);
+                let pc_reserve = reserve(&vaults[1])?.saturating_sub(amm.state_data.need_take_pnl_pc);
+                let (token_reserve, sol_reserve) = if mint_is_coin {
+                    (coin_reserve, pc_reserve)
+                } else {
+                    (pc_reserve, coin_reserve)
+                };
+
+                Ok(AmmPool {
+                    pool,
+                    amm,
+                    mint_is_coin,
+                    token_reserve,
+                    sol_reserve,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,175 @@
+use super::types::SwapInfo;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Deserialize;
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const PUBKEY_RAYDIUM_AMM_V4: Pubkey = pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+pub const PUBKEY_RAYDIUM_AMM_V4_AUTHORITY: Pubkey = pubkey!("5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1");
+
+pub const AMM_COIN_MINT_OFFSET: usize = 400;
+pub const AMM_PC_MINT_OFFSET: usize = 432;
+pub const AMM_ACCOUNT_SIZE: u64 = 752;
+
+// `AmmInfo::status` values that accept swaps, `WaitingTrade` only once `StateData::pool_open_time` is reached
+pub const AMM_STATUS_INITIALIZED: u64 = 1;
+pub const AMM_STATUS_SWAP_ONLY: u64 = 6;
+pub const AMM_STATUS_WAITING_TRADE: u64 = 7;
+
+pub const SWAP_BASE_IN_V2_INSTRUCTION: u8 = 16;
+pub const SWAP_BASE_OUT_V2_INSTRUCTION: u8 = 17;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fees {
+    pub min_separate_numerator: u64,
+    pub min_separate_denominator: u64,
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub pnl_numerator: u64,
+    pub pnl_denominator: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateData {
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub total_pnl_pc: u64,
+    pub total_pnl_coin: u64,
+    pub pool_open_time: u64,
+    pub punish_pc_amount: u64,
+    pub punish_coin_amount: u64,
+    pub orderbook_to_init_time: u64,
+    pub swap_coin_in_amount: u128,
+    pub swap_pc_out_amount: u128,
+    pub swap_acc_pc_fee: u64,
+    pub swap_pc_in_amount: u128,
+    pub swap_coin_out_amount: u128,
+    pub swap_acc_coin_fee: u64,
+}
+
+// No discriminator, the AMM v4 program is not an Anchor program
+#[derive(Debug, Clone, Deserialize)]
+pub struct AmmInfo {
+    pub status: u64,
+    pub nonce: u64,
+    pub order_num: u64,
+    pub depth: u64,
+    pub coin_decimals: u64,
+    pub pc_decimals: u64,
+    pub state: u64,
+    pub reset_flag: u64,
+    pub min_size: u64,
+    pub vol_max_cut_ratio: u64,
+    pub amount_wave: u64,
+    pub coin_lot_size: u64,
+    pub pc_lot_size: u64,
+    pub min_price_multiplier: u64,
+    pub max_price_multiplier: u64,
+    pub sys_decimal_value: u64,
+    pub fees: Fees,
+    pub state_data: StateData,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_vault_mint: Pubkey,
+    pub pc_vault_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+    pub padding1: [u64; 8],
+    pub amm_owner: Pubkey,
+    pub lp_amount: u64,
+    pub client_order_id: u64,
+    pub recent_epoch: u64,
+    pub padding2: u64,
+}
+
+impl AmmInfo {
+    pub fn is_tradable(&self, now: u64) -> bool {
+        match self.status {
+            AMM_STATUS_INITIALIZED | AMM_STATUS_SWAP_ONLY => true,
+            AMM_STATUS_WAITING_TRADE => now >= self.state_data.pool_open_time,
+            _ => false,
+        }
+    }
+}
+
+// A pool of `mint` against WSOL, `mint_is_coin` telling which side of the pool the mint is on
+#[derive(Debug, Clone)]
+pub struct AmmPool {
+    pub pool: Pubkey,
+    pub amm: AmmInfo,
+    pub mint_is_coin: bool,
+    pub token_reserve: u64,
+    pub sol_reserve: u64,
+}
+
+impl AmmPool {
+    pub fn mint_vault(&self) -> Pubkey {
+        if self.mint_is_coin {
+            self.amm.coin_vault
+        } else {
+            self.amm.pc_vault
+        }
+    }
+
+    pub fn sol_vault(&self) -> Pubkey {
+        if self.mint_is_coin {
+            self.amm.pc_vault
+        } else {
+            self.amm.coin_vault
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SwapBaseOutInfo {
+    pub instruction: u8,
+    pub max_amount_in: u64,
+    pub amount_out: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SwapBaseInInfo {
+    pub instruction: u8,
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+}
+
+impl From<SwapInfo> for SwapBaseOutInfo {
+    fn from(buy: SwapInfo) -> Self {
+        Self {
+            instruction: SWAP_BASE_OUT_V2_INSTRUCTION,
+            max_amount_in: buy.sol_amount,
+            amount_out: buy.token_amount,
+        }
+    }
+}
+
+impl From<SwapInfo> for SwapBaseInInfo {
+    fn from(sell: SwapInfo) -> Self {
+        Self {
+            instruction: SWAP_BASE_IN_V2_INSTRUCTION,
+            amount_in: sell.token_amount,
+            minimum_amount_out: sell.sol_amount,
+        }
+    }
+}
+
+impl SwapBaseOutInfo {
+    pub fn to_buffer(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+impl SwapBaseInInfo {
+    pub fn to_buffer(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer)?;
+        Ok(buffer)
+    }
+}
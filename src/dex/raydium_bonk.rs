@@ -1,23 +1,33 @@
 use super::{
+    amm_calc::amm_convert_amount,
     dex_traits::DexTrait,
     raydium_bonk_types::*,
     types::{Create, PoolInfo, SwapInfo},
 };
 use crate::{
-    common::{accounts::PUBKEY_WSOL, trading_endpoint::TradingEndpoint},
+    common::{accounts::PUBKEY_WSOL, signer::TxSigner, trading_endpoint::TradingEndpoint},
     instruction::builder::PriorityFee,
 };
+use futures::TryFutureExt;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
+    signature::Signature,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, RwLock},
 };
-use spl_associated_token_account::get_associated_token_address;
-use std::sync::Arc;
 
 pub struct RaydiumBonk {
     pub endpoint: Arc<TradingEndpoint>,
+    pub pools: RwLock<HashMap<Pubkey, Arc<PoolState>>>,
+    // SOL and quote token vaults of the SOL/quote pool pricing each non-WSOL quote mint
+    pub quote_price_vaults: RwLock<HashMap<Pubkey, (Pubkey, Pubkey)>>,
+    // SOL and quote reserves last read from `quote_price_vaults`
+    quote_rates: RwLock<HashMap<Pubkey, (u64, u64)>>,
 }
 
 #[async_trait::async_trait]
@@ -38,38 +48,62 @@ impl DexTrait for RaydiumBonk {
         self.endpoint.clone()
     }
 
+    fn get_quote_mint(&self, mint: &Pubkey, pool: Option<&Pubkey>) -> Pubkey {
+        self.get_pool_keys(mint, pool).map(|keys| keys.quote_mint).unwrap_or(PUBKEY_WSOL)
+    }
+
     async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo> {
-        let pool = Self::get_pool_pda(mint)?;
-        let account = self.endpoint.rpc.get_account(&pool).await?;
-        if account.data.is_empty() {
-            return Err(anyhow::anyhow!("Bonding curve not found: {}", pool.to_string()));
-        }
+        let pools = RAYDIUM_BONK_QUOTE_MINTS
+            .iter()
+            .map(|quote_mint| Self::get_pool_pda(mint, quote_mint))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let (accounts, _) = tokio::try_join!(self.endpoint.rpc.get_multiple_accounts(&pools).err_into(), self.endpoint.get_mint_info(mint))?;
+        let (pool, account) = pools
+            .into_iter()
+            .zip(accounts)
+            .find_map(|(pool, account)| account.filter(|account| !account.data.is_empty()).map(|account| (pool, account)))
+            .ok_or_else(|| anyhow::anyhow!("Bonding curve not found: {}", mint))?;
 
         let bonding_curve = bincode::deserialize::<PoolState>(&account.data)?;
+        let quote_mint = bonding_curve.quote_mint;
+        let (_, sol_reserves) = tokio::try_join!(
+            self.endpoint.get_mint_info(&quote_mint),
+            self.quote_to_sol_amount(&quote_mint, bonding_curve.virtual_quote)
+        )?;
 
-        Ok(PoolInfo {
+        let pool_info = PoolInfo {
             pool,
             creator: Some(bonding_curve.creator),
             creator_vault: None,
-            config: None,
-            extra_address: None,
+            config: Some(bonding_curve.platform_config),
+            extra_address: Some(pool),
             token_reserves: bonding_curve.virtual_base,
-            sol_reserves: bonding_curve.virtual_quote,
-        })
+            // In SOL, so that quotes and routes compare across quote mints
+            sol_reserves,
+            // 0: fund raising, 1: migrating, 2: migrated
+            complete: bonding_curve.status != 0,
+        };
+        self.pools.write().unwrap().insert(pool, Arc::new(bonding_curve));
+
+        Ok(pool_info)
     }
 
-    async fn create(&self, _: Keypair, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
         Err(anyhow::anyhow!("Not supported"))
     }
 
-    fn build_buy_instruction(&self, payer: &Keypair, mint: &Pubkey, _: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
-        let buy_info: BuyInfo = buy.into();
+        let keys = self.get_pool_keys(mint, pool)?;
+        let buy_info: BuyInfo = SwapInfo {
+            sol_amount: self.sol_to_quote_amount(&keys.quote_mint, buy.sol_amount)?,
+            ..buy
+        }
+        .into();
         let buffer = buy_info.to_buffer()?;
-        let pool_address = Self::get_pool_pda(mint)?;
-        let pool_base_vault = Self::get_pool_mint_vault(mint, &pool_address)?;
-        let pool_quote_vault = Self::get_pool_quote_vault(&PUBKEY_WSOL, &pool_address)?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+        let quote_token_program = self.endpoint.get_cached_token_program(&keys.quote_mint)?;
 
         Ok(Instruction::new_with_bytes(
             PUBKEY_RAYDIUM_BONK,
@@ -77,31 +111,38 @@ impl DexTrait for RaydiumBonk {
             vec![
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK_AUTHORITY, false),
-                AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK_GLOBAL_CONFIG, false),
-                AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK_PLATFORM_CONFIG, false),
-                AccountMeta::new(pool_address, false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL), false),
-                AccountMeta::new(pool_base_vault, false),
-                AccountMeta::new(pool_quote_vault, false),
+                AccountMeta::new_readonly(keys.global_config, false),
+                AccountMeta::new_readonly(keys.platform_config, false),
+                AccountMeta::new(keys.pool, false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(&payer.pubkey(), &keys.quote_mint, &quote_token_program),
+                    false,
+                ),
+                AccountMeta::new(keys.base_vault, false),
+                AccountMeta::new(keys.quote_vault, false),
                 AccountMeta::new_readonly(*mint, false),
-                AccountMeta::new_readonly(PUBKEY_WSOL, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(keys.quote_mint, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(quote_token_program, false),
                 AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK_EVENT_AUTHORITY, false),
                 AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK, false),
             ],
         ))
     }
 
-    fn build_sell_instruction(&self, payer: &Keypair, mint: &Pubkey, _: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
         self.initialized()?;
 
-        let sell_info: SellInfo = sell.into();
+        let keys = self.get_pool_keys(mint, pool)?;
+        let sell_info: SellInfo = SwapInfo {
+            sol_amount: self.sol_to_quote_amount(&keys.quote_mint, sell.sol_amount)?,
+            ..sell
+        }
+        .into();
         let buffer = sell_info.to_buffer()?;
-        let pool_address = Self::get_pool_pda(mint)?;
-        let pool_base_vault = Self::get_pool_mint_vault(mint, &pool_address)?;
-        let pool_quote_vault = Self::get_pool_quote_vault(&PUBKEY_WSOL, &pool_address)?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+        let quote_token_program = self.endpoint.get_cached_token_program(&keys.quote_mint)?;
 
         Ok(Instruction::new_with_bytes(
             PUBKEY_RAYDIUM_BONK,
@@ -109,17 +150,20 @@ impl DexTrait for RaydiumBonk {
             vec![
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK_AUTHORITY, false),
-                AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK_GLOBAL_CONFIG, false),
-                AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK_PLATFORM_CONFIG, false),
-                AccountMeta::new(pool_address, false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
-                AccountMeta::new(get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL), false),
-                AccountMeta::new(pool_base_vault, false),
-                AccountMeta::new(pool_quote_vault, false),
+                AccountMeta::new_readonly(keys.global_config, false),
+                AccountMeta::new_readonly(keys.platform_config, false),
+                AccountMeta::new(keys.pool, false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer.pubkey(), mint, &token_program), false),
+                AccountMeta::new(
+                    get_associated_token_address_with_program_id(&payer.pubkey(), &keys.quote_mint, &quote_token_program),
+                    false,
+                ),
+                AccountMeta::new(keys.base_vault, false),
+                AccountMeta::new(keys.quote_vault, false),
                 AccountMeta::new_readonly(*mint, false),
-                AccountMeta::new_readonly(PUBKEY_WSOL, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
-                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(keys.quote_mint, false),
+                AccountMeta::new_readonly(token_program, false),
+                AccountMeta::new_readonly(quote_token_program, false),
                 AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK_EVENT_AUTHORITY, false),
                 AccountMeta::new_readonly(PUBKEY_RAYDIUM_BONK, false),
             ],
@@ -129,11 +173,86 @@ impl DexTrait for RaydiumBonk {
 
 impl RaydiumBonk {
     pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
-        Self { endpoint }
+        Self {
+            endpoint,
+            pools: RwLock::new(HashMap::new()),
+            quote_price_vaults: RwLock::new(HashMap::new()),
+            quote_rates: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // Pool accounts taken from the cached `PoolState`, the WSOL pool of `mint` when `pool` is not given
+    pub fn get_pool_keys(&self, mint: &Pubkey, pool: Option<&Pubkey>) -> anyhow::Result<PoolKeys> {
+        let pool = match pool {
+            Some(pool) => *pool,
+            None => Self::get_pool_pda(mint, &PUBKEY_WSOL)?,
+        };
+
+        let pools = self.pools.read().unwrap();
+        let pool_state = pools
+            .get(&pool)
+            .ok_or_else(|| anyhow::anyhow!("Pool not loaded, call get_pool first: {}", pool))?;
+        Ok(PoolKeys {
+            pool,
+            global_config: pool_state.global_config,
+            platform_config: pool_state.platform_config,
+            quote_mint: pool_state.quote_mint,
+            base_vault: pool_state.base_vault,
+            quote_vault: pool_state.quote_vault,
+        })
+    }
+
+    // Prices `quote_mint` from the SOL/quote pool holding these two vaults. Pools of a quote mint without one are not tradable.
+    pub fn set_quote_price_vaults(&self, quote_mint: &Pubkey, sol_vault: &Pubkey, quote_vault: &Pubkey) {
+        self.quote_price_vaults.write().unwrap().insert(*quote_mint, (*sol_vault, *quote_vault));
+    }
+
+    // Converts `sol_amount` into `quote_mint` units at the rate read by the last `get_pool` of a pool quoted in it
+    pub fn sol_to_quote_amount(&self, quote_mint: &Pubkey, sol_amount: u64) -> anyhow::Result<u64> {
+        if *quote_mint == PUBKEY_WSOL {
+            return Ok(sol_amount);
+        }
+
+        let (sol_reserve, quote_reserve) = self
+            .quote_rates
+            .read()
+            .unwrap()
+            .get(quote_mint)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("Quote rate not loaded, call get_pool first: {}", quote_mint))?;
+        Ok(amm_convert_amount(sol_amount, sol_reserve, quote_reserve))
+    }
+
+    // Converts `quote_amount` into SOL at the current spot price of the quote mint's price pool, and caches that rate
+    pub async fn quote_to_sol_amount(&self, quote_mint: &Pubkey, quote_amount: u64) -> anyhow::Result<u64> {
+        if *quote_mint == PUBKEY_WSOL {
+            return Ok(quote_amount);
+        }
+
+        let (sol_vault, quote_vault) = self
+            .quote_price_vaults
+            .read()
+            .unwrap()
+            .get(quote_mint)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("No SOL price for quote mint {}, call set_quote_price_vaults first", quote_mint))?;
+        let (sol_reserve, quote_reserve) = self.get_vault_reserves(&sol_vault, &quote_vault).await?;
+        self.quote_rates.write().unwrap().insert(*quote_mint, (sol_reserve, quote_reserve));
+
+        Ok(amm_convert_amount(quote_amount, quote_reserve, sol_reserve))
+    }
+
+    async fn get_vault_reserves(&self, sol_vault: &Pubkey, quote_vault: &Pubkey) -> anyhow::Result<(u64, u64)> {
+        let (sol_balance, quote_balance) = tokio::try_join!(
+            self.endpoint.rpc.get_token_account_balance(sol_vault),
+            self.endpoint.rpc.get_token_account_balance(quote_vault),
+        )?;
+
+        Ok((u64::from_str(&sol_balance.amount)?, u64::from_str(&quote_balance.amount)?))
     }
 
-    pub fn get_pool_pda(mint: &Pubkey) -> anyhow::Result<Pubkey> {
-        let seeds: &[&[u8]; 3] = &[b"pool", mint.as_ref(), PUBKEY_WSOL.as_ref()];
+    pub fn get_pool_pda(mint: &Pubkey, quote_mint: &Pubkey) -> anyhow::Result<Pubkey> {
+        let seeds: &[&[u8]; 3] = &[b"pool", mint.as_ref(), quote_mint.as_ref()];
         let pda = Pubkey::try_find_program_address(seeds, &PUBKEY_RAYDIUM_BONK).ok_or(anyhow::anyhow!("Failed to find program address"))?;
         Ok(pda.0)
     }
@@ -1,4 +1,5 @@
 use super::types::SwapInfo;
+use crate::common::accounts::{PUBKEY_USD1, PUBKEY_WSOL};
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 use solana_sdk::{pubkey, pubkey::Pubkey};
@@ -9,6 +10,18 @@ pub const PUBKEY_RAYDIUM_BONK_PLATFORM_CONFIG: Pubkey = pubkey!("FfYek5vEz23cMkW
 pub const PUBKEY_RAYDIUM_BONK_AUTHORITY: Pubkey = pubkey!("WLHv2UAZm6z4KyaaELi5pjdbJh6RESMva1Rnn8pJVVh");
 pub const PUBKEY_RAYDIUM_BONK_EVENT_AUTHORITY: Pubkey = pubkey!("2DPAtwB8L12vrMRExbLuyGnC7n2J5LNoZQSejeQGpwkr");
 
+pub const RAYDIUM_BONK_QUOTE_MINTS: &[Pubkey] = &[PUBKEY_WSOL, PUBKEY_USD1];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolKeys {
+    pub pool: Pubkey,
+    pub global_config: Pubkey,
+    pub platform_config: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub struct BuyInfo {
     pub discriminator: u64,
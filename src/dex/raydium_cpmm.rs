@@ -0,0 +1,280 @@
+use super::{
+    amm_calc::{amm_amount_after_fee, amm_buy_get_token_out, amm_sell_get_sol_out},
+    dex_traits::DexTrait,
+    raydium_cpmm_types::*,
+    types::{Create, PoolInfo, SwapInfo, SwapQuote},
+};
+use crate::{
+    common::{accounts::PUBKEY_WSOL, mint_info::MintInfo, signer::TxSigner, trading_endpoint::TradingEndpoint},
+    instruction::builder::PriorityFee,
+};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Signature,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Raydium constant-product pools, where LaunchLab tokens migrate to. Only pools quoted in WSOL are traded.
+pub struct RaydiumCpmm {
+    pub endpoint: Arc<TradingEndpoint>,
+    pub pools: RwLock<HashMap<Pubkey, Arc<CpmmPool>>>,
+}
+
+#[async_trait::async_trait]
+impl DexTrait for RaydiumCpmm {
+    async fn initialize(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn initialized(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn use_wsol(&self) -> bool {
+        true
+    }
+
+    fn get_trading_endpoint(&self) -> Arc<TradingEndpoint> {
+        self.endpoint.clone()
+    }
+
+    // The deepest WSOL pool of `mint` that accepts swaps now
+    async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo> {
+        let (pools, _) = tokio::try_join!(self.get_pools_by_mint(mint), self.endpoint.get_mint_info(mint))?;
+        if pools.is_empty() {
+            return Err(anyhow::anyhow!("Pool not found: {}", mint));
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let pool = pools
+            .into_iter()
+            .filter(|pool| pool.state.is_tradable(now))
+            .max_by_key(|pool| pool.sol_reserve)
+            .ok_or_else(|| anyhow::anyhow!("Pool swaps disabled or not open yet: {}", mint))?;
+
+        let pool_info = PoolInfo {
+            pool: pool.pool,
+            creator: Some(pool.state.pool_creator),
+            creator_vault: None,
+            config: Some(pool.state.amm_config),
+            extra_address: Some(pool.pool),
+            token_reserves: pool.token_reserve,
+            sol_reserves: pool.sol_reserve,
+            complete: false,
+        };
+        self.pools.write().unwrap().insert(pool.pool, Arc::new(pool));
+
+        Ok(pool_info)
+    }
+
+    fn get_pool_scans(&self) -> bool {
+        true
+    }
+
+    // The swap fee is taken from the input, so the closed-form split does not apply
+    fn is_constant_product(&self) -> bool {
+        false
+    }
+
+    fn quote_buy(&self, pool_info: &PoolInfo, _mint_info: &MintInfo, sol_amount: u64) -> SwapQuote {
+        let (input_fee_rate, output_fee_rate) = self.get_fee_rates(&pool_info.pool, false);
+        let sol_in = amm_amount_after_fee(sol_amount, input_fee_rate, FEE_RATE_DENOMINATOR);
+        let token_out = amm_buy_get_token_out(pool_info.sol_reserves, pool_info.token_reserves, sol_in);
+        SwapQuote {
+            token_amount: amm_amount_after_fee(token_out, output_fee_rate, FEE_RATE_DENOMINATOR),
+            sol_amount,
+        }
+    }
+
+    fn quote_sell(&self, pool_info: &PoolInfo, mint_info: &MintInfo, token_amount: u64) -> SwapQuote {
+        let (input_fee_rate, output_fee_rate) = self.get_fee_rates(&pool_info.pool, true);
+        let token_amount = mint_info.amount_after_transfer_fee(token_amount);
+        let token_in = amm_amount_after_fee(token_amount, input_fee_rate, FEE_RATE_DENOMINATOR);
+        let sol_out = amm_sell_get_sol_out(pool_info.sol_reserves, pool_info.token_reserves, token_in);
+        SwapQuote {
+            token_amount,
+            sol_amount: amm_amount_after_fee(sol_out, output_fee_rate, FEE_RATE_DENOMINATOR),
+        }
+    }
+
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<Signature>> {
+        Err(anyhow::anyhow!("Not supported"))
+    }
+
+    fn build_buy_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let swap_info: SwapBaseOutputInfo = buy.into();
+        let buffer = swap_info.to_buffer()?;
+        let pool = self.get_cached_pool(mint, pool)?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_RAYDIUM_CPMM,
+            &buffer,
+            Self::build_swap_accounts(payer, &pool, mint, &token_program, false),
+        ))
+    }
+
+    fn build_sell_instruction(&self, payer: &dyn TxSigner, mint: &Pubkey, pool: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+        self.initialized()?;
+
+        let swap_info: SwapBaseInputInfo = sell.into();
+        let buffer = swap_info.to_buffer()?;
+        let pool = self.get_cached_pool(mint, pool)?;
+        let token_program = self.endpoint.get_cached_token_program(mint)?;
+
+        Ok(Instruction::new_with_bytes(
+            PUBKEY_RAYDIUM_CPMM,
+            &buffer,
+            Self::build_swap_accounts(payer, &pool, mint, &token_program, true),
+        ))
+    }
+}
+
+impl RaydiumCpmm {
+    pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
+        Self {
+            endpoint,
+            pools: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn get_cached_pool(&self, mint: &Pubkey, pool: Option<&Pubkey>) -> anyhow::Result<Arc<CpmmPool>> {
+        let pool = pool.ok_or_else(|| anyhow::anyhow!("Pool address required: {}", mint))?;
+        self.pools
+            .read()
+            .unwrap()
+            .get(pool)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Pool not loaded, call get_pool first: {}", pool))
+    }
+
+    fn get_fee_rates(&self, pool: &Pubkey, sell: bool) -> (u64, u64) {
+        self.pools.read().unwrap().get(pool).map(|pool| pool.fee_rates(sell)).unwrap_or((0, 0))
+    }
+
+    // Input and output follow the swap direction: WSOL to `mint` on buys, `mint` to WSOL on sells
+    fn build_swap_accounts(payer: &dyn TxSigner, pool: &CpmmPool, mint: &Pubkey, token_program: &Pubkey, sell: bool) -> Vec<AccountMeta> {
+        let mint_side = (
+            get_associated_token_address_with_program_id(&payer.pubkey(), mint, token_program),
+            pool.mint_vault(),
+            *token_program,
+            *mint,
+        );
+        let sol_side = (
+            get_associated_token_address_with_program_id(&payer.pubkey(), &PUBKEY_WSOL, &spl_token::ID),
+            pool.sol_vault(),
+            spl_token::ID,
+            PUBKEY_WSOL,
+        );
+        let (input, output) = if sell { (mint_side, sol_side) } else { (sol_side, mint_side) };
+
+        vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(PUBKEY_RAYDIUM_CPMM_AUTHORITY, false),
+            AccountMeta::new_readonly(pool.state.amm_config, false),
+            AccountMeta::new(pool.pool, false),
+            AccountMeta::new(input.0, false),
+            AccountMeta::new(output.0, false),
+            AccountMeta::new(input.1, false),
+            AccountMeta::new(output.1, false),
+            AccountMeta::new_readonly(input.2, false),
+            AccountMeta::new_readonly(output.2, false),
+            AccountMeta::new_readonly(input.3, false),
+            AccountMeta::new_readonly(output.3, false),
+            AccountMeta::new(pool.state.observation_key, false),
+        ]
+    }
+
+    // Every WSOL pool of `mint`, with fee-free reserves read from the vaults. Pool mints are sorted, so the mint side is known upfront.
+    pub async fn get_pools_by_mint(&self, mint: &Pubkey) -> anyhow::Result<Vec<CpmmPool>> {
+        let mint_is_token_0 = mint < &PUBKEY_WSOL;
+        let (token_0_mint, token_1_mint) = if mint_is_token_0 { (mint, &PUBKEY_WSOL) } else { (&PUBKEY_WSOL, mint) };
+        let accounts = self
+            .endpoint
+            .rpc
+            .get_program_accounts_with_config(
+                &PUBKEY_RAYDIUM_CPMM,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(POOL_ACCOUNT_SIZE),
+                        RpcFilterType::Memcmp(Memcmp::new(
+                            POOL_TOKEN_0_MINT_OFFSET,
+                            MemcmpEncodedBytes::Bytes(token_0_mint.to_bytes().to_vec()),
+                        )),
+                        RpcFilterType::Memcmp(Memcmp::new(
+                            POOL_TOKEN_1_MINT_OFFSET,
+                            MemcmpEncodedBytes::Bytes(token_1_mint.to_bytes().to_vec()),
+                        )),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                        commitment: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    },
+                    with_context: None,
+                    sort_results: None,
+                },
+            )
+            .await?;
+
+        let states = accounts
+            .into_iter()
+            .map(|(pool, account)| Ok((pool, bincode::deserialize::<PoolState>(&account.data)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // Two vaults and the config of every pool
+        let keys = states
+            .iter()
+            .flat_map(|(_, state)| [state.token_0_vault, state.token_1_vault, state.amm_config])
+            .collect::<Vec<_>>();
+        let mut key_accounts = vec![];
+        for chunk in keys.chunks(99) {
+            key_accounts.extend(self.endpoint.rpc.get_multiple_accounts(chunk).await?);
+        }
+
+        states
+            .into_iter()
+            .zip(key_accounts.chunks(3))
+            .map(|((pool, state), accounts)| {
+                let reserve = |vault: &Option<Account>| -> anyhow::Result<u64> {
+                    let vault = vault.as_ref().ok_or_else(|| anyhow::anyhow!("Pool vault not found: {}", pool))?;
+                    Ok(StateWithExtensions::<TokenAccount>::unpack(&vault.data)?.base.amount)
+                };
+                let config = accounts[2]
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("Amm config not found: {}", state.amm_config))?;
+                let config = bincode::deserialize::<AmmConfig>(&config.data)?;
+                let creator_fee_rate = if state.enable_creator_fee { config.creator_fee_rate } else { 0 };
+
+                let (fees_0, fees_1) = state.pending_fees();
+                let reserve_0 = reserve(&accounts[0])?.saturating_sub(fees_0);
+                let reserve_1 = reserve(&accounts[1])?.saturating_sub(fees_1);
+                let (token_reserve, sol_reserve) = if mint_is_token_0 { (reserve_0, reserve_1) } else { (reserve_1, reserve_0) };
+
+                Ok(CpmmPool {
+                    pool,
+                    state,
+                    mint_is_token_0,
+                    trade_fee_rate: config.trade_fee_rate,
+                    creator_fee_rate,
+                    token_reserve,
+                    sol_reserve,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,177 @@
+use super::types::SwapInfo;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Deserialize;
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const PUBKEY_RAYDIUM_CPMM: Pubkey = pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP8C");
+pub const PUBKEY_RAYDIUM_CPMM_AUTHORITY: Pubkey = pubkey!("GpMZbSM2GgvTKHJirzeGfMFoaZ8UR2X7F4v8vHTvxFbL");
+
+pub const POOL_TOKEN_0_MINT_OFFSET: usize = 168;
+pub const POOL_TOKEN_1_MINT_OFFSET: usize = 200;
+pub const POOL_ACCOUNT_SIZE: u64 = 637;
+pub const FEE_RATE_DENOMINATOR: u64 = 1_000_000;
+// `PoolState::status` bit disabling swaps
+pub const POOL_STATUS_SWAP_DISABLED: u8 = 1 << 2;
+// `PoolState::creator_fee_on`, the token the creator fee is charged in
+pub const CREATOR_FEE_ON_BOTH_TOKENS: u8 = 0;
+pub const CREATOR_FEE_ON_TOKEN_0: u8 = 1;
+pub const CREATOR_FEE_ON_TOKEN_1: u8 = 2;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PoolState {
+    pub discriminator: u64,
+    pub amm_config: Pubkey,
+    pub pool_creator: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub token_0_program: Pubkey,
+    pub token_1_program: Pubkey,
+    pub observation_key: Pubkey,
+    pub auth_bump: u8,
+    pub status: u8,
+    pub lp_mint_decimals: u8,
+    pub mint_0_decimals: u8,
+    pub mint_1_decimals: u8,
+    pub lp_supply: u64,
+    pub protocol_fees_token_0: u64,
+    pub protocol_fees_token_1: u64,
+    pub fund_fees_token_0: u64,
+    pub fund_fees_token_1: u64,
+    pub open_time: u64,
+    pub recent_epoch: u64,
+    pub creator_fee_on: u8,
+    pub enable_creator_fee: bool,
+    pub padding1: [u8; 6],
+    pub creator_fees_token_0: u64,
+    pub creator_fees_token_1: u64,
+}
+
+impl PoolState {
+    // Swaps fail on chain while disabled, or until the block time is past `open_time`
+    pub fn is_tradable(&self, now: u64) -> bool {
+        self.status & POOL_STATUS_SWAP_DISABLED == 0 && now > self.open_time
+    }
+
+    // Fees accrued in the vaults that are not part of the swappable reserves
+    pub fn pending_fees(&self) -> (u64, u64) {
+        (
+            self.protocol_fees_token_0 + self.fund_fees_token_0 + self.creator_fees_token_0,
+            self.protocol_fees_token_1 + self.fund_fees_token_1 + self.creator_fees_token_1,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AmmConfig {
+    pub discriminator: u64,
+    pub bump: u8,
+    pub disable_create_pool: bool,
+    pub index: u16,
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    pub create_pool_fee: u64,
+    pub protocol_owner: Pubkey,
+    pub fund_owner: Pubkey,
+    pub creator_fee_rate: u64,
+}
+
+// A pool of `mint` against WSOL, `mint_is_token_0` telling which side of the pool the mint is on
+#[derive(Debug, Clone)]
+pub struct CpmmPool {
+    pub pool: Pubkey,
+    pub state: PoolState,
+    pub mint_is_token_0: bool,
+    // Out of `FEE_RATE_DENOMINATOR`, the creator fee being 0 when the pool does not charge it
+    pub trade_fee_rate: u64,
+    pub creator_fee_rate: u64,
+    pub token_reserve: u64,
+    pub sol_reserve: u64,
+}
+
+impl CpmmPool {
+    pub fn mint_vault(&self) -> Pubkey {
+        if self.mint_is_token_0 {
+            self.state.token_0_vault
+        } else {
+            self.state.token_1_vault
+        }
+    }
+
+    pub fn sol_vault(&self) -> Pubkey {
+        if self.mint_is_token_0 {
+            self.state.token_1_vault
+        } else {
+            self.state.token_0_vault
+        }
+    }
+
+    // Fee rates taken from the input and from the output of a swap, buys swapping WSOL for the mint and sells the other way.
+    // The trade fee is always taken from the input, the creator fee from the input only when it is charged in the input token.
+    pub fn fee_rates(&self, sell: bool) -> (u64, u64) {
+        let input_is_token_0 = self.mint_is_token_0 == sell;
+        let creator_fee_on_input = match self.state.creator_fee_on {
+            CREATOR_FEE_ON_TOKEN_0 => input_is_token_0,
+            CREATOR_FEE_ON_TOKEN_1 => !input_is_token_0,
+            _ => true,
+        };
+        if creator_fee_on_input {
+            (self.trade_fee_rate + self.creator_fee_rate, 0)
+        } else {
+            (self.trade_fee_rate, self.creator_fee_rate)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SwapBaseOutputInfo {
+    pub discriminator: u64,
+    pub max_amount_in: u64,
+    pub amount_out: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SwapBaseInputInfo {
+    pub discriminator: u64,
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+}
+
+impl From<SwapInfo> for SwapBaseOutputInfo {
+    fn from(buy: SwapInfo) -> Self {
+        Self {
+            discriminator: 12516711329758894391,
+            max_amount_in: buy.sol_amount,
+            amount_out: buy.token_amount,
+        }
+    }
+}
+
+impl From<SwapInfo> for SwapBaseInputInfo {
+    fn from(sell: SwapInfo) -> Self {
+        Self {
+            discriminator: 16011174931058048655,
+            amount_in: sell.token_amount,
+            minimum_amount_out: sell.sol_amount,
+        }
+    }
+}
+
+impl SwapBaseOutputInfo {
+    pub fn to_buffer(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer)?;
+        Ok(buffer)
+    }
+}
+
+impl SwapBaseInputInfo {
+    pub fn to_buffer(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.serialize(&mut buffer)?;
+        Ok(buffer)
+    }
+}
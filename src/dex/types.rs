@@ -1,14 +1,15 @@
 use super::{dex_traits::DexTrait, pumpfun, pumpswap};
 use crate::{
-    common::trading_endpoint::TradingEndpoint,
-    dex::{believe, boopfun, raydium_bonk, meteora_dbc},
+    common::{signer::TxSigner, trading_endpoint::TradingEndpoint},
+    dex::{aggregator, believe, boopfun, meteora_damm_v1, meteora_damm_v2, meteora_dbc, raydium_amm_v4, raydium_bonk, raydium_cpmm},
 };
 use serde::{Deserialize, Serialize};
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{pubkey::Pubkey, signature::Keypair};
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use std::sync::Arc;
 
+#[derive(Debug, Clone)]
 pub struct PoolInfo {
     pub pool: Pubkey,
     pub creator: Option<Pubkey>,
@@ -17,13 +18,33 @@ pub struct PoolInfo {
     pub extra_address: Option<Pubkey>,
     pub token_reserves: u64,
     pub sol_reserves: u64,
+    // The bonding curve has graduated, trading moved to the migration venue
+    pub complete: bool,
 }
 
+impl PoolInfo {
+    // Spot price in lamports per raw token unit
+    pub fn price(&self) -> f64 {
+        if self.token_reserves == 0 {
+            return 0.0;
+        }
+        self.sol_reserves as f64 / self.token_reserves as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SwapInfo {
     pub token_amount: u64,
     pub sol_amount: u64,
 }
 
+// Amounts moving through the pool reserves for one swap, before slippage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub token_amount: u64,
+    pub sol_amount: u64,
+}
+
 pub struct Create {
     pub name: String,
     pub symbol: String,
@@ -31,6 +52,42 @@ pub struct Create {
     pub mint_private_key: Keypair,
     pub buy_sol_amount: Option<u64>,
     pub slippage_basis_points: Option<u64>,
+    pub create_type: CreateType,
+}
+
+impl Create {
+    // Legacy SPL Token create, set `create_type` for a Token-2022 mint
+    pub fn new(name: String, symbol: String, uri: String, mint_private_key: Keypair, buy_sol_amount: Option<u64>, slippage_basis_points: Option<u64>) -> Self {
+        Self {
+            name,
+            symbol,
+            uri,
+            mint_private_key,
+            buy_sol_amount,
+            slippage_basis_points,
+            create_type: CreateType::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CreateType {
+    /// SPL Token mint with Metaplex metadata
+    #[default]
+    Legacy,
+    /// Token-2022 mint with metadata stored in the mint extensions
+    Token2022,
+    /// Token-2022 mint launched in Mayhem mode
+    Token2022Mayhem,
+}
+
+impl CreateType {
+    pub fn token_program(&self) -> Pubkey {
+        match self {
+            CreateType::Legacy => spl_token::ID,
+            CreateType::Token2022 | CreateType::Token2022Mayhem => spl_token_2022::ID,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -41,6 +98,14 @@ pub enum DexType {
     Boopfun,
     Believe,
     MeteoraDBC,
+    /// Meteora DAMM v2 pools, where DBC tokens graduate to
+    MeteoraDammV2,
+    MeteoraDammV1,
+    /// Raydium CPMM pools, where LaunchLab tokens graduate to
+    RaydiumCpmm,
+    RaydiumAmmV4,
+    /// External Jupiter-compatible aggregator, a fallback when no native venue is live. Opt-in, not part of `all`.
+    Aggregator,
 }
 
 impl DexType {
@@ -52,9 +117,21 @@ impl DexType {
             DexType::Boopfun,
             DexType::Believe,
             DexType::MeteoraDBC,
+            DexType::MeteoraDammV2,
+            DexType::MeteoraDammV1,
+            DexType::RaydiumCpmm,
+            DexType::RaydiumAmmV4,
         ]
     }
 
+    // Launchpads whose tokens trade on a bonding curve until they graduate to a pool
+    pub fn is_bonding_curve(&self) -> bool {
+        matches!(
+            self,
+            DexType::Pumpfun | DexType::RayBonk | DexType::Boopfun | DexType::Believe | DexType::MeteoraDBC
+        )
+    }
+
     pub fn instantiate(&self, endpoint: Arc<TradingEndpoint>) -> Arc<dyn DexTrait> {
         match self {
             DexType::Pumpfun => Arc::new(pumpfun::Pumpfun::new(endpoint)),
@@ -63,20 +140,26 @@ impl DexType {
             DexType::Boopfun => Arc::new(boopfun::Boopfun::new(endpoint)),
             DexType::Believe => Arc::new(believe::Believe::new(endpoint)),
             DexType::MeteoraDBC => Arc::new(meteora_dbc::MeteoraDBC::new(endpoint)),
+            DexType::MeteoraDammV2 => Arc::new(meteora_damm_v2::MeteoraDammV2::new(endpoint)),
+            DexType::MeteoraDammV1 => Arc::new(meteora_damm_v1::MeteoraDammV1::new(endpoint)),
+            DexType::RaydiumCpmm => Arc::new(raydium_cpmm::RaydiumCpmm::new(endpoint)),
+            DexType::RaydiumAmmV4 => Arc::new(raydium_amm_v4::RaydiumAmmV4::new(endpoint)),
+            DexType::Aggregator => Arc::new(aggregator::Aggregator::new(endpoint)),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TokenAmountType {
     Percent(u64),
     Amount(u64),
 }
 
 impl TokenAmountType {
-    pub async fn to_amount(&self, rpc: Arc<RpcClient>, payer: &Pubkey, mint: &Pubkey) -> anyhow::Result<u64> {
+    pub async fn to_amount(&self, rpc: Arc<RpcClient>, payer: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> anyhow::Result<u64> {
         match self {
             TokenAmountType::Percent(percent) => {
-                let ata = get_associated_token_address(payer, mint);
+                let ata = get_associated_token_address_with_program_id(payer, mint, token_program);
                 let balance = rpc.get_token_account_balance(&ata).await?;
                 let balance_u64 = balance.amount.parse::<u64>()?;
                 Ok((balance_u64 * percent) / 100)
@@ -93,12 +176,23 @@ pub enum CreateATA {
 }
 
 pub struct BatchBuyParam {
-    pub payer: Keypair,
+    pub payer: Arc<dyn TxSigner>,
     pub sol_amount: u64,
 }
 
 pub struct BatchSellParam {
-    pub payer: Keypair,
-    pub token_amount: u64,
+    pub payer: Arc<dyn TxSigner>,
+    pub token_amount: TokenAmountType,
     pub close_mint_ata: bool,
 }
+
+impl BatchSellParam {
+    // Sells a fixed `token_amount`, as before `token_amount` took a `TokenAmountType`
+    pub fn new(payer: Arc<dyn TxSigner>, token_amount: u64, close_mint_ata: bool) -> Self {
+        Self {
+            payer,
+            token_amount: TokenAmountType::Amount(token_amount),
+            close_mint_ata,
+        }
+    }
+}
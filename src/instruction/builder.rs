@@ -1,17 +1,18 @@
-use crate::{common::accounts::PUBKEY_WSOL, dex::types::CreateATA};
+use crate::{
+    common::{accounts::PUBKEY_WSOL, signer::TxSigner},
+    dex::types::CreateATA,
+};
 use serde::{Deserialize, Serialize};
 use solana_sdk::{
     compute_budget::ComputeBudgetInstruction,
     hash::Hash,
     instruction::Instruction,
-    message::{v0, VersionedMessage},
+    message::{v0, AddressLookupTableAccount, VersionedMessage},
     pubkey::Pubkey,
-    signature::Keypair,
-    signer::Signer,
     transaction::VersionedTransaction,
 };
 use spl_associated_token_account::{
-    get_associated_token_address,
+    get_associated_token_address, get_associated_token_address_with_program_id,
     instruction::{create_associated_token_account, create_associated_token_account_idempotent},
 };
 use spl_token::instruction::{close_account, sync_native};
@@ -28,47 +29,94 @@ pub struct TipFee {
     pub tip_lamports: u64,
 }
 
+// A durable nonce used in place of a recent blockhash, advanced by the first instruction of the transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurableNonce {
+    pub nonce_account: Pubkey,
+    pub authority: Pubkey,
+    pub nonce: Hash,
+}
+
 pub fn build_transaction(
-    payer: &Keypair,
+    payer: &dyn TxSigner,
     instructions: Vec<Instruction>,
     blockhash: Hash,
     fee: Option<PriorityFee>,
     tip: Option<TipFee>,
-    other_signers: Option<Vec<&Keypair>>,
+    other_signers: Option<Vec<&dyn TxSigner>>,
+) -> anyhow::Result<VersionedTransaction> {
+    build_transaction_with_lookup_tables(payer, instructions, blockhash, fee, tip, other_signers, &[])
+}
+
+pub fn build_transaction_with_lookup_tables(
+    payer: &dyn TxSigner,
+    instructions: Vec<Instruction>,
+    blockhash: Hash,
+    fee: Option<PriorityFee>,
+    tip: Option<TipFee>,
+    other_signers: Option<Vec<&dyn TxSigner>>,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> anyhow::Result<VersionedTransaction> {
+    build_transaction_with_tips(payer, instructions, blockhash, fee, tip.as_slice(), other_signers, lookup_tables, None)
+}
+
+// Builds a transaction paying every tip of `tips`. With a durable nonce, `blockhash` is replaced by the nonce.
+#[allow(clippy::too_many_arguments)]
+pub fn build_transaction_with_tips(
+    payer: &dyn TxSigner,
+    instructions: Vec<Instruction>,
+    blockhash: Hash,
+    fee: Option<PriorityFee>,
+    tips: &[TipFee],
+    other_signers: Option<Vec<&dyn TxSigner>>,
+    lookup_tables: &[AddressLookupTableAccount],
+    nonce: Option<&DurableNonce>,
 ) -> anyhow::Result<VersionedTransaction> {
     let mut insts = vec![];
+    let mut blockhash = blockhash;
+    if let Some(nonce) = nonce {
+        insts.push(solana_sdk::system_instruction::advance_nonce_account(&nonce.nonce_account, &nonce.authority));
+        blockhash = nonce.nonce;
+    }
+
     if let Some(fee) = fee {
         insts.push(ComputeBudgetInstruction::set_compute_unit_price(fee.unit_price));
         insts.push(ComputeBudgetInstruction::set_compute_unit_limit(fee.unit_limit));
     }
 
-    if let Some(tip) = tip {
+    for tip in tips {
         insts.push(solana_sdk::system_instruction::transfer(&payer.pubkey(), &tip.tip_account, tip.tip_lamports));
     }
 
     insts.extend(instructions);
 
-    let v0_message: v0::Message = v0::Message::try_compile(&payer.pubkey(), &insts, &[], blockhash)?;
+    let v0_message: v0::Message = v0::Message::try_compile(&payer.pubkey(), &insts, lookup_tables, blockhash)?;
     let versioned_message: VersionedMessage = VersionedMessage::V0(v0_message);
-    let signers = vec![payer].into_iter().chain(other_signers.unwrap_or_default().into_iter()).collect::<Vec<_>>();
+    let signers = vec![payer].into_iter().chain(other_signers.unwrap_or_default()).collect::<Vec<_>>();
     let transaction = VersionedTransaction::try_new(versioned_message, &signers)?;
 
     Ok(transaction)
 }
 
-pub fn build_sol_buy_instructions(payer: &Keypair, mint: &Pubkey, buy_instruction: Instruction, crate_ata: CreateATA) -> anyhow::Result<Vec<Instruction>> {
+pub fn build_sol_buy_instructions(
+    payer: &dyn TxSigner,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    buy_instruction: Instruction,
+    crate_ata: CreateATA,
+) -> anyhow::Result<Vec<Instruction>> {
     let mut instructions = vec![];
 
     match crate_ata {
         CreateATA::Create => {
-            instructions.push(create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint, &spl_token::ID));
+            instructions.push(create_associated_token_account(&payer.pubkey(), &payer.pubkey(), mint, token_program));
         }
         CreateATA::Idempotent => {
             instructions.push(create_associated_token_account_idempotent(
                 &payer.pubkey(),
                 &payer.pubkey(),
-                &mint,
-                &spl_token::ID,
+                mint,
+                token_program,
             ));
         }
         CreateATA::None => {}
@@ -80,24 +128,53 @@ pub fn build_sol_buy_instructions(payer: &Keypair, mint: &Pubkey, buy_instructio
 }
 
 pub fn build_sol_sell_instructions(
-    payer: &Keypair,
+    payer: &dyn TxSigner,
     mint: &Pubkey,
+    token_program: &Pubkey,
     sell_instruction: Instruction,
     close_mint_ata: bool,
 ) -> Result<Vec<Instruction>, anyhow::Error> {
     let mut instructions = vec![sell_instruction];
 
     if close_mint_ata {
-        let mint_ata = get_associated_token_address(&payer.pubkey(), &mint);
-        instructions.push(close_account(&spl_token::ID, &mint_ata, &payer.pubkey(), &payer.pubkey(), &[&payer.pubkey()])?);
+        let mint_ata = get_associated_token_address_with_program_id(&payer.pubkey(), mint, token_program);
+        instructions.push(spl_token_2022::instruction::close_account(
+            token_program,
+            &mint_ata,
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &[&payer.pubkey()],
+        )?);
     }
 
     Ok(instructions)
 }
 
+pub fn build_quote_sell_instructions(
+    payer: &dyn TxSigner,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    quote_mint: &Pubkey,
+    quote_token_program: &Pubkey,
+    sell_instruction: Instruction,
+    close_mint_ata: bool,
+) -> anyhow::Result<Vec<Instruction>> {
+    let mut instructions = vec![create_associated_token_account_idempotent(
+        &payer.pubkey(),
+        &payer.pubkey(),
+        quote_mint,
+        quote_token_program,
+    )];
+
+    instructions.extend(build_sol_sell_instructions(payer, mint, token_program, sell_instruction, close_mint_ata)?);
+
+    Ok(instructions)
+}
+
 pub fn build_wsol_buy_instructions(
-    payer: &Keypair,
+    payer: &dyn TxSigner,
     mint: &Pubkey,
+    token_program: &Pubkey,
     amount_sol: u64,
     buy_instruction: Instruction,
     crate_ata: CreateATA,
@@ -106,14 +183,14 @@ pub fn build_wsol_buy_instructions(
 
     match crate_ata {
         CreateATA::Create => {
-            instructions.push(create_associated_token_account(&payer.pubkey(), &payer.pubkey(), &mint, &spl_token::ID));
+            instructions.push(create_associated_token_account(&payer.pubkey(), &payer.pubkey(), mint, token_program));
         }
         CreateATA::Idempotent => {
             instructions.push(create_associated_token_account_idempotent(
                 &payer.pubkey(),
                 &payer.pubkey(),
-                &mint,
-                &spl_token::ID,
+                mint,
+                token_program,
             ));
         }
         CreateATA::None => {}
@@ -138,8 +215,14 @@ pub fn build_wsol_buy_instructions(
     Ok(instructions)
 }
 
-pub fn build_wsol_sell_instructions(payer: &Keypair, mint: &Pubkey, sell_instruction: Instruction, close_mint_ata: bool) -> anyhow::Result<Vec<Instruction>> {
-    let mint_ata = get_associated_token_address(&payer.pubkey(), &mint);
+pub fn build_wsol_sell_instructions(
+    payer: &dyn TxSigner,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    sell_instruction: Instruction,
+    close_mint_ata: bool,
+) -> anyhow::Result<Vec<Instruction>> {
+    let mint_ata = get_associated_token_address_with_program_id(&payer.pubkey(), mint, token_program);
     let wsol_ata = get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL);
 
     let mut instructions = vec![];
@@ -155,8 +238,29 @@ pub fn build_wsol_sell_instructions(payer: &Keypair, mint: &Pubkey, sell_instruc
     instructions.push(close_account(&spl_token::ID, &wsol_ata, &payer.pubkey(), &payer.pubkey(), &[&payer.pubkey()]).unwrap());
 
     if close_mint_ata {
-        instructions.push(close_account(&spl_token::ID, &mint_ata, &payer.pubkey(), &payer.pubkey(), &[&payer.pubkey()]).unwrap());
+        instructions.push(spl_token_2022::instruction::close_account(
+            token_program,
+            &mint_ata,
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &[&payer.pubkey()],
+        )?);
     }
 
     Ok(instructions)
 }
+
+pub fn build_wrap_sol_instructions(payer: &dyn TxSigner, amount_sol: u64) -> anyhow::Result<Vec<Instruction>> {
+    let wsol_ata = get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL);
+
+    Ok(vec![
+        create_associated_token_account_idempotent(&payer.pubkey(), &payer.pubkey(), &PUBKEY_WSOL, &spl_token::ID),
+        solana_sdk::system_instruction::transfer(&payer.pubkey(), &wsol_ata, amount_sol),
+        sync_native(&spl_token::ID, &wsol_ata)?,
+    ])
+}
+
+pub fn build_close_wsol_instruction(payer: &dyn TxSigner) -> anyhow::Result<Instruction> {
+    let wsol_ata = get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL);
+    Ok(close_account(&spl_token::ID, &wsol_ata, &payer.pubkey(), &payer.pubkey(), &[&payer.pubkey()])?)
+}
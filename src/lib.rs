@@ -2,4 +2,5 @@ pub mod instruction;
 pub mod ipfs;
 pub mod dex;
 pub mod swqos;
-pub mod common;
\ No newline at end of file
+pub mod common;
+pub mod strategy;
\ No newline at end of file
@@ -29,8 +29,7 @@ pub fn get_solana_client() -> Arc<RpcClient> {
 }
 
 pub fn get_swqos_client() -> DefaultSWQoSClient {
-    let swqos_client = DefaultSWQoSClient::new("default", get_solana_client(), RPC_ENDPOINT.to_string(), None, vec![]);
-    swqos_client
+    DefaultSWQoSClient::new("default", get_solana_client(), RPC_ENDPOINT.to_string(), None, vec![])
 }
 
 pub async fn transfer_sol() -> anyhow::Result<()> {
@@ -130,15 +129,15 @@ pub async fn create() -> anyhow::Result<()> {
     };
     let tip = sol_str_to_lamports("0.001").unwrap();
 
-    let create = Create {
-        mint_private_key: mint_key,
-        name: metadata.metadata.name,
-        symbol: metadata.metadata.symbol,
-        uri: metadata.metadata_uri,
+    let create = Create::new(
+        metadata.metadata.name,
+        metadata.metadata.symbol,
+        metadata.metadata_uri,
+        mint_key,
         buy_sol_amount,
-        slippage_basis_points: Some(slippage_basis_points),
-    };
-    pumpfun_client.create(payer, create, Some(fee), Some(tip)).await?;
+        Some(slippage_basis_points),
+    );
+    pumpfun_client.create(&payer, create, Some(fee), Some(tip)).await?;
 
     Ok(())
 }
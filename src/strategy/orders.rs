@@ -0,0 +1,560 @@
+use super::position_manager::MAX_SIGNATURE_STATUSES;
+use crate::{
+    common::signer::TxSigner,
+    dex::{
+        dex_traits::DexTrait,
+        types::{DexType, PoolInfo, TokenAmountType},
+    },
+    instruction::builder::PriorityFee,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature, transaction::TransactionError};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+pub const ORDER_MAX_ATTEMPTS: u32 = 5;
+// Doubled after every failed attempt, up to `ORDER_MAX_BACKOFF`
+pub const ORDER_RETRY_BACKOFF: Duration = Duration::from_secs(5);
+pub const ORDER_MAX_BACKOFF: Duration = Duration::from_secs(300);
+// A slice not confirmed by then has an expired blockhash, it counts as a failed attempt
+pub const ORDER_CONFIRM_TIMEOUT: Duration = Duration::from_secs(90);
+
+// Prices are in lamports per raw token unit, see `PoolInfo::price`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrderKind {
+    /// Buys once the price drops to `max_price` or below
+    LimitBuy { sol_amount: u64, max_price: f64 },
+    /// Sells once the price rises to `min_price` or above
+    LimitSell { token_amount: TokenAmountType, min_price: f64 },
+    /// Buys `sol_amount` in `slices` equal parts, one every `interval_secs`
+    DcaBuy { sol_amount: u64, slices: u32, interval_secs: u64 },
+    /// Sells `token_amount` in `slices` equal parts, one every `interval_secs`
+    DcaSell { token_amount: u64, slices: u32, interval_secs: u64 },
+}
+
+impl OrderKind {
+    pub fn slices(&self) -> u32 {
+        match self {
+            OrderKind::LimitBuy { .. } | OrderKind::LimitSell { .. } => 1,
+            OrderKind::DcaBuy { slices, .. } | OrderKind::DcaSell { slices, .. } => (*slices).max(1),
+        }
+    }
+
+    pub fn is_triggered(&self, pool_info: &PoolInfo) -> bool {
+        match self {
+            OrderKind::LimitBuy { max_price, .. } => pool_info.price() <= *max_price,
+            OrderKind::LimitSell { min_price, .. } => pool_info.price() >= *min_price,
+            OrderKind::DcaBuy { .. } | OrderKind::DcaSell { .. } => true,
+        }
+    }
+
+    // Size of the `index`-th slice, the last one taking the remainder
+    fn slice_amount(total: u64, slices: u32, index: u32) -> u64 {
+        let slices = slices.max(1) as u64;
+        let amount = total / slices;
+        if index as u64 + 1 == slices {
+            total - amount * (slices - 1)
+        } else {
+            amount
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus {
+    Open,
+    Filled,
+    Cancelled,
+    Failed(String),
+}
+
+// Saved before the slice is sent, so that a restart never sends it a second time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InFlightSlice {
+    pub slice: u32,
+    // Empty until the transactions are sent
+    pub signatures: Vec<String>,
+    // Unix timestamp in seconds
+    pub sent_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    pub id: u64,
+    pub dex: DexType,
+    pub payer: Pubkey,
+    pub mint: Pubkey,
+    pub kind: OrderKind,
+    pub slippage_basis_points: u64,
+    pub fee: Option<PriorityFee>,
+    pub tip: Option<u64>,
+    pub status: OrderStatus,
+    pub slices_done: u32,
+    // Unix timestamps in seconds
+    pub created_at: u64,
+    pub next_execution: u64,
+    pub signatures: Vec<String>,
+    // Consecutive failed executions, reset once a slice executes
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    // Slice waiting for its signatures to confirm, it counts in `slices_done` once one of them lands
+    #[serde(default)]
+    pub in_flight: Option<InFlightSlice>,
+}
+
+impl Order {
+    pub fn is_due(&self, now: u64) -> bool {
+        self.status == OrderStatus::Open && self.in_flight.is_none() && now >= self.next_execution
+    }
+}
+
+#[derive(Debug)]
+pub enum OrderEvent {
+    /// A slice of the order was sent, it is confirmed on the following ticks
+    Sent { id: u64, signatures: Vec<Signature> },
+    /// A slice of the order landed with one of these signatures
+    Executed { id: u64, signatures: Vec<Signature> },
+    /// Sending the slice failed, or it failed or expired on chain. The order stays open and is retried at its `next_execution`
+    Retrying { id: u64, attempts: u32, error: String },
+    /// The slice failed `max_attempts` times in a row, or the engine stopped while sending it. The order is given up
+    Failed { id: u64, error: String },
+    /// Fetching a pool or saving the order book failed, nothing changed for the orders involved
+    Error(anyhow::Error),
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OrderBook {
+    next_id: u64,
+    orders: Vec<Order>,
+}
+
+// Executes limit and DCA orders through `DexTrait::buy`/`sell`, persisting the order book to a JSON file after every change.
+// Orders only keep the payer pubkey, its signer has to be registered with `add_signer` after a restart.
+pub struct OrderEngine {
+    pub path: PathBuf,
+    pub dexs: HashMap<DexType, Arc<dyn DexTrait>>,
+    pub max_attempts: u32,
+    pub retry_backoff: Duration,
+    signers: RwLock<HashMap<Pubkey, Arc<dyn TxSigner>>>,
+    book: Mutex<OrderBook>,
+    // Held for a whole tick, so that `run` and a manual `tick` never execute the same order twice
+    ticking: tokio::sync::Mutex<()>,
+    stopped: AtomicBool,
+}
+
+impl OrderEngine {
+    pub fn new(dexs: HashMap<DexType, Arc<dyn DexTrait>>, path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let path = path.into();
+        let book = Self::load(&path)?;
+
+        Ok(Self {
+            path,
+            dexs,
+            max_attempts: ORDER_MAX_ATTEMPTS,
+            retry_backoff: ORDER_RETRY_BACKOFF,
+            signers: RwLock::new(HashMap::new()),
+            book: Mutex::new(book),
+            ticking: tokio::sync::Mutex::new(()),
+            stopped: AtomicBool::new(false),
+        })
+    }
+
+    fn load(path: &Path) -> anyhow::Result<OrderBook> {
+        if !path.exists() {
+            return Ok(OrderBook::default());
+        }
+        let data = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    // Written to a temporary file first so that a crash never leaves a truncated order book
+    fn save(&self, book: &OrderBook) -> anyhow::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(book)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    pub fn add_signer(&self, signer: Arc<dyn TxSigner>) {
+        self.signers.write().unwrap().insert(signer.pubkey(), signer);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn place(
+        &self,
+        dex: DexType,
+        payer: &Pubkey,
+        mint: &Pubkey,
+        kind: OrderKind,
+        slippage_basis_points: u64,
+        fee: Option<PriorityFee>,
+        tip: Option<u64>,
+    ) -> anyhow::Result<u64> {
+        if !self.dexs.contains_key(&dex) {
+            return Err(anyhow::anyhow!("Dex not configured: {:?}", dex));
+        }
+
+        let now = Self::now();
+        let mut book = self.book.lock().unwrap();
+        let id = book.next_id;
+        book.next_id += 1;
+        book.orders.push(Order {
+            id,
+            dex,
+            payer: *payer,
+            mint: *mint,
+            kind,
+            slippage_basis_points,
+            fee,
+            tip,
+            status: OrderStatus::Open,
+            slices_done: 0,
+            created_at: now,
+            next_execution: now,
+            signatures: vec![],
+            attempts: 0,
+            last_error: None,
+            in_flight: None,
+        });
+        self.save(&book)?;
+
+        Ok(id)
+    }
+
+    pub fn cancel(&self, id: u64) -> anyhow::Result<()> {
+        let mut book = self.book.lock().unwrap();
+        let order = book
+            .orders
+            .iter_mut()
+            .find(|order| order.id == id)
+            .ok_or(anyhow::anyhow!("Order not found: {}", id))?;
+        if order.status != OrderStatus::Open {
+            return Err(anyhow::anyhow!("Order {} is not open: {:?}", id, order.status));
+        }
+        order.status = OrderStatus::Cancelled;
+        self.save(&book)
+    }
+
+    pub fn get_orders(&self) -> Vec<Order> {
+        self.book.lock().unwrap().orders.clone()
+    }
+
+    // Drops filled, cancelled and failed orders from the book, unless a slice of theirs is still in flight
+    pub fn prune(&self) -> anyhow::Result<()> {
+        let mut book = self.book.lock().unwrap();
+        book.orders.retain(|order| order.status == OrderStatus::Open || order.in_flight.is_some());
+        self.save(&book)
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    // Settles the slices in flight, then checks every due order once, sending the triggered ones.
+    // Concurrent calls run one after the other.
+    pub async fn tick(&self) -> anyhow::Result<Vec<OrderEvent>> {
+        let _ticking = self.ticking.lock().await;
+        let now = Self::now();
+        let mut events = self.settle_in_flight(now).await;
+        let due = self
+            .book
+            .lock()
+            .unwrap()
+            .orders
+            .iter()
+            .filter(|order| order.is_due(now))
+            .cloned()
+            .collect::<Vec<_>>();
+        if due.is_empty() {
+            if !events.is_empty() {
+                self.save(&self.book.lock().unwrap())?;
+            }
+            return Ok(events);
+        }
+
+        // One pool fetch per (dex, mint) for the limit orders
+        let keys = due
+            .iter()
+            .filter(|order| matches!(order.kind, OrderKind::LimitBuy { .. } | OrderKind::LimitSell { .. }))
+            .map(|order| (order.dex, order.mint))
+            .collect::<HashSet<_>>();
+        let pools = futures::future::join_all(
+            keys.into_iter()
+                .map(|(dex, mint)| async move { ((dex, mint), self.dexs[&dex].get_pool(&mint).await) }),
+        )
+        .await
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+        for ((dex, mint), pool_info) in pools.iter() {
+            if let Err(e) = pool_info {
+                events.push(OrderEvent::Error(anyhow::anyhow!("Failed to get {:?} pool for {}: {}", dex, mint, e)));
+            }
+        }
+
+        let executions = due
+            .into_iter()
+            .filter(|order| match pools.get(&(order.dex, order.mint)) {
+                Some(Ok(pool_info)) => order.kind.is_triggered(pool_info),
+                // Checked again on the next tick
+                Some(Err(_)) => false,
+                None => true,
+            })
+            .collect::<Vec<_>>();
+
+        // A crash between sending and saving must not send the slice again after a restart
+        {
+            let mut book = self.book.lock().unwrap();
+            for order in book.orders.iter_mut() {
+                if executions.iter().any(|execution| execution.id == order.id) {
+                    order.in_flight = Some(InFlightSlice {
+                        slice: order.slices_done,
+                        signatures: vec![],
+                        sent_at: now,
+                    });
+                }
+            }
+            if let Err(e) = self.save(&book) {
+                for order in book.orders.iter_mut() {
+                    if executions.iter().any(|execution| execution.id == order.id) {
+                        order.in_flight = None;
+                    }
+                }
+                events.push(OrderEvent::Error(e));
+                return Ok(events);
+            }
+        }
+
+        let results = futures::future::join_all(executions.into_iter().map(|order| async move {
+            let result = self.execute(&order).await;
+            (order, result)
+        }))
+        .await;
+
+        let mut book = self.book.lock().unwrap();
+        for (executed, result) in results {
+            let Some(order) = book.orders.iter_mut().find(|order| order.id == executed.id) else {
+                continue;
+            };
+            let Some(in_flight) = order.in_flight.as_mut() else {
+                continue;
+            };
+            let error = match result {
+                Ok(signatures) if !signatures.is_empty() => {
+                    in_flight.signatures = signatures.iter().map(|signature| signature.to_string()).collect();
+                    in_flight.sent_at = Self::now();
+                    events.push(OrderEvent::Sent { id: order.id, signatures });
+                    continue;
+                }
+                Ok(_) => "No transaction sent".to_string(),
+                Err(e) => e.to_string(),
+            };
+            order.in_flight = None;
+            if order.status == OrderStatus::Open {
+                events.push(self.fail_attempt(order, error, now));
+            }
+        }
+        self.save(&book)?;
+
+        Ok(events)
+    }
+
+    // Counts the slices that landed, and the ones that failed or were not confirmed in time as failed attempts
+    async fn settle_in_flight(&self, now: u64) -> Vec<OrderEvent> {
+        let mut signatures = HashMap::<DexType, Vec<Signature>>::new();
+        for order in self.book.lock().unwrap().orders.iter() {
+            if let Some(in_flight) = &order.in_flight {
+                signatures
+                    .entry(order.dex)
+                    .or_default()
+                    .extend(in_flight.signatures.iter().filter_map(|signature| signature.parse::<Signature>().ok()));
+            }
+        }
+
+        let mut events = vec![];
+        let mut statuses = HashMap::<Signature, Option<TransactionError>>::new();
+        // Without statuses the slices stay in flight, so a flaky RPC never counts as a failed attempt
+        let mut unsettled = HashSet::new();
+        for (dex, signatures) in signatures {
+            let Some(endpoint) = self.dexs.get(&dex).map(|dex| dex.get_trading_endpoint()) else {
+                unsettled.insert(dex);
+                continue;
+            };
+            for chunk in signatures.chunks(MAX_SIGNATURE_STATUSES) {
+                match endpoint.rpc.get_signature_statuses(chunk).await {
+                    Ok(response) => {
+                        for (signature, status) in chunk.iter().zip(response.value) {
+                            if let Some(status) = status.filter(|status| status.satisfies_commitment(CommitmentConfig::confirmed())) {
+                                statuses.insert(*signature, status.err);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        events.push(OrderEvent::Error(anyhow::anyhow!("Failed to get {:?} signature statuses: {}", dex, e)));
+                        unsettled.insert(dex);
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut book = self.book.lock().unwrap();
+        for order in book.orders.iter_mut().filter(|order| !unsettled.contains(&order.dex)) {
+            let Some(in_flight) = order.in_flight.take() else {
+                continue;
+            };
+            let signatures = in_flight
+                .signatures
+                .iter()
+                .filter_map(|signature| signature.parse::<Signature>().ok())
+                .collect::<Vec<_>>();
+
+            // Saved before sending and never updated, the engine stopped while sending and the slice may have landed
+            if signatures.is_empty() {
+                let error = format!("Interrupted while sending slice {}, it may have landed", in_flight.slice + 1);
+                order.last_error = Some(error.clone());
+                if order.status == OrderStatus::Open {
+                    order.status = OrderStatus::Failed(error.clone());
+                    events.push(OrderEvent::Failed { id: order.id, error });
+                }
+                continue;
+            }
+
+            let landed = signatures.iter().any(|signature| statuses.get(signature) == Some(&None));
+            let failed = signatures.iter().find_map(|signature| statuses.get(signature).cloned().flatten());
+            let error = if landed {
+                order.signatures.extend(in_flight.signatures);
+                order.slices_done += 1;
+                order.attempts = 0;
+                order.last_error = None;
+                if order.status == OrderStatus::Open {
+                    if order.slices_done >= order.kind.slices() {
+                        order.status = OrderStatus::Filled;
+                    } else if let OrderKind::DcaBuy { interval_secs, .. } | OrderKind::DcaSell { interval_secs, .. } = order.kind {
+                        order.next_execution = in_flight.sent_at + interval_secs;
+                    }
+                }
+                events.push(OrderEvent::Executed { id: order.id, signatures });
+                continue;
+            } else if let Some(err) = failed {
+                err.to_string()
+            } else if now >= in_flight.sent_at + ORDER_CONFIRM_TIMEOUT.as_secs() {
+                "Slice not confirmed".to_string()
+            } else {
+                order.in_flight = Some(in_flight);
+                continue;
+            };
+            if order.status == OrderStatus::Open {
+                events.push(self.fail_attempt(order, error, now));
+            }
+        }
+
+        events
+    }
+
+    fn fail_attempt(&self, order: &mut Order, error: String, now: u64) -> OrderEvent {
+        order.attempts += 1;
+        order.last_error = Some(error.clone());
+        if order.attempts >= self.max_attempts {
+            order.status = OrderStatus::Failed(error.clone());
+            OrderEvent::Failed { id: order.id, error }
+        } else {
+            order.next_execution = now + self.get_backoff(order.attempts).as_secs();
+            OrderEvent::Retrying {
+                id: order.id,
+                attempts: order.attempts,
+                error,
+            }
+        }
+    }
+
+    // Delay before the retry following the `attempts`-th consecutive failure
+    fn get_backoff(&self, attempts: u32) -> Duration {
+        self.retry_backoff
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(ORDER_MAX_BACKOFF)
+    }
+
+    async fn execute(&self, order: &Order) -> anyhow::Result<Vec<Signature>> {
+        let dex = self.dexs.get(&order.dex).ok_or(anyhow::anyhow!("Dex not configured: {:?}", order.dex))?;
+        let payer = self
+            .signers
+            .read()
+            .unwrap()
+            .get(&order.payer)
+            .cloned()
+            .ok_or(anyhow::anyhow!("No signer registered for {}", order.payer))?;
+
+        let slices = order.kind.slices();
+        match &order.kind {
+            OrderKind::LimitBuy { sol_amount, .. } => {
+                dex.buy(payer.as_ref(), &order.mint, *sol_amount, order.slippage_basis_points, order.fee, order.tip)
+                    .await
+            }
+            OrderKind::LimitSell { token_amount, .. } => {
+                dex.sell(
+                    payer.as_ref(),
+                    &order.mint,
+                    *token_amount,
+                    order.slippage_basis_points,
+                    false,
+                    order.fee,
+                    order.tip,
+                )
+                .await
+            }
+            OrderKind::DcaBuy { sol_amount, .. } => {
+                let sol_amount = OrderKind::slice_amount(*sol_amount, slices, order.slices_done);
+                dex.buy(payer.as_ref(), &order.mint, sol_amount, order.slippage_basis_points, order.fee, order.tip)
+                    .await
+            }
+            OrderKind::DcaSell { token_amount, .. } => {
+                let token_amount = OrderKind::slice_amount(*token_amount, slices, order.slices_done);
+                dex.sell(
+                    payer.as_ref(),
+                    &order.mint,
+                    TokenAmountType::Amount(token_amount),
+                    order.slippage_basis_points,
+                    false,
+                    order.fee,
+                    order.tip,
+                )
+                .await
+            }
+        }
+    }
+
+    // Ticks every `interval` until `stop` is called, sending what happened to `events`.
+    // A dropped receiver only means nobody listens, the orders keep being executed.
+    pub async fn run(&self, interval: Duration, events: UnboundedSender<OrderEvent>) -> anyhow::Result<()> {
+        while !self.stopped.load(Ordering::SeqCst) {
+            match self.tick().await {
+                Ok(tick_events) => {
+                    for event in tick_events {
+                        let _ = events.send(event);
+                    }
+                }
+                Err(e) => {
+                    let _ = events.send(OrderEvent::Error(e));
+                }
+            }
+            tokio::time::sleep(interval).await;
+        }
+
+        Ok(())
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+    }
+}
@@ -0,0 +1,514 @@
+use crate::{
+    common::{mint_info::MintInfo, signer::TxSigner, trading_endpoint::TradingEndpoint},
+    dex::{
+        dex_traits::DexTrait,
+        types::{PoolInfo, TokenAmountType},
+    },
+    instruction::builder::PriorityFee,
+};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey, signature::Signature, transaction::TransactionError};
+use solana_transaction_status::{UiTransactionEncoding, UiTransactionTokenBalance};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
+
+pub const ENTRY_CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+pub const ENTRY_CONFIRM_INTERVAL: Duration = Duration::from_secs(1);
+// An exit not confirmed by then has an expired blockhash, its rule is armed again
+pub const EXIT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(90);
+const BASIS_POINTS: u64 = 10_000;
+// Most signatures `getSignatureStatuses` accepts per request
+pub(crate) const MAX_SIGNATURE_STATUSES: usize = 256;
+
+// Thresholds are relative to the cost basis, in basis points of the position value after price impact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitTrigger {
+    /// Value rose by at least this many basis points
+    TakeProfit(u64),
+    /// Value fell by at least this many basis points
+    StopLoss(u64),
+    /// Value fell this many basis points below its highest point since entry
+    TrailingStop(u64),
+}
+
+impl ExitTrigger {
+    fn is_triggered(&self, value_bps: u64, peak_bps: u64) -> bool {
+        match self {
+            ExitTrigger::TakeProfit(gain) => value_bps >= BASIS_POINTS + gain,
+            ExitTrigger::StopLoss(loss) => value_bps <= BASIS_POINTS.saturating_sub(*loss),
+            ExitTrigger::TrailingStop(drop) => value_bps <= peak_bps * BASIS_POINTS.saturating_sub(*drop) / BASIS_POINTS,
+        }
+    }
+}
+
+// `TokenAmountType::Percent` is a share of the remaining position, not of the wallet balance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitRule {
+    pub trigger: ExitTrigger,
+    pub token_amount: TokenAmountType,
+}
+
+impl ExitRule {
+    pub fn new(trigger: ExitTrigger, token_amount: TokenAmountType) -> Self {
+        Self { trigger, token_amount }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExitConfig {
+    pub slippage_basis_points: u64,
+    pub fee: Option<PriorityFee>,
+    pub tip: Option<u64>,
+}
+
+// Sell of a triggered rule, its tokens stay in the position until one of its signatures confirms
+#[derive(Debug, Clone)]
+pub struct PendingExit {
+    pub id: u64,
+    pub rule: ExitRule,
+    pub value_bps: u64,
+    pub token_amount: u64,
+    pub min_sol_amount: u64,
+    // Empty while the sell is being built and broadcast
+    pub signatures: Vec<Signature>,
+    pub sent: Instant,
+}
+
+pub struct Position {
+    pub dex: Arc<dyn DexTrait>,
+    pub payer: Arc<dyn TxSigner>,
+    pub mint: Pubkey,
+    pub mint_info: MintInfo,
+    pub token_amount: u64,
+    pub cost_basis: u64,
+    pub peak_bps: u64,
+    pub rules: Vec<ExitRule>,
+    pub pending: Vec<PendingExit>,
+    pub exit: ExitConfig,
+}
+
+impl Position {
+    // Value of the remaining tokens against the cost basis, in basis points
+    pub fn value_bps(&self, pool_info: &PoolInfo) -> u64 {
+        if self.cost_basis == 0 {
+            return 0;
+        }
+
+        let sol_amount = self.dex.quote_sell(pool_info, &self.mint_info, self.token_amount).sol_amount;
+        (sol_amount as u128 * BASIS_POINTS as u128 / self.cost_basis as u128) as u64
+    }
+
+    // Tokens not already being sold by a pending exit
+    pub fn available_amount(&self) -> u64 {
+        self.token_amount.saturating_sub(self.pending.iter().map(|pending| pending.token_amount).sum())
+    }
+
+    fn is_open(&self) -> bool {
+        self.token_amount > 0 && !(self.rules.is_empty() && self.pending.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// Sell broadcast, the position is reduced once it confirms
+    Sent,
+    /// Sell confirmed with this signature and the position reduced
+    Confirmed(Signature),
+    /// Sell failed to build, broadcast or land, its rule fires again on the next update
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ExitEvent {
+    pub payer: Pubkey,
+    pub mint: Pubkey,
+    pub rule: ExitRule,
+    pub value_bps: u64,
+    pub token_amount: u64,
+    pub min_sol_amount: u64,
+    pub signatures: Vec<Signature>,
+    pub status: ExitStatus,
+}
+
+impl ExitEvent {
+    fn new(payer: Pubkey, mint: Pubkey, pending: PendingExit, status: ExitStatus) -> Self {
+        Self {
+            payer,
+            mint,
+            rule: pending.rule,
+            value_bps: pending.value_bps,
+            token_amount: pending.token_amount,
+            min_sol_amount: pending.min_sol_amount,
+            signatures: pending.signatures,
+            status,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PositionEvent {
+    Exit(ExitEvent),
+    /// Fetching or evaluating the pool of `mint` failed, its positions are evaluated again on the next update
+    Error(Pubkey, anyhow::Error),
+}
+
+// Sell planned while the positions are locked and sent once they are released
+struct PlannedExit {
+    key: (Pubkey, Pubkey),
+    id: u64,
+    dex: Arc<dyn DexTrait>,
+    payer: Arc<dyn TxSigner>,
+    extra_address: Option<Pubkey>,
+    token_amount: u64,
+    min_sol_amount: u64,
+    exit: ExitConfig,
+}
+
+// Watches open positions and sells them when an exit rule triggers.
+// Pool updates come from `run` polling `get_pool`, or are pushed by a subscription source through `on_pool_update`/`run_stream`.
+pub struct PositionManager {
+    pub endpoint: Arc<TradingEndpoint>,
+    pub positions: Mutex<HashMap<(Pubkey, Pubkey), Position>>,
+    blockhash: RwLock<Option<(Hash, Instant)>>,
+    next_exit_id: AtomicU64,
+    stopped: AtomicBool,
+}
+
+impl PositionManager {
+    pub fn new(endpoint: Arc<TradingEndpoint>) -> Self {
+        Self {
+            endpoint,
+            positions: Mutex::new(HashMap::new()),
+            blockhash: RwLock::new(None),
+            next_exit_id: AtomicU64::new(0),
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn open(
+        &self,
+        dex: Arc<dyn DexTrait>,
+        payer: Arc<dyn TxSigner>,
+        mint: &Pubkey,
+        token_amount: u64,
+        cost_basis: u64,
+        rules: Vec<ExitRule>,
+        exit: ExitConfig,
+    ) -> anyhow::Result<()> {
+        // Without a cost basis the position would read as down 100% and its stop losses would sell it right away
+        if token_amount == 0 || cost_basis == 0 || rules.is_empty() {
+            return Err(anyhow::anyhow!("Position without tokens, cost basis or exit rules: {}", mint));
+        }
+
+        let mint_info = self.endpoint.get_mint_info(mint).await?;
+        let position = Position {
+            dex,
+            payer: payer.clone(),
+            mint: *mint,
+            mint_info,
+            token_amount,
+            cost_basis,
+            peak_bps: BASIS_POINTS,
+            rules,
+            pending: vec![],
+            exit,
+        };
+        self.positions.lock().unwrap().insert((payer.pubkey(), *mint), position);
+
+        Ok(())
+    }
+
+    // Opens the position from a buy once it is confirmed, the cost basis being the SOL the payer spent minus the network fee
+    pub async fn open_from_buy(
+        &self,
+        dex: Arc<dyn DexTrait>,
+        payer: Arc<dyn TxSigner>,
+        mint: &Pubkey,
+        signature: &Signature,
+        rules: Vec<ExitRule>,
+        exit: ExitConfig,
+    ) -> anyhow::Result<(u64, u64)> {
+        let (token_amount, cost_basis) = self.get_buy_entry(&payer.pubkey(), mint, signature).await?;
+        self.open(dex, payer, mint, token_amount, cost_basis, rules, exit).await?;
+        Ok((token_amount, cost_basis))
+    }
+
+    // Tokens received and SOL spent by `owner` in the confirmed transaction
+    pub async fn get_buy_entry(&self, owner: &Pubkey, mint: &Pubkey, signature: &Signature) -> anyhow::Result<(u64, u64)> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+
+        let started = Instant::now();
+        let tx = loop {
+            if let Ok(tx) = self.endpoint.rpc.get_transaction_with_config(signature, config).await {
+                break tx;
+            }
+            if started.elapsed() >= ENTRY_CONFIRM_TIMEOUT {
+                return Err(anyhow::anyhow!("Transaction confirmation timedout: {:?}", signature));
+            }
+            tokio::time::sleep(ENTRY_CONFIRM_INTERVAL).await;
+        };
+
+        let meta = tx.transaction.meta.ok_or(anyhow::anyhow!("Transaction meta not found: {}", signature))?;
+        if let Some(err) = meta.err {
+            return Err(anyhow::anyhow!("Buy transaction failed: {}: {:?}", signature, err));
+        }
+
+        let token_balance = |balances: Option<Vec<UiTransactionTokenBalance>>| -> u64 {
+            balances
+                .unwrap_or_default()
+                .iter()
+                .filter(|balance| balance.mint == mint.to_string() && Option::<&String>::from(balance.owner.as_ref()) == Some(&owner.to_string()))
+                .filter_map(|balance| balance.ui_token_amount.amount.parse::<u64>().ok())
+                .sum()
+        };
+        let token_amount = token_balance(meta.post_token_balances.into()).saturating_sub(token_balance(meta.pre_token_balances.into()));
+        // The fee payer is always the first account
+        let sol_amount = meta.pre_balances[0].saturating_sub(meta.post_balances[0]).saturating_sub(meta.fee);
+
+        Ok((token_amount, sol_amount))
+    }
+
+    pub fn close(&self, payer: &Pubkey, mint: &Pubkey) -> Option<Position> {
+        self.positions.lock().unwrap().remove(&(*payer, *mint))
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    pub async fn refresh_blockhash(&self) -> anyhow::Result<Hash> {
+        let blockhash = self.endpoint.get_latest_blockhash().await?;
+        *self.blockhash.write().unwrap() = Some((blockhash, Instant::now()));
+        Ok(blockhash)
+    }
+
+    // Cached so that exits only build, sign and send
+    async fn get_blockhash(&self, max_age: Duration) -> anyhow::Result<Hash> {
+        match *self.blockhash.read().unwrap() {
+            Some((blockhash, updated)) if updated.elapsed() < max_age => return Ok(blockhash),
+            _ => {}
+        }
+        self.refresh_blockhash().await
+    }
+
+    // Settles the pending exits on `mint`, then evaluates its positions against the new pool state and sends the triggered exits.
+    // The positions are only locked to plan and record exits, never while signing or querying the RPC.
+    pub async fn on_pool_update(&self, mint: &Pubkey, pool_info: &PoolInfo) -> anyhow::Result<Vec<ExitEvent>> {
+        let mut events = self.settle_pending_exits(mint).await;
+        let blockhash = self.get_blockhash(Duration::from_secs(30)).await?;
+        let mut pool_info = pool_info.clone();
+        let mut planned = vec![];
+
+        {
+            let mut positions = self.positions.lock().unwrap();
+            for (key, position) in positions.iter_mut().filter(|(_, position)| position.mint == *mint) {
+                let value_bps = position.value_bps(&pool_info);
+                let peak_bps = position.peak_bps.max(value_bps);
+                position.peak_bps = peak_bps;
+
+                let (triggered, armed): (Vec<_>, Vec<_>) = std::mem::take(&mut position.rules)
+                    .into_iter()
+                    .partition(|rule| rule.trigger.is_triggered(value_bps, peak_bps));
+                position.rules = armed;
+
+                for rule in triggered {
+                    let available = position.available_amount();
+                    let token_amount = match rule.token_amount {
+                        TokenAmountType::Percent(percent) => available * percent.min(100) / 100,
+                        TokenAmountType::Amount(amount) => amount.min(available),
+                    };
+                    if token_amount == 0 {
+                        position.rules.push(rule);
+                        continue;
+                    }
+
+                    let swap = position
+                        .dex
+                        .quote_sell_item(&mut pool_info, &position.mint_info, token_amount, position.exit.slippage_basis_points);
+                    let id = self.next_exit_id.fetch_add(1, Ordering::SeqCst);
+                    position.pending.push(PendingExit {
+                        id,
+                        rule,
+                        value_bps,
+                        token_amount: swap.token_amount,
+                        min_sol_amount: swap.sol_amount,
+                        signatures: vec![],
+                        sent: Instant::now(),
+                    });
+                    planned.push(PlannedExit {
+                        key: *key,
+                        id,
+                        dex: position.dex.clone(),
+                        payer: position.payer.clone(),
+                        extra_address: pool_info.extra_address,
+                        token_amount: swap.token_amount,
+                        min_sol_amount: swap.sol_amount,
+                        exit: position.exit,
+                    });
+                }
+            }
+        }
+
+        for plan in planned {
+            let result = plan.dex.sell_immediately(
+                plan.payer.as_ref(),
+                mint,
+                plan.extra_address.as_ref(),
+                plan.token_amount,
+                plan.min_sol_amount,
+                false,
+                blockhash,
+                plan.exit.fee,
+                plan.exit.tip,
+            );
+
+            let mut positions = self.positions.lock().unwrap();
+            let Some(position) = positions.get_mut(&plan.key) else {
+                continue;
+            };
+            let Some(index) = position.pending.iter().position(|pending| pending.id == plan.id) else {
+                continue;
+            };
+            match result {
+                Ok(signatures) if !signatures.is_empty() => {
+                    let pending = &mut position.pending[index];
+                    pending.signatures = signatures;
+                    pending.sent = Instant::now();
+                    events.push(ExitEvent::new(plan.key.0, *mint, pending.clone(), ExitStatus::Sent));
+                }
+                result => {
+                    let reason = result.err().map_or("No SWQoS client sent the exit".to_string(), |e| e.to_string());
+                    let pending = position.pending.remove(index);
+                    position.rules.push(pending.rule);
+                    events.push(ExitEvent::new(plan.key.0, *mint, pending, ExitStatus::Failed(reason)));
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    // Reduces the positions by the pending exits that confirmed, and re-arms the rules of those that failed or expired
+    async fn settle_pending_exits(&self, mint: &Pubkey) -> Vec<ExitEvent> {
+        let signatures = self
+            .positions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|position| position.mint == *mint)
+            .flat_map(|position| position.pending.iter().flat_map(|pending| pending.signatures.clone()))
+            .collect::<Vec<_>>();
+        let mut statuses = HashMap::<Signature, Option<TransactionError>>::new();
+        for chunk in signatures.chunks(MAX_SIGNATURE_STATUSES) {
+            // Without statuses the exits stay pending, so a flaky RPC never holds back the exit rules
+            let Ok(response) = self.endpoint.rpc.get_signature_statuses(chunk).await else {
+                return vec![];
+            };
+            for (signature, status) in chunk.iter().zip(response.value) {
+                if let Some(status) = status.filter(|status| status.satisfies_commitment(CommitmentConfig::confirmed())) {
+                    statuses.insert(*signature, status.err);
+                }
+            }
+        }
+
+        let mut events = vec![];
+        let mut positions = self.positions.lock().unwrap();
+        for position in positions.values_mut().filter(|position| position.mint == *mint) {
+            let payer = position.payer.pubkey();
+            for pending in std::mem::take(&mut position.pending) {
+                let landed = pending.signatures.iter().find(|signature| statuses.get(*signature) == Some(&None));
+                let failed = pending.signatures.iter().find_map(|signature| statuses.get(signature).cloned().flatten());
+
+                if let Some(signature) = landed.copied() {
+                    // An earlier exit landing in the same poll may already have sold the whole position
+                    let token_amount = pending.token_amount.min(position.token_amount);
+                    if token_amount == position.token_amount {
+                        position.cost_basis = 0;
+                    } else {
+                        position.cost_basis -= (position.cost_basis as u128 * token_amount as u128 / position.token_amount as u128) as u64;
+                    }
+                    position.token_amount -= token_amount;
+                    events.push(ExitEvent::new(payer, *mint, pending, ExitStatus::Confirmed(signature)));
+                } else if let Some(err) = failed {
+                    position.rules.push(pending.rule);
+                    events.push(ExitEvent::new(payer, *mint, pending, ExitStatus::Failed(err.to_string())));
+                } else if !pending.signatures.is_empty() && pending.sent.elapsed() >= EXIT_CONFIRM_TIMEOUT {
+                    position.rules.push(pending.rule);
+                    events.push(ExitEvent::new(payer, *mint, pending, ExitStatus::Failed("Exit not confirmed".to_string())));
+                } else {
+                    position.pending.push(pending);
+                }
+            }
+        }
+        positions.retain(|_, position| position.is_open());
+
+        events
+    }
+
+    // Polls the pool of every open position each `interval` until `stop` is called, sending what happened to `events`
+    pub async fn run(&self, interval: Duration, events: UnboundedSender<PositionEvent>) -> anyhow::Result<()> {
+        while !self.stopped.load(Ordering::SeqCst) {
+            let dexs = self
+                .positions
+                .lock()
+                .unwrap()
+                .values()
+                .map(|position| (position.mint, position.dex.clone()))
+                .collect::<HashMap<_, _>>();
+
+            let (pools, _) = tokio::join!(
+                futures::future::join_all(dexs.iter().map(|(mint, dex)| async move { (*mint, dex.get_pool(mint).await) })),
+                self.refresh_blockhash()
+            );
+            for (mint, pool_info) in pools {
+                let result = match pool_info {
+                    Ok(pool_info) => self.on_pool_update(&mint, &pool_info).await,
+                    Err(e) => Err(e),
+                };
+                Self::emit(&events, mint, result);
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+
+        Ok(())
+    }
+
+    // Consumes (mint, pool) updates from a subscription source until the channel closes or `stop` is called
+    pub async fn run_stream(&self, mut updates: Receiver<(Pubkey, PoolInfo)>, events: UnboundedSender<PositionEvent>) -> anyhow::Result<()> {
+        while let Some((mint, pool_info)) = updates.recv().await {
+            if self.stopped.load(Ordering::SeqCst) {
+                break;
+            }
+            Self::emit(&events, mint, self.on_pool_update(&mint, &pool_info).await);
+        }
+
+        Ok(())
+    }
+
+    // A dropped receiver only means nobody listens, the positions keep being managed
+    fn emit(events: &UnboundedSender<PositionEvent>, mint: Pubkey, result: anyhow::Result<Vec<ExitEvent>>) {
+        match result {
+            Ok(exits) => {
+                for exit in exits {
+                    let _ = events.send(PositionEvent::Exit(exit));
+                }
+            }
+            Err(e) => {
+                let _ = events.send(PositionEvent::Error(mint, e));
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const ASTRALANE_TIP_ACCOUNTS: &[Pubkey] = &[
+    pubkey!("astrazznxsGUhWShqgNtAdfrzP2G83DzcWVJDxwV9bF"),
+    pubkey!("astra4uejePWneqNaJKuFFA8oonqCE1sqF6b45kDMZm"),
+    pubkey!("astra9xWY93QyfG6yM8zwsKsRodscjQ2uU2HKNL5prk"),
+    pubkey!("astraRVUuTHjpwEVvNBeQEgwYx9w9CFyfxjYoobCZhL"),
+];
+
+pub const ASTRALANE_ENDPOINT_FRA: &str = "http://fr.gateway.astralane.io";
+pub const ASTRALANE_ENDPOINT_AMS: &str = "http://ams.gateway.astralane.io";
+pub const ASTRALANE_ENDPOINT_NY: &str = "http://ny.gateway.astralane.io";
+pub const ASTRALANE_ENDPOINT_TOKYO: &str = "http://jp.gateway.astralane.io";
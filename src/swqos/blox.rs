@@ -1,5 +1,5 @@
 use super::{
-    swqos_rpc::{SWQoSClientTrait, SWQoSRequest},
+    swqos_rpc::{EndpointLatency, SWQoSClientTrait, SWQoSHttpClient, SWQoSPing, SWQoSRequest},
     SWQoSTrait,
 };
 use crate::swqos::swqos_rpc::FormatBase64VersionedTransaction;
@@ -22,24 +22,46 @@ pub const BLOX_ENDPOINT_UK: &str = "https://uk.solana.dex.blxrbdn.com";
 pub const BLOX_ENDPOINT_LA: &str = "https://la.solana.dex.blxrbdn.com";
 pub const BLOX_ENDPOINT_TOKYO: &str = "https://tokyo.solana.dex.blxrbdn.com";
 
+// Leader-aware submission: bloXroute holds the transaction back from slot leaders below the reputation level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BloxSubmitProtection {
+    Low,
+    Medium,
+    High,
+}
+
+impl BloxSubmitProtection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BloxSubmitProtection::Low => "SP_LOW",
+            BloxSubmitProtection::Medium => "SP_MEDIUM",
+            BloxSubmitProtection::High => "SP_HIGH",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct BloxClient {
     pub rpc_client: Arc<RpcClient>,
     pub swqos_endpoint: String,
     pub swqos_header: Option<(String, String)>,
-    pub swqos_client: Arc<reqwest::Client>,
+    pub swqos_client: Arc<SWQoSHttpClient>,
+    pub submit_protection: Option<BloxSubmitProtection>,
 }
 
 #[async_trait::async_trait]
 impl SWQoSTrait for BloxClient {
     async fn send_transaction(&self, transaction: VersionedTransaction) -> anyhow::Result<()> {
-        let body = serde_json::json!({
+        let mut body = serde_json::json!({
             "transaction": {
                 "content": transaction.to_base64_string(),
             },
             "frontRunningProtection": false,
             "useStakedRPCs": true,
         });
+        if let Some(submit_protection) = self.submit_protection {
+            body["submitProtection"] = submit_protection.as_str().into();
+        }
 
         self.swqos_client
             .swqos_json_post(
@@ -88,17 +110,37 @@ impl SWQoSTrait for BloxClient {
     fn get_name(&self) -> &str {
         "blox"
     }
+
+    fn get_endpoint(&self) -> &str {
+        &self.swqos_endpoint
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.swqos_client
+            .swqos_ping(&SWQoSPing::Get(self.swqos_endpoint.clone()), self.swqos_header.clone())
+            .await
+    }
+
+    fn get_latency(&self) -> Option<EndpointLatency> {
+        Some(self.swqos_client.get_latency())
+    }
 }
 
 impl BloxClient {
     pub fn new(rpc_client: Arc<RpcClient>, endpoint: String, auth_token: String) -> Self {
-        let swqos_client = reqwest::Client::new_swqos_client();
-
         Self {
             rpc_client,
             swqos_endpoint: endpoint,
             swqos_header: Some(("Authorization".to_string(), auth_token)),
-            swqos_client: Arc::new(swqos_client),
+            swqos_client: Arc::new(SWQoSHttpClient::new()),
+            submit_protection: None,
+        }
+    }
+
+    pub fn new_leader_aware(rpc_client: Arc<RpcClient>, endpoint: String, auth_token: String, submit_protection: BloxSubmitProtection) -> Self {
+        Self {
+            submit_protection: Some(submit_protection),
+            ..Self::new(rpc_client, endpoint, auth_token)
         }
     }
 }
@@ -1,17 +1,14 @@
 use super::{
-    swqos_rpc::{SWQoSClientTrait, SWQoSRequest},
+    swqos_rpc::{EndpointLatency, SWQoSClientTrait, SWQoSHttpClient, SWQoSPing, SWQoSRequest},
     SWQoSTrait,
 };
-use crate::instruction::builder::{build_transaction, PriorityFee};
+use crate::{
+    common::signer::TxSigner,
+    instruction::builder::{build_transaction, PriorityFee},
+};
 use rand::seq::IndexedRandom;
 use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
-use solana_sdk::{
-    commitment_config::CommitmentConfig,
-    pubkey::Pubkey,
-    signature::{Keypair, Signature},
-    signer::Signer,
-    transaction::VersionedTransaction,
-};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
 use solana_transaction_status::UiTransactionEncoding;
 use spl_associated_token_account::{get_associated_token_address, instruction::create_associated_token_account_idempotent};
 use std::sync::Arc;
@@ -23,7 +20,8 @@ pub struct DefaultSWQoSClient {
     pub tip_accounts: Vec<Pubkey>,
     pub swqos_endpoint: String,
     pub swqos_header: Option<(String, String)>,
-    pub swqos_client: Arc<reqwest::Client>,
+    pub swqos_client: Arc<SWQoSHttpClient>,
+    pub ping: SWQoSPing,
 }
 
 #[async_trait::async_trait]
@@ -57,6 +55,18 @@ impl SWQoSTrait for DefaultSWQoSClient {
     fn get_name(&self) -> &str {
         &self.name
     }
+
+    fn get_endpoint(&self) -> &str {
+        &self.swqos_endpoint
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.swqos_client.swqos_ping(&self.ping, self.swqos_header.clone()).await
+    }
+
+    fn get_latency(&self) -> Option<EndpointLatency> {
+        Some(self.swqos_client.get_latency())
+    }
 }
 
 pub struct TransferInfo {
@@ -66,19 +76,24 @@ pub struct TransferInfo {
 
 impl DefaultSWQoSClient {
     pub fn new(name: &str, rpc_client: Arc<RpcClient>, endpoint: String, header: Option<(String, String)>, tip_accounts: Vec<Pubkey>) -> Self {
-        let swqos_client = reqwest::Client::new_swqos_client();
-
         Self {
             name: name.to_string(),
             rpc_client,
             tip_accounts,
+            ping: SWQoSPing::JsonRpc(endpoint.clone(), "getHealth".to_string()),
             swqos_endpoint: endpoint,
             swqos_header: header,
-            swqos_client: Arc::new(swqos_client),
+            swqos_client: Arc::new(SWQoSHttpClient::new()),
         }
     }
 
-    pub async fn transfer(&self, from: &Keypair, to: &Pubkey, amount: u64, fee: Option<PriorityFee>) -> anyhow::Result<Signature> {
+    // Replaces the default `getHealth` ping, for providers that do not serve JSON-RPC reads
+    pub fn with_ping(mut self, ping: SWQoSPing) -> Self {
+        self.ping = ping;
+        self
+    }
+
+    pub async fn transfer(&self, from: &dyn TxSigner, to: &Pubkey, amount: u64, fee: Option<PriorityFee>) -> anyhow::Result<Signature> {
         let blockhash = self.rpc_client.get_latest_blockhash().await?;
         let instruction = solana_sdk::system_instruction::transfer(&from.pubkey(), to, amount);
         let transaction = build_transaction(from, vec![instruction], blockhash, fee, None, None)?;
@@ -87,7 +102,7 @@ impl DefaultSWQoSClient {
         Ok(signature)
     }
 
-    pub async fn batch_transfer(&self, from: &Keypair, to: Vec<TransferInfo>, fee: Option<PriorityFee>) -> anyhow::Result<Signature> {
+    pub async fn batch_transfer(&self, from: &dyn TxSigner, to: Vec<TransferInfo>, fee: Option<PriorityFee>) -> anyhow::Result<Signature> {
         let blockhash = self.rpc_client.get_latest_blockhash().await?;
         let instructions = to
             .iter()
@@ -99,11 +114,11 @@ impl DefaultSWQoSClient {
         Ok(signature)
     }
 
-    pub async fn spl_transfer(&self, from: &Keypair, to: &Pubkey, mint: &Pubkey, amount: u64, fee: Option<PriorityFee>) -> anyhow::Result<Signature> {
+    pub async fn spl_transfer(&self, from: &dyn TxSigner, to: &Pubkey, mint: &Pubkey, amount: u64, fee: Option<PriorityFee>) -> anyhow::Result<Signature> {
         let blockhash = self.rpc_client.get_latest_blockhash().await?;
         let from_ata = get_associated_token_address(&from.pubkey(), mint);
         let to_ata = get_associated_token_address(to, mint);
-        let create_ata = create_associated_token_account_idempotent(&from.pubkey(), to, &mint, &spl_token::ID);
+        let create_ata = create_associated_token_account_idempotent(&from.pubkey(), to, mint, &spl_token::ID);
         let instruction = spl_token::instruction::transfer(&spl_token::ID, &from_ata, &to_ata, &from.pubkey(), &[], amount)?;
         let transaction = build_transaction(from, vec![create_ata, instruction], blockhash, fee, None, None)?;
         let signature = transaction.signatures[0];
@@ -111,14 +126,14 @@ impl DefaultSWQoSClient {
         Ok(signature)
     }
 
-    pub async fn spl_batch_transfer(&self, from: &Keypair, to: Vec<TransferInfo>, mint: &Pubkey, fee: Option<PriorityFee>) -> anyhow::Result<Signature> {
+    pub async fn spl_batch_transfer(&self, from: &dyn TxSigner, to: Vec<TransferInfo>, mint: &Pubkey, fee: Option<PriorityFee>) -> anyhow::Result<Signature> {
         let blockhash = self.rpc_client.get_latest_blockhash().await?;
         let from_ata = get_associated_token_address(&from.pubkey(), mint);
         let mut instructions = Vec::new();
 
         for transfer in &to {
             let to_ata = get_associated_token_address(&transfer.to, mint);
-            let create_ata = create_associated_token_account_idempotent(&from.pubkey(), &transfer.to, &mint, &spl_token::ID);
+            let create_ata = create_associated_token_account_idempotent(&from.pubkey(), &transfer.to, mint, &spl_token::ID);
             let instruction = spl_token::instruction::transfer(&spl_token::ID, &from_ata, &to_ata, &from.pubkey(), &[], transfer.amount)?;
             instructions.push(create_ata);
             instructions.push(instruction);
@@ -137,7 +152,7 @@ impl DefaultSWQoSClient {
             if let Ok(tx) = self
                 .rpc_client
                 .get_transaction_with_config(
-                    &signature,
+                    signature,
                     RpcTransactionConfig {
                         encoding: Some(UiTransactionEncoding::Json),
                         commitment: Some(CommitmentConfig::confirmed()),
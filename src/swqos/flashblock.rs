@@ -0,0 +1,94 @@
+use super::{
+    swqos_rpc::{EndpointLatency, FormatBase64VersionedTransaction, SWQoSClientTrait, SWQoSHttpClient, SWQoSPing, SWQoSRequest},
+    SWQoSTrait,
+};
+use rand::seq::IndexedRandom;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey, pubkey::Pubkey, transaction::VersionedTransaction};
+use std::sync::Arc;
+
+pub const FLASHBLOCK_TIP_ACCOUNTS: &[Pubkey] = &[
+    pubkey!("FLaShB3iXXTWE1vu9wQsChUKq3HFtpMAhb8kAh1pf1wi"),
+    pubkey!("FLashhsorBmM9dLpuq6qATawcpqk1Y2aqaZfkd48iT3W"),
+    pubkey!("FLaSHJNm5dWYzEgnHJWWJP5ccu128Mu61NJLxUf7mUXU"),
+    pubkey!("FLaSHR4Vv7sttd6TyDF4yR1bJyAxRwWKbohDytEMu3wL"),
+    pubkey!("FLASHRzANfcAKDuQ3RXv9hbkBy4WVEKDzoAgxJ56DiE4"),
+    pubkey!("FLasHstqx11M8W56zrSEqkCyhMCCpr6ze6Mjdvqope5s"),
+    pubkey!("FLAShWTjcweNT4NSotpjpxAkwxUr2we3eXQGhpTVzRwy"),
+    pubkey!("FLasHXTqrbNvpWFB6grN47HGZfK6pze9HLNTgbukfPSk"),
+    pubkey!("FLAshyAyBcKb39KPxSzXcepiS8iDYUhDGwJcJDPX4g2B"),
+    pubkey!("FLAsHZTRcf3Dy1APaz6j74ebdMC6Xx4g6i9YxjyrDybR"),
+];
+
+pub const FLASHBLOCK_ENDPOINT_FRA: &str = "http://fra.flashblock.trade";
+pub const FLASHBLOCK_ENDPOINT_AMS: &str = "http://ams.flashblock.trade";
+pub const FLASHBLOCK_ENDPOINT_NY: &str = "http://ny.flashblock.trade";
+pub const FLASHBLOCK_ENDPOINT_SLC: &str = "http://slc.flashblock.trade";
+pub const FLASHBLOCK_ENDPOINT_SINGAPORE: &str = "http://singapore.flashblock.trade";
+pub const FLASHBLOCK_ENDPOINT_TOKYO: &str = "http://tokyo.flashblock.trade";
+
+#[derive(Clone)]
+pub struct FlashblockClient {
+    pub rpc_client: Arc<RpcClient>,
+    pub swqos_endpoint: String,
+    pub swqos_header: Option<(String, String)>,
+    pub swqos_client: Arc<SWQoSHttpClient>,
+}
+
+#[async_trait::async_trait]
+impl SWQoSTrait for FlashblockClient {
+    async fn send_transaction(&self, transaction: VersionedTransaction) -> anyhow::Result<()> {
+        self.send_transactions(vec![transaction]).await
+    }
+
+    async fn send_transactions(&self, transactions: Vec<VersionedTransaction>) -> anyhow::Result<()> {
+        let body = serde_json::json!({
+            "transactions": transactions.iter().map(|tx| tx.to_base64_string()).collect::<Vec<_>>(),
+        });
+
+        self.swqos_client
+            .swqos_json_post(
+                SWQoSRequest {
+                    name: self.get_name().to_string(),
+                    url: format!("{}/api/v2/submit-batch", self.swqos_endpoint),
+                    auth_header: self.swqos_header.clone(),
+                    transactions,
+                },
+                body,
+            )
+            .await
+    }
+
+    fn get_tip_account(&self) -> Option<Pubkey> {
+        Some(*FLASHBLOCK_TIP_ACCOUNTS.choose(&mut rand::rng())?)
+    }
+
+    fn get_name(&self) -> &str {
+        "flashblock"
+    }
+
+    fn get_endpoint(&self) -> &str {
+        &self.swqos_endpoint
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.swqos_client
+            .swqos_ping(&SWQoSPing::Get(self.swqos_endpoint.clone()), self.swqos_header.clone())
+            .await
+    }
+
+    fn get_latency(&self) -> Option<EndpointLatency> {
+        Some(self.swqos_client.get_latency())
+    }
+}
+
+impl FlashblockClient {
+    pub fn new(rpc_client: Arc<RpcClient>, endpoint: String, auth_token: String) -> Self {
+        Self {
+            rpc_client,
+            swqos_endpoint: endpoint,
+            swqos_header: Some(("Authorization".to_string(), auth_token)),
+            swqos_client: Arc::new(SWQoSHttpClient::new()),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::{
+    swqos_rpc::{EndpointLatency, FormatBase64VersionedTransaction, SWQoSClientTrait, SWQoSHttpClient, SWQoSPing, SWQoSRequest},
+    SWQoSTrait,
+};
+use rand::seq::IndexedRandom;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey, pubkey::Pubkey, transaction::VersionedTransaction};
+use std::sync::Arc;
+
+pub const HELIUS_SENDER_TIP_ACCOUNTS: &[Pubkey] = &[
+    pubkey!("4ACfpUFoaSD9bfPdeu6DBt89gB6ENTeHBXCAi87NhDEE"),
+    pubkey!("D2L6yPZ2FmmmTKPgzaMKdhu6EWZcTpLy1Vhx8uvZe7NZ"),
+    pubkey!("9bnz4RShgq1hAnLnZbP8kbgBg1kEmcJBYQq3gQbmnSta"),
+    pubkey!("5VY91ws6B2hMmBFRsXkoAAdsPHBJwRfBht4DXox3xkwn"),
+    pubkey!("2nyhqdwKcJZR2vcqCyrYsaPVdAnFoJjiksCXJ7hfEYgD"),
+    pubkey!("2q5pghRs6arqVjRvT5gfgWfWcHWmw1ZuCzphgd5KfWGJ"),
+    pubkey!("wyvPkWjVZz1M8fHQnMMCDTQDbkManefNNhweYk5WkcF"),
+    pubkey!("3KCKozbAaF75qEU33jtzozcJ29yJuaLJTy2jFdzUY8bT"),
+    pubkey!("4vieeGHPYPG2MmyPRcYjdiDmmhN3ww7hsFNap8pVN3Ey"),
+    pubkey!("4TQLFNWK8AovT1gFvda5jfw2oJeRMKEmw7aH6MGBJ3or"),
+];
+
+pub const HELIUS_SENDER_ENDPOINT_GLOBAL: &str = "https://sender.helius-rpc.com";
+pub const HELIUS_SENDER_ENDPOINT_FRA: &str = "http://fra-sender.helius-rpc.com";
+pub const HELIUS_SENDER_ENDPOINT_AMS: &str = "http://ams-sender.helius-rpc.com";
+pub const HELIUS_SENDER_ENDPOINT_LONDON: &str = "http://lon-sender.helius-rpc.com";
+pub const HELIUS_SENDER_ENDPOINT_NY: &str = "http://ewr-sender.helius-rpc.com";
+pub const HELIUS_SENDER_ENDPOINT_SLC: &str = "http://slc-sender.helius-rpc.com";
+pub const HELIUS_SENDER_ENDPOINT_SINGAPORE: &str = "http://sg-sender.helius-rpc.com";
+pub const HELIUS_SENDER_ENDPOINT_TOKYO: &str = "http://tyo-sender.helius-rpc.com";
+
+// Helius Sender, which forwards to both staked connections and Jito. It requires a tip and skipped preflight.
+#[derive(Clone)]
+pub struct HeliusSenderClient {
+    pub rpc_client: Arc<RpcClient>,
+    pub swqos_endpoint: String,
+    pub swqos_client: Arc<SWQoSHttpClient>,
+}
+
+#[async_trait::async_trait]
+impl SWQoSTrait for HeliusSenderClient {
+    async fn send_transaction(&self, transaction: VersionedTransaction) -> anyhow::Result<()> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "sendTransaction",
+            "params": [
+                transaction.to_base64_string(),
+                { "encoding": "base64", "skipPreflight": true, "maxRetries": 0 }
+            ],
+            "id": 1,
+        });
+
+        self.swqos_client
+            .swqos_json_post(
+                SWQoSRequest {
+                    name: self.get_name().to_string(),
+                    url: format!("{}/fast", self.swqos_endpoint),
+                    auth_header: None,
+                    transactions: vec![transaction],
+                },
+                body,
+            )
+            .await
+    }
+
+    // Sender has no batch method, the transactions are sent concurrently
+    async fn send_transactions(&self, transactions: Vec<VersionedTransaction>) -> anyhow::Result<()> {
+        let result = futures::future::join_all(transactions.into_iter().map(|tx| self.send_transaction(tx))).await;
+        result.into_iter().collect()
+    }
+
+    fn get_tip_account(&self) -> Option<Pubkey> {
+        Some(*HELIUS_SENDER_TIP_ACCOUNTS.choose(&mut rand::rng())?)
+    }
+
+    fn get_name(&self) -> &str {
+        "helius"
+    }
+
+    fn get_endpoint(&self) -> &str {
+        &self.swqos_endpoint
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.swqos_client
+            .swqos_ping(&SWQoSPing::Get(format!("{}/ping", self.swqos_endpoint)), None)
+            .await
+    }
+
+    fn get_latency(&self) -> Option<EndpointLatency> {
+        Some(self.swqos_client.get_latency())
+    }
+}
+
+impl HeliusSenderClient {
+    pub fn new(rpc_client: Arc<RpcClient>, endpoint: String) -> Self {
+        Self {
+            rpc_client,
+            swqos_endpoint: endpoint,
+            swqos_client: Arc::new(SWQoSHttpClient::new()),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
-    swqos_rpc::{SWQoSClientTrait, SWQoSRequest},
-    SWQoSTrait,
+    swqos_rpc::{EndpointLatency, SWQoSClientTrait, SWQoSHttpClient, SWQoSPing, SWQoSRequest},
+    BundleStatus, SWQoSTrait, MAX_BUNDLE_TRANSACTIONS,
 };
 use crate::swqos::swqos_rpc::FormatBase64VersionedTransaction;
 use rand::seq::IndexedRandom;
@@ -37,7 +37,7 @@ pub const JITO_RELAYER_LONDON: &str = "http://london.mainnet.relayer.jito.wtf:81
 pub struct JitoClient {
     pub rpc_client: Arc<RpcClient>,
     pub swqos_endpoint: String,
-    pub swqos_client: Arc<reqwest::Client>,
+    pub swqos_client: Arc<SWQoSHttpClient>,
 }
 
 #[async_trait::async_trait]
@@ -54,47 +54,125 @@ impl SWQoSTrait for JitoClient {
     }
 
     async fn send_transactions(&self, transactions: Vec<VersionedTransaction>) -> anyhow::Result<()> {
+        self.send_bundle(transactions).await?;
+        Ok(())
+    }
+
+    fn get_tip_account(&self) -> Option<Pubkey> {
+        Some(*JITO_TIP_ACCOUNTS.choose(&mut rand::rng())?)
+    }
+
+    fn get_name(&self) -> &str {
+        "jito"
+    }
+
+    fn get_endpoint(&self) -> &str {
+        &self.swqos_endpoint
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.swqos_client
+            .swqos_ping(
+                &SWQoSPing::JsonRpc(format!("{}/api/v1/bundles", self.swqos_endpoint), "getTipAccounts".to_string()),
+                None,
+            )
+            .await
+    }
+
+    fn get_latency(&self) -> Option<EndpointLatency> {
+        Some(self.swqos_client.get_latency())
+    }
+
+    fn supports_bundles(&self) -> bool {
+        true
+    }
+
+    async fn send_bundle(&self, transactions: Vec<VersionedTransaction>) -> anyhow::Result<String> {
+        if transactions.is_empty() || transactions.len() > MAX_BUNDLE_TRANSACTIONS {
+            return Err(anyhow::anyhow!(
+                "Bundle must contain 1 to {} transactions, got {}",
+                MAX_BUNDLE_TRANSACTIONS,
+                transactions.len()
+            ));
+        }
+
         let txs_base64 = transactions.iter().map(|tx| tx.to_base64_string()).collect::<Vec<String>>();
+        let response = self
+            .bundle_rpc("sendBundle", serde_json::json!([txs_base64, { "encoding": "base64" }]), transactions)
+            .await?;
+        let bundle_id = response["result"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Invalid sendBundle response: {}", response))?;
+
+        Ok(bundle_id.to_string())
+    }
+
+    async fn get_bundle_statuses(&self, bundle_ids: &[String]) -> anyhow::Result<Vec<BundleStatus>> {
+        let inflight = self.bundle_rpc("getInflightBundleStatuses", serde_json::json!([bundle_ids]), vec![]).await?;
+        let inflight = inflight["result"]["value"].as_array().cloned().unwrap_or_default();
+
+        let mut statuses = vec![];
+        for bundle_id in bundle_ids {
+            let entry = inflight.iter().find(|entry| entry["bundle_id"].as_str() == Some(bundle_id));
+            let status = match entry.and_then(|entry| entry["status"].as_str()) {
+                Some("Pending") => BundleStatus::Pending,
+                Some("Failed") => BundleStatus::Failed("Bundle failed in the block engine".to_string()),
+                Some("Landed") => BundleStatus::Landed {
+                    slot: entry.and_then(|entry| entry["landed_slot"].as_u64()).unwrap_or_default(),
+                },
+                // `Invalid` or missing: either not seen yet or older than the last 5 minutes of inflight statuses
+                _ => self.get_landed_bundle_status(bundle_id).await?,
+            };
+            statuses.push(status);
+        }
+
+        Ok(statuses)
+    }
+}
+
+impl JitoClient {
+    async fn get_landed_bundle_status(&self, bundle_id: &str) -> anyhow::Result<BundleStatus> {
+        let response = self.bundle_rpc("getBundleStatuses", serde_json::json!([[bundle_id]]), vec![]).await?;
+        // Unknown right after sendBundle until the block engine picks it up, the caller's timeout decides when it is dropped
+        let entry = &response["result"]["value"][0];
+        if entry.is_null() {
+            return Ok(BundleStatus::Pending);
+        }
+
+        match entry.get("err").and_then(|err| err.get("Ok")) {
+            Some(_) => Ok(BundleStatus::Landed {
+                slot: entry["slot"].as_u64().unwrap_or_default(),
+            }),
+            None => Ok(BundleStatus::Failed(entry["err"].to_string())),
+        }
+    }
+
+    async fn bundle_rpc(&self, method: &str, params: serde_json::Value, transactions: Vec<VersionedTransaction>) -> anyhow::Result<serde_json::Value> {
         let body = serde_json::json!({
             "jsonrpc": "2.0",
-            "method": "sendBundle",
-            "params": [
-                txs_base64,
-                { "encoding": "base64" }
-            ],
+            "method": method,
+            "params": params,
             "id": 1,
         });
 
         self.swqos_client
-            .swqos_json_post(
+            .swqos_json_post_with_response(
                 SWQoSRequest {
                     name: self.get_name().to_string(),
                     url: format!("{}/api/v1/bundles", self.swqos_endpoint),
                     auth_header: None,
-                    transactions: transactions,
+                    transactions,
                 },
                 body,
             )
             .await
     }
 
-    fn get_tip_account(&self) -> Option<Pubkey> {
-        Some(*JITO_TIP_ACCOUNTS.choose(&mut rand::rng())?)
-    }
-
-    fn get_name(&self) -> &str {
-        "jito"
-    }
-}
-
-impl JitoClient {
     pub fn new(rpc_client: Arc<RpcClient>, endpoint: String) -> Self {
-        let swqos_client = reqwest::Client::new_swqos_client();
-
         Self {
             rpc_client,
             swqos_endpoint: endpoint,
-            swqos_client: Arc::new(swqos_client),
+            swqos_client: Arc::new(SWQoSHttpClient::new()),
         }
     }
 }
@@ -1,19 +1,36 @@
+pub mod astralane;
 pub mod blox;
 pub mod default;
+pub mod flashblock;
+pub mod helius;
 pub mod jito;
 pub mod nextblock;
+pub mod node1;
+pub mod regional;
+pub mod scoreboard;
+pub mod stellium;
 pub mod swqos_rpc;
 pub mod temporal;
+pub mod tpu;
 pub mod zeroslot;
 
-use blox::BloxClient;
+use astralane::ASTRALANE_TIP_ACCOUNTS;
+use blox::{BloxClient, BloxSubmitProtection};
 use default::DefaultSWQoSClient;
+use flashblock::FlashblockClient;
+use helius::HeliusSenderClient;
 use jito::JitoClient;
 use nextblock::NextBlockClient;
+use node1::NODE1_TIP_ACCOUNTS;
+use regional::RegionalSWQoS;
 use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_keypair::read_keypair_file;
 use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
-use std::{any::Any, sync::Arc};
+use std::{any::Any, sync::Arc, time::Duration};
+use stellium::STELLIUM_TIP_ACCOUNTS;
+use swqos_rpc::{EndpointLatency, SWQoSPing};
 use temporal::TEMPORAL_TIP_ACCOUNTS;
+use tpu::TpuClient;
 use zeroslot::ZEROSLOT_TIP_ACCOUNTS;
 
 // (endpoint, auth_token)
@@ -25,6 +42,33 @@ pub enum SWQoSType {
     Blox(String, String),
     Temporal(String, String),
     ZeroSlot(String, String),
+    // Endpoint only, Helius Sender takes no auth
+    HeliusSender(String),
+    Astralane(String, String),
+    BloxLeaderAware(String, String, BloxSubmitProtection),
+    Node1(String, String),
+    Flashblock(String, String),
+    Stellium(String, String),
+    // (number of upcoming leaders, staked identity keypair file)
+    Tpu(usize, Option<String>),
+    // (provider, region matched by `RoutingPolicy::ByRegion`)
+    Regional(Box<SWQoSType>, String),
+}
+
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleStatus {
+    Pending,
+    Landed { slot: u64 },
+    Failed(String),
+    Dropped,
+}
+
+impl BundleStatus {
+    pub fn is_final(&self) -> bool {
+        !matches!(self, BundleStatus::Pending)
+    }
 }
 
 #[async_trait::async_trait]
@@ -33,11 +77,53 @@ pub trait SWQoSTrait: Send + Sync + Any {
     async fn send_transactions(&self, transactions: Vec<VersionedTransaction>) -> anyhow::Result<()>;
     fn get_tip_account(&self) -> Option<Pubkey>;
     fn get_name(&self) -> &str;
+    // Base URL the client sends to, empty for providers without one
+    #[deprecated(note = "Regions are no longer matched against endpoint URLs, tag providers with `SWQoSType::Regional` and use `get_region`")]
+    fn get_endpoint(&self) -> &str {
+        ""
+    }
+    // Region the provider was configured in, matched by `RoutingPolicy::ByRegion`
+    fn get_region(&self) -> Option<&str> {
+        None
+    }
+    fn supports_bundles(&self) -> bool {
+        false
+    }
+    // Sends the transactions as one atomic bundle and returns the bundle id
+    async fn send_bundle(&self, _transactions: Vec<VersionedTransaction>) -> anyhow::Result<String> {
+        Err(anyhow::anyhow!("Bundles not supported by SWQoS: {}", self.get_name()))
+    }
+    async fn get_bundle_statuses(&self, _bundle_ids: &[String]) -> anyhow::Result<Vec<BundleStatus>> {
+        Err(anyhow::anyhow!("Bundles not supported by SWQoS: {}", self.get_name()))
+    }
+    // Hits the provider's health route so that its pooled connection stays warm
+    async fn ping(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+    fn get_latency(&self) -> Option<EndpointLatency> {
+        None
+    }
+}
+
+// Pings every provider right away to warm up their connections, then every `interval` to keep them alive
+pub fn spawn_keep_alive(swqos: Arc<Vec<Arc<dyn SWQoSTrait>>>, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let result = futures::future::join_all(swqos.iter().map(|swqos| swqos.ping())).await;
+            for (swqos, res) in swqos.iter().zip(result) {
+                if let Err(err) = res {
+                    eprintln!("SWQoS ping failed: {} {}", swqos.get_name(), err);
+                }
+            }
+        }
+    })
 }
 
 impl SWQoSType {
-    pub fn instantiate(&self, rpc_client: Arc<RpcClient>) -> Arc<dyn SWQoSTrait> {
-        match self {
+    pub fn instantiate(&self, rpc_client: Arc<RpcClient>) -> anyhow::Result<Arc<dyn SWQoSTrait>> {
+        Ok(match self {
             SWQoSType::Default(endpoint, header) => Arc::new(DefaultSWQoSClient::new("default", rpc_client, endpoint.to_string(), header.clone(), vec![])),
             SWQoSType::Jito(endpoint) => Arc::new(JitoClient::new(rpc_client, endpoint.to_string())),
             SWQoSType::NextBlock(endpoint, auth_token) => Arc::new(NextBlockClient::new(rpc_client, endpoint.to_string(), auth_token.to_string())),
@@ -50,13 +136,59 @@ impl SWQoSType {
                 ZEROSLOT_TIP_ACCOUNTS.into(),
             )),
 
-            SWQoSType::Temporal(endpoint, auth_token) => Arc::new(DefaultSWQoSClient::new(
-                "temporal",
+            SWQoSType::Temporal(endpoint, auth_token) => Arc::new(
+                DefaultSWQoSClient::new(
+                    "temporal",
+                    rpc_client,
+                    format!("{}?c={}", endpoint, auth_token),
+                    None,
+                    TEMPORAL_TIP_ACCOUNTS.into(),
+                )
+                .with_ping(SWQoSPing::Get(format!("{}/ping", endpoint))),
+            ),
+            SWQoSType::HeliusSender(endpoint) => Arc::new(HeliusSenderClient::new(rpc_client, endpoint.to_string())),
+            SWQoSType::Astralane(endpoint, auth_token) => Arc::new(
+                DefaultSWQoSClient::new(
+                    "astralane",
+                    rpc_client,
+                    format!("{}/iris?api-key={}", endpoint, auth_token),
+                    None,
+                    ASTRALANE_TIP_ACCOUNTS.into(),
+                )
+                .with_ping(SWQoSPing::Get(format!("{}/gethealth", endpoint))),
+            ),
+            SWQoSType::BloxLeaderAware(endpoint, auth_token, submit_protection) => Arc::new(BloxClient::new_leader_aware(
+                rpc_client,
+                endpoint.to_string(),
+                auth_token.to_string(),
+                *submit_protection,
+            )),
+            SWQoSType::Node1(endpoint, auth_token) => Arc::new(
+                DefaultSWQoSClient::new(
+                    "node1",
+                    rpc_client,
+                    endpoint.to_string(),
+                    Some(("api-key".to_string(), auth_token.to_string())),
+                    NODE1_TIP_ACCOUNTS.into(),
+                )
+                .with_ping(SWQoSPing::Get(format!("{}/ping", endpoint))),
+            ),
+            SWQoSType::Flashblock(endpoint, auth_token) => Arc::new(FlashblockClient::new(rpc_client, endpoint.to_string(), auth_token.to_string())),
+            SWQoSType::Stellium(endpoint, auth_token) => Arc::new(DefaultSWQoSClient::new(
+                "stellium",
                 rpc_client,
-                format!("{}?c={}", endpoint, auth_token),
+                format!("{}/{}", endpoint, auth_token),
                 None,
-                TEMPORAL_TIP_ACCOUNTS.into(),
+                STELLIUM_TIP_ACCOUNTS.into(),
             )),
-        }
+            SWQoSType::Tpu(fanout, identity) => {
+                let identity = identity
+                    .as_ref()
+                    .map(|path| read_keypair_file(path).map_err(|err| anyhow::anyhow!("Failed to read TPU identity {}: {}", path, err)))
+                    .transpose()?;
+                Arc::new(TpuClient::new(rpc_client, *fanout, identity.as_ref()))
+            }
+            SWQoSType::Regional(swqos, region) => Arc::new(RegionalSWQoS::new(swqos.instantiate(rpc_client)?, region)),
+        })
     }
 }
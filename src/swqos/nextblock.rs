@@ -1,4 +1,7 @@
-use super::{swqos_rpc::SWQoSRequest, SWQoSTrait};
+use super::{
+    swqos_rpc::{EndpointLatency, SWQoSHttpClient, SWQoSPing, SWQoSRequest},
+    SWQoSTrait,
+};
 use crate::swqos::swqos_rpc::SWQoSClientTrait;
 use base64::{engine::general_purpose, Engine};
 use rand::seq::IndexedRandom;
@@ -25,7 +28,7 @@ pub struct NextBlockClient {
     pub rpc_client: Arc<RpcClient>,
     pub swqos_endpoint: String,
     pub swqos_header: Option<(String, String)>,
-    pub swqos_client: Arc<reqwest::Client>,
+    pub swqos_client: Arc<SWQoSHttpClient>,
 }
 
 #[async_trait::async_trait]
@@ -91,17 +94,29 @@ impl SWQoSTrait for NextBlockClient {
     fn get_name(&self) -> &str {
         "nextblock"
     }
+
+    fn get_endpoint(&self) -> &str {
+        &self.swqos_endpoint
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.swqos_client
+            .swqos_ping(&SWQoSPing::Get(self.swqos_endpoint.clone()), self.swqos_header.clone())
+            .await
+    }
+
+    fn get_latency(&self) -> Option<EndpointLatency> {
+        Some(self.swqos_client.get_latency())
+    }
 }
 
 impl NextBlockClient {
     pub fn new(rpc_client: Arc<RpcClient>, endpoint: String, auth_token: String) -> Self {
-        let swqos_client = reqwest::Client::new_swqos_client();
-
         Self {
             rpc_client,
             swqos_endpoint: endpoint,
             swqos_header: Some(("Authorization".to_string(), auth_token)),
-            swqos_client: Arc::new(swqos_client),
+            swqos_client: Arc::new(SWQoSHttpClient::new()),
         }
     }
 }
@@ -0,0 +1,15 @@
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const NODE1_TIP_ACCOUNTS: &[Pubkey] = &[
+    pubkey!("node1PqAa3BWWzUnTHVbw8NJHC874zn9ngAkXjgWEej"),
+    pubkey!("node1UzzTxAAeBTpfZkQPJXBAqixsbdth11ba1NXLBG"),
+    pubkey!("node1Qm1bV4fwYnCurP8otJ9s5yrkPq7SPZ5uhj3Tsv"),
+    pubkey!("node1PUber6SFmSQgvf2ECmXsHP5o3boRSGhvJyPMX1"),
+    pubkey!("node1AyMbeqiVN6eoQzEAwCA6Pk826hrdqdAHR7cdJ3"),
+    pubkey!("node1YtWCoTwwVYTFLfS19zquRQzYX332hs1HEuRBjC"),
+];
+
+pub const NODE1_ENDPOINT_FRA: &str = "http://fra.node1.me";
+pub const NODE1_ENDPOINT_AMS: &str = "http://ams.node1.me";
+pub const NODE1_ENDPOINT_NY: &str = "http://ny.node1.me";
+pub const NODE1_ENDPOINT_TOKYO: &str = "http://tk.node1.me";
@@ -0,0 +1,66 @@
+use super::{swqos_rpc::EndpointLatency, BundleStatus, SWQoSTrait};
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+use std::sync::Arc;
+
+// A provider tagged with the region it was configured in, everything else is forwarded to it
+pub struct RegionalSWQoS {
+    pub inner: Arc<dyn SWQoSTrait>,
+    pub region: String,
+}
+
+#[async_trait::async_trait]
+impl SWQoSTrait for RegionalSWQoS {
+    async fn send_transaction(&self, transaction: VersionedTransaction) -> anyhow::Result<()> {
+        self.inner.send_transaction(transaction).await
+    }
+
+    async fn send_transactions(&self, transactions: Vec<VersionedTransaction>) -> anyhow::Result<()> {
+        self.inner.send_transactions(transactions).await
+    }
+
+    fn get_tip_account(&self) -> Option<Pubkey> {
+        self.inner.get_tip_account()
+    }
+
+    fn get_name(&self) -> &str {
+        self.inner.get_name()
+    }
+
+    #[allow(deprecated)]
+    fn get_endpoint(&self) -> &str {
+        self.inner.get_endpoint()
+    }
+
+    fn get_region(&self) -> Option<&str> {
+        Some(&self.region)
+    }
+
+    fn supports_bundles(&self) -> bool {
+        self.inner.supports_bundles()
+    }
+
+    async fn send_bundle(&self, transactions: Vec<VersionedTransaction>) -> anyhow::Result<String> {
+        self.inner.send_bundle(transactions).await
+    }
+
+    async fn get_bundle_statuses(&self, bundle_ids: &[String]) -> anyhow::Result<Vec<BundleStatus>> {
+        self.inner.get_bundle_statuses(bundle_ids).await
+    }
+
+    async fn ping(&self) -> anyhow::Result<()> {
+        self.inner.ping().await
+    }
+
+    fn get_latency(&self) -> Option<EndpointLatency> {
+        self.inner.get_latency()
+    }
+}
+
+impl RegionalSWQoS {
+    pub fn new(inner: Arc<dyn SWQoSTrait>, region: &str) -> Self {
+        Self {
+            inner,
+            region: region.to_string(),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use super::SWQoSTrait;
+use solana_sdk::signature::Signature;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+pub const SCOREBOARD_WINDOW: usize = 100;
+pub const UNHEALTHY_ERROR_RATE: f64 = 0.5;
+// A tracked signature not seen on chain after this long is counted as not landed
+pub const LAND_ATTRIBUTION_TIMEOUT: Duration = Duration::from_secs(90);
+// Tracked signatures are forgotten after this long when `TradingEndpoint::attribute_landings` is never called
+pub const PENDING_SIGNATURE_TTL: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RoutingPolicy {
+    /// Every provider
+    #[default]
+    All,
+    /// The `k` best scored tipped providers, plus every provider that takes no tip
+    TopK(usize),
+    /// The first healthy provider of the list of names, in order, plus every provider that takes no tip
+    PrimaryFallback(Vec<String>),
+    /// Providers configured in one of the regions through `SWQoSType::Regional`, e.g. `ny` or `frankfurt`
+    ByRegion(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderScore {
+    pub name: String,
+    pub sends: usize,
+    pub avg_latency: Option<Duration>,
+    pub error_rate: f64,
+    // Share of the resolved signatures that landed, `None` until one is resolved
+    pub land_rate: Option<f64>,
+    pub score: f64,
+}
+
+#[derive(Debug, Default)]
+struct ProviderStats {
+    // (latency, succeeded) of the last sends
+    sends: VecDeque<(Duration, bool)>,
+    // Whether the last resolved signatures landed
+    lands: VecDeque<bool>,
+}
+
+impl ProviderStats {
+    fn push<T>(samples: &mut VecDeque<T>, sample: T) {
+        if samples.len() >= SCOREBOARD_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    fn avg_latency(&self) -> Option<Duration> {
+        let latencies = self.sends.iter().filter(|(_, ok)| *ok).map(|(latency, _)| *latency).collect::<Vec<_>>();
+        (!latencies.is_empty()).then(|| latencies.iter().sum::<Duration>() / latencies.len() as u32)
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.sends.is_empty() {
+            return 0.0;
+        }
+        self.sends.iter().filter(|(_, ok)| !ok).count() as f64 / self.sends.len() as f64
+    }
+
+    // Land rate with a uniform prior so that new providers start at 0.5, scaled by the success rate and slowed by latency
+    fn score(&self) -> f64 {
+        let landed = self.lands.iter().filter(|landed| **landed).count() as f64;
+        let land_rate = (landed + 1.0) / (self.lands.len() as f64 + 2.0);
+        let latency = self.avg_latency().unwrap_or_default().as_secs_f64();
+        land_rate * (1.0 - self.error_rate()) / (1.0 + latency)
+    }
+}
+
+// Rolling per-provider latency, error rate and land rate, indexed like `TradingEndpoint::swqos`.
+// Lands are attributed through the signatures each provider was sent: tipped providers each get their own transaction.
+pub struct ProviderScoreboard {
+    stats: Mutex<Vec<ProviderStats>>,
+    pending: Mutex<HashMap<Signature, (Vec<usize>, Instant)>>,
+}
+
+impl ProviderScoreboard {
+    pub fn new(providers: usize) -> Self {
+        Self {
+            stats: Mutex::new((0..providers).map(|_| ProviderStats::default()).collect()),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record_send(&self, provider: usize, latency: Duration, ok: bool) {
+        if let Some(stats) = self.stats.lock().unwrap().get_mut(provider) {
+            ProviderStats::push(&mut stats.sends, (latency, ok));
+        }
+    }
+
+    // Remembers that `provider` was sent `signature`, until `record_landed` resolves it
+    pub fn track(&self, signature: Signature, provider: usize) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, (_, sent_at)| sent_at.elapsed() < PENDING_SIGNATURE_TTL);
+        pending.entry(signature).or_insert_with(|| (vec![], Instant::now())).0.push(provider);
+    }
+
+    pub fn get_pending(&self) -> Vec<(Signature, Instant)> {
+        self.pending
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(signature, (_, sent_at))| (*signature, *sent_at))
+            .collect()
+    }
+
+    pub fn record_landed(&self, signature: &Signature, landed: bool) {
+        let Some((providers, _)) = self.pending.lock().unwrap().remove(signature) else {
+            return;
+        };
+        let mut stats = self.stats.lock().unwrap();
+        for provider in providers {
+            if let Some(stats) = stats.get_mut(provider) {
+                ProviderStats::push(&mut stats.lands, landed);
+            }
+        }
+    }
+
+    pub fn is_healthy(&self, provider: usize) -> bool {
+        self.stats
+            .lock()
+            .unwrap()
+            .get(provider)
+            .map(|stats| stats.error_rate() < UNHEALTHY_ERROR_RATE)
+            .unwrap_or(false)
+    }
+
+    pub fn get_scores(&self, swqos: &[Arc<dyn SWQoSTrait>]) -> Vec<ProviderScore> {
+        let stats = self.stats.lock().unwrap();
+        swqos
+            .iter()
+            .zip(stats.iter())
+            .map(|(swqos, stats)| ProviderScore {
+                name: swqos.get_name().to_string(),
+                sends: stats.sends.len(),
+                avg_latency: stats.avg_latency(),
+                error_rate: stats.error_rate(),
+                land_rate: (!stats.lands.is_empty()).then(|| stats.lands.iter().filter(|landed| **landed).count() as f64 / stats.lands.len() as f64),
+                score: stats.score(),
+            })
+            .collect()
+    }
+
+    // Indices of the providers `policy` sends to, in `swqos` order
+    pub fn select(&self, policy: &RoutingPolicy, swqos: &[Arc<dyn SWQoSTrait>]) -> Vec<usize> {
+        let untipped = |index: &usize| swqos[*index].get_tip_account().is_none();
+        let mut selected = match policy {
+            RoutingPolicy::All => (0..swqos.len()).collect(),
+            RoutingPolicy::TopK(k) => {
+                let scores = self.get_scores(swqos);
+                let mut tipped = (0..swqos.len()).filter(|index| !untipped(index)).collect::<Vec<_>>();
+                tipped.sort_by(|a, b| scores[*b].score.total_cmp(&scores[*a].score));
+                tipped.truncate(*k);
+                tipped.into_iter().chain((0..swqos.len()).filter(untipped)).collect::<Vec<_>>()
+            }
+            RoutingPolicy::PrimaryFallback(names) => {
+                let listed = names
+                    .iter()
+                    .filter_map(|name| (0..swqos.len()).find(|index| swqos[*index].get_name() == name && !untipped(index)))
+                    .collect::<Vec<_>>();
+                // When every listed provider is unhealthy the primary is still tried
+                let chosen = listed.iter().find(|index| self.is_healthy(**index)).or(listed.first()).copied();
+                chosen.into_iter().chain((0..swqos.len()).filter(untipped)).collect()
+            }
+            RoutingPolicy::ByRegion(regions) => (0..swqos.len())
+                .filter(|index| {
+                    let provider_region = swqos[*index].get_region();
+                    regions
+                        .iter()
+                        .any(|region| provider_region.is_some_and(|provider_region| provider_region.eq_ignore_ascii_case(region)))
+                })
+                .collect(),
+        };
+        selected.sort();
+        selected.dedup();
+        selected
+    }
+}
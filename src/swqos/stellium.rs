@@ -0,0 +1,15 @@
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const STELLIUM_TIP_ACCOUNTS: &[Pubkey] = &[
+    pubkey!("ste11JV3MLMM7x7EJUM2sXcJC1H7F4jBLnP9a9PG8PH"),
+    pubkey!("ste11MWPjXCRfQryCshzi86SGhuXjF4Lv6xMXD2AoSt"),
+    pubkey!("ste11p5x8tJ53H1NbNQsRBg1YNRd4GcVpxtDw8PBpmb"),
+    pubkey!("ste11p7e2KLYou5bwtt35H7BM6uMdo4pvioGjJXKFcN"),
+    pubkey!("ste11TMV68LMi1BguM4RQujtbNCZvf1sjsASpqgAvSX"),
+];
+
+pub const STELLIUM_ENDPOINT_FRA: &str = "http://fra1.flashrpc.com";
+pub const STELLIUM_ENDPOINT_AMS: &str = "http://ams1.flashrpc.com";
+pub const STELLIUM_ENDPOINT_NY: &str = "http://ewr1.flashrpc.com";
+pub const STELLIUM_ENDPOINT_LONDON: &str = "http://lhr1.flashrpc.com";
+pub const STELLIUM_ENDPOINT_TOKYO: &str = "http://tyo1.flashrpc.com";
@@ -1,9 +1,43 @@
 use base64::{engine::general_purpose, Engine};
 use solana_sdk::transaction::VersionedTransaction;
-use std::{str::FromStr, time::Duration};
+use std::{
+    collections::VecDeque,
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 use tokio::time::timeout;
 
 pub const SWQOS_RPC_TIMEOUT: std::time::Duration = Duration::from_secs(10);
+pub const SWQOS_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// Idle connections outlive the keep-alive ping interval so that pings keep them in the pool
+pub const SWQOS_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+pub const SWQOS_POOL_MAX_IDLE_PER_HOST: usize = 8;
+pub const SWQOS_TCP_KEEP_ALIVE: Duration = Duration::from_secs(15);
+pub const SWQOS_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+pub const SWQOS_LATENCY_WINDOW: usize = 100;
+
+// Non-success HTTP status from a provider, kept apart so that submission retries can tell transient failures
+#[derive(Debug, Clone)]
+pub struct SWQoSHttpError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl std::fmt::Display for SWQoSHttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for SWQoSHttpError {}
+
+impl SWQoSHttpError {
+    // Server errors and rate limiting
+    pub fn is_retryable(&self) -> bool {
+        self.status >= 500 || self.status == 429
+    }
+}
 
 pub struct SWQoSRequest {
     pub name: String,
@@ -23,18 +57,46 @@ impl FormatBase64VersionedTransaction for VersionedTransaction {
     }
 }
 
+// Request that keeps a provider connection warm, any HTTP response counts as alive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SWQoSPing {
+    Get(String),
+    // (url, JSON-RPC method), e.g. `getHealth`
+    JsonRpc(String, String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointLatency {
+    pub requests: u64,
+    pub errors: u64,
+    pub last: Option<Duration>,
+    // Over the last `SWQOS_LATENCY_WINDOW` successful requests
+    pub average: Option<Duration>,
+    pub max: Option<Duration>,
+    pub last_ping: Option<Duration>,
+    pub ping_errors: u64,
+}
+
 #[async_trait::async_trait]
-pub trait SWQoSClientTrait {
+pub trait SWQoSClientTrait: Sync {
+    // HTTP/2 when the provider negotiates it, no Nagle delay, and pooled connections kept alive between trades
     fn new_swqos_client() -> reqwest::Client {
-        reqwest::Client::builder().timeout(SWQOS_RPC_TIMEOUT).build().unwrap()
+        reqwest::Client::builder()
+            .timeout(SWQOS_RPC_TIMEOUT)
+            .connect_timeout(SWQOS_CONNECT_TIMEOUT)
+            .tcp_nodelay(true)
+            .tcp_keepalive(SWQOS_TCP_KEEP_ALIVE)
+            .pool_idle_timeout(SWQOS_POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(SWQOS_POOL_MAX_IDLE_PER_HOST)
+            .http2_adaptive_window(true)
+            .http2_keep_alive_interval(SWQOS_KEEP_ALIVE_INTERVAL)
+            .http2_keep_alive_while_idle(true)
+            .build()
+            .unwrap()
     }
-    async fn swqos_send_transaction(&self, request: SWQoSRequest) -> anyhow::Result<()>;
-    async fn swqos_send_transactions(&self, request: SWQoSRequest) -> anyhow::Result<()>;
-    async fn swqos_json_post(&self, request: SWQoSRequest, body: serde_json::Value) -> anyhow::Result<()>;
-}
 
-#[async_trait::async_trait]
-impl SWQoSClientTrait for reqwest::Client {
+    async fn swqos_json_post_with_response(&self, request: SWQoSRequest, body: serde_json::Value) -> anyhow::Result<serde_json::Value>;
+
     async fn swqos_send_transaction(&self, request: SWQoSRequest) -> anyhow::Result<()> {
         let body = serde_json::json!({
             "jsonrpc": "2.0",
@@ -65,6 +127,24 @@ impl SWQoSClientTrait for reqwest::Client {
     }
 
     async fn swqos_json_post(&self, request: SWQoSRequest, body: serde_json::Value) -> anyhow::Result<()> {
+        let name = request.name.clone();
+        let txs_hash = request
+            .transactions
+            .iter()
+            .map(|tx| tx.signatures[0].to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let response_json = self.swqos_json_post_with_response(request, body).await?;
+
+        println!("swqos_json_post success: {} {} {:#?}", name, txs_hash, response_json);
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl SWQoSClientTrait for reqwest::Client {
+    async fn swqos_json_post_with_response(&self, request: SWQoSRequest, body: serde_json::Value) -> anyhow::Result<serde_json::Value> {
         let txs_hash = request
             .transactions
             .iter()
@@ -82,18 +162,106 @@ impl SWQoSClientTrait for reqwest::Client {
         if !http_status.is_success() {
             let error = format!("swqos_json_post error: {} {} {} {}", request.name, txs_hash, http_status, response_body);
             eprintln!("{}", error);
-            return Err(anyhow::anyhow!(error));
+            return Err(SWQoSHttpError {
+                status: http_status.as_u16(),
+                message: error,
+            }
+            .into());
         }
 
         let response_json = serde_json::Value::from_str(&response_body)?;
         if let Some(error) = response_json.get("error") {
-            let error = format!("swqos_json_post error: {} {} {} {}", request.name, txs_hash, http_status, error.to_string());
+            let error = format!("swqos_json_post error: {} {} {} {}", request.name, txs_hash, http_status, error);
             eprintln!("{}", error);
             return Err(anyhow::anyhow!(error));
         }
 
-        println!("swqos_json_post success: {} {} {:#?}", request.name, txs_hash, response_json);
+        Ok(response_json)
+    }
+}
+
+// Tuned HTTP client of one provider, recording the latency of its requests and keep-alive pings
+pub struct SWQoSHttpClient {
+    pub client: reqwest::Client,
+    latency: Mutex<(EndpointLatency, VecDeque<Duration>)>,
+}
 
-        Ok(())
+impl Default for SWQoSHttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl SWQoSClientTrait for SWQoSHttpClient {
+    async fn swqos_json_post_with_response(&self, request: SWQoSRequest, body: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let started = Instant::now();
+        let result = self.client.swqos_json_post_with_response(request, body).await;
+        self.record(started.elapsed(), result.is_ok());
+        result
+    }
+}
+
+impl SWQoSHttpClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new_swqos_client(),
+            latency: Mutex::new((EndpointLatency::default(), VecDeque::new())),
+        }
+    }
+
+    pub fn get_latency(&self) -> EndpointLatency {
+        self.latency.lock().unwrap().0.clone()
+    }
+
+    fn record(&self, elapsed: Duration, ok: bool) {
+        let mut guard = self.latency.lock().unwrap();
+        let (latency, samples) = &mut *guard;
+        latency.requests += 1;
+        if !ok {
+            latency.errors += 1;
+            return;
+        }
+
+        if samples.len() >= SWQOS_LATENCY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(elapsed);
+        latency.last = Some(elapsed);
+        latency.average = Some(samples.iter().sum::<Duration>() / samples.len() as u32);
+        latency.max = samples.iter().max().copied();
+    }
+
+    pub async fn swqos_ping(&self, ping: &SWQoSPing, auth_header: Option<(String, String)>) -> anyhow::Result<()> {
+        let request = match ping {
+            SWQoSPing::Get(url) => self.client.get(url),
+            SWQoSPing::JsonRpc(url, method) => self.client.post(url).json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": [],
+                "id": 1,
+            })),
+        };
+        let request = match auth_header {
+            Some((key, value)) => request.header(key, value),
+            None => request,
+        };
+
+        let started = Instant::now();
+        let result = match timeout(SWQOS_RPC_TIMEOUT, request.send()).await {
+            Ok(response) => response.map_err(anyhow::Error::from),
+            Err(err) => Err(err.into()),
+        };
+        let mut guard = self.latency.lock().unwrap();
+        match result {
+            Ok(_) => {
+                guard.0.last_ping = Some(started.elapsed());
+                Ok(())
+            }
+            Err(err) => {
+                guard.0.ping_errors += 1;
+                Err(err)
+            }
+        }
     }
 }
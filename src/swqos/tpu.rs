@@ -0,0 +1,199 @@
+use super::SWQoSTrait;
+use solana_client::{connection_cache::ConnectionCache, nonblocking::rpc_client::RpcClient};
+use solana_connection_cache::nonblocking::client_connection::ClientConnection;
+use solana_keypair::Keypair;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signer::Signer, transaction::VersionedTransaction};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+pub const TPU_DEFAULT_FANOUT: usize = 4;
+pub const TPU_CONNECTION_POOL_SIZE: usize = 2;
+pub const TPU_LEADER_SLOTS_PER_FETCH: u64 = 100;
+pub const TPU_CLUSTER_NODES_TTL: Duration = Duration::from_secs(60);
+// The estimated slot drifts from the chain, it is read again after this long
+pub const TPU_SLOT_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+pub const TPU_SLOT_DURATION: Duration = Duration::from_millis(400);
+pub const NUM_CONSECUTIVE_LEADER_SLOTS: u64 = 4;
+
+#[derive(Default)]
+struct LeaderSchedule {
+    // (slot, when it was read)
+    slot: Option<(u64, Instant)>,
+    // Leaders of the slots from `start_slot`
+    start_slot: u64,
+    leaders: Vec<Pubkey>,
+    tpu_addresses: HashMap<Pubkey, SocketAddr>,
+    nodes_fetched_at: Option<Instant>,
+}
+
+// Sends transactions over QUIC straight to the TPU of the next `fanout` leaders, resolved from `getSlotLeaders`
+// and `getClusterNodes`. With an identity keypair the connections are made as that validator, so that its stake
+// gives them priority; otherwise they are unstaked.
+pub struct TpuClient {
+    pub rpc_client: Arc<RpcClient>,
+    pub rpc_endpoint: String,
+    pub fanout: usize,
+    pub identity: Option<Pubkey>,
+    connection_cache: ConnectionCache,
+    schedule: RwLock<LeaderSchedule>,
+}
+
+#[async_trait::async_trait]
+impl SWQoSTrait for TpuClient {
+    async fn send_transaction(&self, transaction: VersionedTransaction) -> anyhow::Result<()> {
+        let wire_transaction = bincode::serialize(&transaction)?;
+        let addresses = self.get_leader_addresses().await?;
+        self.send_to(&addresses, &[wire_transaction]).await
+    }
+
+    async fn send_transactions(&self, transactions: Vec<VersionedTransaction>) -> anyhow::Result<()> {
+        let wire_transactions = transactions.iter().map(bincode::serialize).collect::<Result<Vec<_>, _>>()?;
+        let addresses = self.get_leader_addresses().await?;
+        self.send_to(&addresses, &wire_transactions).await
+    }
+
+    fn get_tip_account(&self) -> Option<Pubkey> {
+        None
+    }
+
+    fn get_name(&self) -> &str {
+        "tpu"
+    }
+
+    fn get_endpoint(&self) -> &str {
+        &self.rpc_endpoint
+    }
+}
+
+impl TpuClient {
+    pub fn new(rpc_client: Arc<RpcClient>, fanout: usize, identity: Option<&Keypair>) -> Self {
+        let unstaked = Keypair::new();
+        let cert_keypair = identity.unwrap_or(&unstaked);
+        let connection_cache = ConnectionCache::new_with_client_options(
+            "solana_trading_sdk_tpu",
+            TPU_CONNECTION_POOL_SIZE,
+            None,
+            Some((cert_keypair, IpAddr::V4(Ipv4Addr::UNSPECIFIED))),
+            None,
+        );
+
+        Self {
+            rpc_endpoint: rpc_client.url(),
+            rpc_client,
+            fanout: fanout.max(1),
+            identity: identity.map(|identity| identity.pubkey()),
+            connection_cache,
+            schedule: RwLock::new(LeaderSchedule::default()),
+        }
+    }
+
+    // Sends the serialized transactions to every address, succeeding when at least one accepted them
+    pub async fn send_to(&self, addresses: &[SocketAddr], wire_transactions: &[Vec<u8>]) -> anyhow::Result<()> {
+        let tasks = addresses.iter().map(|address| async move {
+            let connection = self.connection_cache.get_nonblocking_connection(address);
+            match wire_transactions {
+                [wire_transaction] => connection.send_data(wire_transaction).await,
+                _ => connection.send_data_batch(wire_transactions).await,
+            }
+        });
+        let errors = futures::future::join_all(tasks)
+            .await
+            .into_iter()
+            .zip(addresses)
+            .filter_map(|(result, address)| result.err().map(|err| format!("{}: {}", address, err)))
+            .collect::<Vec<_>>();
+
+        if errors.len() == addresses.len() {
+            return Err(anyhow::anyhow!("tpu send error: {:?}", errors));
+        }
+        if !errors.is_empty() {
+            eprintln!("tpu send error: {:?}", errors);
+        }
+
+        Ok(())
+    }
+
+    // TPU QUIC addresses of the distinct leaders of the next `fanout` leader rotations
+    pub async fn get_leader_addresses(&self) -> anyhow::Result<Vec<SocketAddr>> {
+        let slot = self.get_estimated_slot().await?;
+        let leaders = self.get_upcoming_leaders(slot).await?;
+        self.refresh_cluster_nodes().await?;
+
+        // Leaders without a known TPU address, e.g. offline or not yet in the cached nodes, are skipped
+        let schedule = self.schedule.read().unwrap();
+        let addresses = leaders
+            .iter()
+            .filter_map(|leader| schedule.tpu_addresses.get(leader).copied())
+            .collect::<Vec<_>>();
+        if addresses.is_empty() {
+            return Err(anyhow::anyhow!("No TPU address found for the upcoming leaders at slot {}", slot));
+        }
+        Ok(addresses)
+    }
+
+    async fn get_estimated_slot(&self) -> anyhow::Result<u64> {
+        let cached = self.schedule.read().unwrap().slot;
+        if let Some((slot, read_at)) = cached.filter(|(_, read_at)| read_at.elapsed() < TPU_SLOT_REFRESH_INTERVAL) {
+            return Ok(slot + (read_at.elapsed().as_millis() / TPU_SLOT_DURATION.as_millis()) as u64);
+        }
+
+        let slot = self.rpc_client.get_slot_with_commitment(CommitmentConfig::processed()).await?;
+        self.schedule.write().unwrap().slot = Some((slot, Instant::now()));
+        Ok(slot)
+    }
+
+    async fn get_upcoming_leaders(&self, slot: u64) -> anyhow::Result<Vec<Pubkey>> {
+        let last_slot = slot + self.fanout as u64 * NUM_CONSECUTIVE_LEADER_SLOTS;
+        let covered = {
+            let schedule = self.schedule.read().unwrap();
+            slot >= schedule.start_slot && last_slot < schedule.start_slot + schedule.leaders.len() as u64
+        };
+        if !covered {
+            let leaders = self
+                .rpc_client
+                .get_slot_leaders(slot, TPU_LEADER_SLOTS_PER_FETCH.max(last_slot - slot + 1))
+                .await?;
+            let mut schedule = self.schedule.write().unwrap();
+            schedule.start_slot = slot;
+            schedule.leaders = leaders;
+        }
+
+        let schedule = self.schedule.read().unwrap();
+        let mut leaders = vec![];
+        for slot in (slot..=last_slot).step_by(NUM_CONSECUTIVE_LEADER_SLOTS as usize) {
+            if let Some(leader) = schedule.leaders.get((slot - schedule.start_slot) as usize) {
+                if !leaders.contains(leader) {
+                    leaders.push(*leader);
+                }
+            }
+            if leaders.len() >= self.fanout {
+                break;
+            }
+        }
+
+        Ok(leaders)
+    }
+
+    async fn refresh_cluster_nodes(&self) -> anyhow::Result<()> {
+        let fetched_at = self.schedule.read().unwrap().nodes_fetched_at;
+        if fetched_at.is_some_and(|fetched_at| fetched_at.elapsed() < TPU_CLUSTER_NODES_TTL) {
+            return Ok(());
+        }
+
+        let nodes = self.rpc_client.get_cluster_nodes().await?;
+        let tpu_addresses = nodes
+            .into_iter()
+            .filter_map(|node| Some((Pubkey::from_str(&node.pubkey).ok()?, node.tpu_quic?)))
+            .collect();
+        let mut schedule = self.schedule.write().unwrap();
+        schedule.tpu_addresses = tpu_addresses;
+        schedule.nodes_fetched_at = Some(Instant::now());
+
+        Ok(())
+    }
+}
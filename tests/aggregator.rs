@@ -0,0 +1,233 @@
+mod common;
+
+use base64::{engine::general_purpose, Engine};
+use common::{mock_rpc, multiple_accounts_result, program_account, StubSWQoS};
+use solana_address_lookup_table_interface::{
+    program::ID as ADDRESS_LOOKUP_TABLE_PROGRAM,
+    state::{AddressLookupTable, LookupTableMeta},
+};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::VersionedTransaction};
+use solana_trading_sdk::{
+    common::{accounts::PUBKEY_WSOL, trading_endpoint::TradingEndpoint},
+    dex::{aggregator::Aggregator, dex_traits::DexTrait},
+    swqos::SWQoSTrait,
+};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{mpsc, Arc},
+    thread,
+};
+
+struct Request {
+    method: String,
+    target: String,
+    headers: HashMap<String, String>,
+    body: Option<serde_json::Value>,
+}
+
+// Serves one request per connection, answering each path prefix with its JSON response
+fn serve_api(responses: Vec<(&'static str, serde_json::Value)>) -> (String, mpsc::Receiver<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/swap/v1/", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for _ in 0..responses.len() {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut headers = HashMap::new();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                match header.trim_end().split_once(':') {
+                    Some((name, value)) => headers.insert(name.to_ascii_lowercase(), value.trim().to_string()),
+                    None => break,
+                };
+            }
+            let content_length = headers.get("content-length").map_or(0, |length| length.parse().unwrap());
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let mut parts = request_line.split_whitespace();
+            let request = Request {
+                method: parts.next().unwrap().to_string(),
+                target: parts.next().unwrap().to_string(),
+                headers,
+                body: (!body.is_empty()).then(|| serde_json::from_slice(&body).unwrap()),
+            };
+            let (_, response) = responses.iter().find(|(path, _)| request.target.starts_with(path)).unwrap();
+            let response = response.to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            );
+            reader.into_inner().write_all(response.as_bytes()).unwrap();
+            sender.send(request).unwrap();
+        }
+    });
+
+    (url, receiver)
+}
+
+fn instruction_json(program_id: &Pubkey, accounts: &[(Pubkey, bool, bool)], data: &[u8]) -> serde_json::Value {
+    serde_json::json!({
+        "programId": program_id.to_string(),
+        "accounts": accounts
+            .iter()
+            .map(|(pubkey, is_signer, is_writable)| serde_json::json!({ "pubkey": pubkey.to_string(), "isSigner": is_signer, "isWritable": is_writable }))
+            .collect::<Vec<_>>(),
+        "data": general_purpose::STANDARD.encode(data),
+    })
+}
+
+// Account keys of a v0 message, the static ones followed by those loaded from `table`
+fn account_keys(transaction: &VersionedTransaction, table: &[Pubkey]) -> Vec<Pubkey> {
+    let lookups = transaction.message.address_table_lookups().unwrap();
+    let mut keys = transaction.message.static_account_keys().to_vec();
+    keys.extend(
+        lookups
+            .iter()
+            .flat_map(|lookup| lookup.writable_indexes.iter().map(|index| table[*index as usize])),
+    );
+    keys.extend(
+        lookups
+            .iter()
+            .flat_map(|lookup| lookup.readonly_indexes.iter().map(|index| table[*index as usize])),
+    );
+    keys
+}
+
+#[tokio::test]
+async fn buy_swaps_through_the_api() {
+    let payer = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let (setup_program, swap_program, cleanup_program) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (route_account, table_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let table = Pubkey::new_unique();
+    let table_data = AddressLookupTable {
+        meta: LookupTableMeta::default(),
+        addresses: Cow::Owned(vec![Pubkey::new_unique(), table_account]),
+    }
+    .serialize_for_tests()
+    .unwrap();
+
+    let quote = serde_json::json!({
+        "inputMint": PUBKEY_WSOL.to_string(),
+        "inAmount": "1000000000",
+        "outputMint": mint.to_string(),
+        "outAmount": "35000000000",
+        "otherAmountThreshold": "34650000000",
+        "priceImpactPct": "0.002",
+        "routePlan": [{ "percent": 100 }],
+    });
+    let swap_instructions = serde_json::json!({
+        "computeBudgetInstructions": [instruction_json(&solana_sdk::compute_budget::ID, &[], &[2, 0, 0, 1, 0])],
+        "setupInstructions": [instruction_json(&setup_program, &[(payer.pubkey(), true, true)], &[1])],
+        "swapInstruction": instruction_json(&swap_program, &[(payer.pubkey(), true, true), (route_account, false, true), (table_account, false, false)], &[2, 3]),
+        "cleanupInstruction": instruction_json(&cleanup_program, &[(payer.pubkey(), true, true)], &[4]),
+        "addressLookupTableAddresses": [table.to_string()],
+    });
+    let (url, requests) = serve_api(vec![("/swap/v1/quote", quote.clone()), ("/swap/v1/swap-instructions", swap_instructions)]);
+
+    let stub = Arc::new(StubSWQoS::default());
+    let rpc = mock_rpc(vec![(
+        RpcRequest::GetMultipleAccounts,
+        multiple_accounts_result(&[(table, program_account(&ADDRESS_LOOKUP_TABLE_PROGRAM, table_data))]),
+    )]);
+    let endpoint = Arc::new(TradingEndpoint::new(rpc, vec![stub.clone() as Arc<dyn SWQoSTrait>]));
+    let aggregator = Aggregator::with_api(endpoint, &url, Some("secret".to_string()));
+
+    let signatures = aggregator.buy(&payer, &mint, 1_000_000_000, 100, None, Some(100_000)).await.unwrap();
+    assert_eq!(signatures.len(), 1);
+
+    let request = requests.recv().unwrap();
+    assert_eq!(request.method, "GET");
+    let (path, query) = request.target.split_once('?').unwrap();
+    assert_eq!(path, "/swap/v1/quote");
+    let query = query.split('&').map(|pair| pair.split_once('=').unwrap()).collect::<HashMap<_, _>>();
+    let wsol = PUBKEY_WSOL.to_string();
+    let mint_string = mint.to_string();
+    let expected = HashMap::from([
+        ("inputMint", wsol.as_str()),
+        ("outputMint", mint_string.as_str()),
+        ("amount", "1000000000"),
+        ("slippageBps", "100"),
+        ("swapMode", "ExactIn"),
+    ]);
+    assert_eq!(query, expected);
+    assert_eq!(request.headers["x-api-key"], "secret");
+
+    let request = requests.recv().unwrap();
+    assert_eq!((request.method.as_str(), request.target.as_str()), ("POST", "/swap/v1/swap-instructions"));
+    assert_eq!(request.headers["x-api-key"], "secret");
+    let body = request.body.unwrap();
+    assert_eq!(body["quoteResponse"], quote);
+    assert_eq!(body["userPublicKey"], payer.pubkey().to_string());
+    assert_eq!(body["wrapAndUnwrapSol"], true);
+
+    // Setup, swap and cleanup are sent in order, the API's compute budget is replaced by ours
+    let transaction = stub.wait_sent(1).await.remove(0);
+    let lookups = transaction.message.address_table_lookups().unwrap();
+    assert_eq!(lookups.len(), 1);
+    assert_eq!(lookups[0].account_key, table);
+    assert_eq!(lookups[0].readonly_indexes, vec![1]);
+
+    let keys = account_keys(&transaction, &[Pubkey::new_unique(), table_account]);
+    let instructions = transaction
+        .message
+        .instructions()
+        .iter()
+        .map(|instruction| {
+            let accounts = instruction.accounts.iter().map(|index| keys[*index as usize]).collect::<Vec<_>>();
+            (keys[instruction.program_id_index as usize], accounts, instruction.data.clone())
+        })
+        .filter(|(program_id, _, _)| [setup_program, swap_program, cleanup_program].contains(program_id))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        instructions,
+        vec![
+            (setup_program, vec![payer.pubkey()], vec![1]),
+            (swap_program, vec![payer.pubkey(), route_account, table_account], vec![2, 3]),
+            (cleanup_program, vec![payer.pubkey()], vec![4]),
+        ]
+    );
+    let compute_budget = transaction
+        .message
+        .instructions()
+        .iter()
+        .filter(|instruction| keys[instruction.program_id_index as usize] == solana_sdk::compute_budget::ID)
+        .all(|instruction| instruction.data != [2, 0, 0, 1, 0]);
+    assert!(compute_budget);
+}
+
+#[tokio::test]
+async fn route_with_other_instructions_is_rejected() {
+    let payer = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let quote = serde_json::json!({
+        "inAmount": "1000000000",
+        "outAmount": "35000000000",
+        "otherAmountThreshold": "34650000000",
+    });
+    let swap_instructions = serde_json::json!({
+        "swapInstruction": instruction_json(&Pubkey::new_unique(), &[(payer.pubkey(), true, true)], &[2]),
+        "otherInstructions": [instruction_json(&Pubkey::new_unique(), &[(payer.pubkey(), true, true)], &[5])],
+    });
+    let (url, _requests) = serve_api(vec![("/swap/v1/quote", quote), ("/swap/v1/swap-instructions", swap_instructions)]);
+
+    let stub = Arc::new(StubSWQoS::default());
+    let endpoint = Arc::new(TradingEndpoint::new(mock_rpc(vec![]), vec![stub.clone() as Arc<dyn SWQoSTrait>]));
+    let aggregator = Aggregator::with_api(endpoint, &url, None);
+
+    let error = aggregator.buy(&payer, &mint, 1_000_000_000, 100, None, Some(100_000)).await.unwrap_err();
+    assert!(error.to_string().contains("other instructions"));
+    assert!(stub.sent.lock().unwrap().is_empty());
+}
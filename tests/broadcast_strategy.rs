@@ -0,0 +1,50 @@
+mod common;
+
+use common::{mock_rpc, StubSWQoS};
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair};
+use solana_trading_sdk::{
+    common::trading_endpoint::{BatchTxItem, BroadcastStrategy, TipPlacement, TradingEndpoint},
+    instruction::builder::PriorityFee,
+    swqos::SWQoSTrait,
+};
+use std::sync::Arc;
+
+fn items() -> Vec<BatchTxItem> {
+    vec![BatchTxItem {
+        payer: Arc::new(Keypair::new()),
+        instructions: vec![],
+        other_signers: vec![],
+    }]
+}
+
+// Batches and bundles carry one tip per provider, so only the per-provider strategy applies to them
+#[tokio::test]
+async fn batches_and_bundles_require_per_provider_strategy() {
+    let stub = Arc::new(StubSWQoS::default());
+    let endpoint = TradingEndpoint::new(mock_rpc(vec![]), vec![stub.clone() as Arc<dyn SWQoSTrait>]);
+    let fee = PriorityFee {
+        unit_limit: 200_000,
+        unit_price: 1_000,
+    };
+
+    for strategy in [BroadcastStrategy::SingleTransaction, BroadcastStrategy::SharedNonce(Pubkey::new_unique())] {
+        endpoint.set_broadcast_strategy(strategy);
+        let error = endpoint
+            .build_and_broadcast_batch_txs(items(), Hash::default(), fee, 100_000)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not supported for batches and bundles"), "{}", error);
+        let error = endpoint
+            .build_and_broadcast_bundle(items(), Hash::default(), Some(fee), 100_000, TipPlacement::LastTransaction)
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("not supported for batches and bundles"), "{}", error);
+    }
+    assert!(stub.sent.lock().unwrap().is_empty());
+
+    endpoint.set_broadcast_strategy(BroadcastStrategy::PerProvider);
+    let signatures = endpoint.build_and_broadcast_batch_txs(items(), Hash::default(), fee, 100_000).await.unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(stub.sent.lock().unwrap().len(), 1);
+}
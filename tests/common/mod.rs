@@ -0,0 +1,273 @@
+#![allow(dead_code)]
+
+use serde_json::Value;
+use solana_account_decoder::{encode_ui_account, UiAccountEncoding};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_request::RpcRequest,
+    rpc_response::{Response, RpcKeyedAccount, RpcResponseContext},
+};
+use solana_program::program_pack::Pack;
+use solana_rpc_client::mock_sender::MocksMap;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use solana_trading_sdk::{
+    common::{accounts::PUBKEY_WSOL, signer::TxSigner, trading_endpoint::TradingEndpoint},
+    dex::{
+        dex_traits::DexTrait,
+        types::{Create, PoolInfo, SwapInfo},
+    },
+    instruction::builder::PriorityFee,
+    swqos::SWQoSTrait,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+pub const STUB_TIP_ACCOUNT: Pubkey = pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5");
+
+// SWQoS that records what it is sent instead of sending it
+#[derive(Default)]
+pub struct StubSWQoS {
+    pub sent: Mutex<Vec<VersionedTransaction>>,
+}
+
+#[async_trait::async_trait]
+impl SWQoSTrait for StubSWQoS {
+    async fn send_transaction(&self, transaction: VersionedTransaction) -> anyhow::Result<()> {
+        self.sent.lock().unwrap().push(transaction);
+        Ok(())
+    }
+
+    async fn send_transactions(&self, transactions: Vec<VersionedTransaction>) -> anyhow::Result<()> {
+        self.sent.lock().unwrap().extend(transactions);
+        Ok(())
+    }
+
+    fn get_tip_account(&self) -> Option<Pubkey> {
+        Some(STUB_TIP_ACCOUNT)
+    }
+
+    fn get_name(&self) -> &str {
+        "stub"
+    }
+}
+
+impl StubSWQoS {
+    // Single transactions are sent from a spawned task, so they are awaited here
+    pub async fn wait_sent(&self, count: usize) -> Vec<VersionedTransaction> {
+        for _ in 0..100 {
+            if self.sent.lock().unwrap().len() >= count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+// DEX serving a fixed pool, its instructions only carry the swap amounts
+pub struct StubDex {
+    pub endpoint: Arc<TradingEndpoint>,
+    pub program_id: Pubkey,
+    pub pool_info: Option<PoolInfo>,
+    pub quote_mint: Pubkey,
+    pub buy_fee_basis_points: u64,
+}
+
+impl StubDex {
+    pub fn new(endpoint: Arc<TradingEndpoint>, pool_info: Option<PoolInfo>) -> Self {
+        Self {
+            endpoint,
+            program_id: Pubkey::new_unique(),
+            pool_info,
+            quote_mint: PUBKEY_WSOL,
+            buy_fee_basis_points: 0,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DexTrait for StubDex {
+    async fn initialize(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn initialized(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn use_wsol(&self) -> bool {
+        false
+    }
+
+    fn get_trading_endpoint(&self) -> Arc<TradingEndpoint> {
+        self.endpoint.clone()
+    }
+
+    fn get_quote_mint(&self, _: &Pubkey, _: Option<&Pubkey>) -> Pubkey {
+        self.quote_mint
+    }
+
+    fn get_buy_fee_basis_points(&self, _: &PoolInfo) -> u64 {
+        self.buy_fee_basis_points
+    }
+
+    async fn get_pool(&self, mint: &Pubkey) -> anyhow::Result<PoolInfo> {
+        self.pool_info.clone().ok_or(anyhow::anyhow!("Pool not found: {}", mint))
+    }
+
+    async fn create(&self, _: &dyn TxSigner, _: Create, _: Option<PriorityFee>, _: Option<u64>) -> anyhow::Result<Vec<solana_sdk::signature::Signature>> {
+        Err(anyhow::anyhow!("Not supported"))
+    }
+
+    fn build_buy_instruction(&self, _: &dyn TxSigner, _: &Pubkey, _: Option<&Pubkey>, buy: SwapInfo) -> anyhow::Result<Instruction> {
+        Ok(Instruction::new_with_bincode(self.program_id, &(0u8, buy.token_amount, buy.sol_amount), vec![]))
+    }
+
+    fn build_sell_instruction(&self, _: &dyn TxSigner, _: &Pubkey, _: Option<&Pubkey>, sell: SwapInfo) -> anyhow::Result<Instruction> {
+        Ok(Instruction::new_with_bincode(
+            self.program_id,
+            &(1u8, sell.token_amount, sell.sol_amount),
+            vec![],
+        ))
+    }
+}
+
+pub fn pool_info(sol_reserves: u64, token_reserves: u64) -> PoolInfo {
+    PoolInfo {
+        pool: Pubkey::new_unique(),
+        creator: None,
+        creator_vault: None,
+        config: None,
+        extra_address: None,
+        token_reserves,
+        sol_reserves,
+        complete: false,
+    }
+}
+
+// RPC client answering each request type with the given results, anything else with the "succeeds" mock defaults
+// Mocks of the same request are answered in order, one per call
+pub fn mock_rpc(mocks: Vec<(RpcRequest, Value)>) -> Arc<RpcClient> {
+    Arc::new(RpcClient::new_mock_with_mocks_map("succeeds", mocks.into_iter().collect::<MocksMap>()))
+}
+
+pub fn account_result(pubkey: &Pubkey, account: &Account) -> Value {
+    serde_json::to_value(Response {
+        context: RpcResponseContext::new(1),
+        value: Some(encode_ui_account(pubkey, account, UiAccountEncoding::Base64, None, None)),
+    })
+    .unwrap()
+}
+
+pub fn multiple_accounts_result(accounts: &[(Pubkey, Account)]) -> Value {
+    serde_json::to_value(Response {
+        context: RpcResponseContext::new(1),
+        value: accounts
+            .iter()
+            .map(|(pubkey, account)| Some(encode_ui_account(pubkey, account, UiAccountEncoding::Base64, None, None)))
+            .collect::<Vec<_>>(),
+    })
+    .unwrap()
+}
+
+// Like `multiple_accounts_result`, with missing accounts
+pub fn optional_accounts_result(accounts: &[(Pubkey, Option<Account>)]) -> Value {
+    serde_json::to_value(Response {
+        context: RpcResponseContext::new(1),
+        value: accounts
+            .iter()
+            .map(|(pubkey, account)| {
+                account
+                    .as_ref()
+                    .map(|account| encode_ui_account(pubkey, account, UiAccountEncoding::Base64, None, None))
+            })
+            .collect::<Vec<_>>(),
+    })
+    .unwrap()
+}
+
+// Response of a `getProgramAccounts` scan without context
+pub fn program_accounts_result(accounts: &[(Pubkey, Account)]) -> Value {
+    serde_json::to_value(
+        accounts
+            .iter()
+            .map(|(pubkey, account)| RpcKeyedAccount {
+                pubkey: pubkey.to_string(),
+                account: encode_ui_account(pubkey, account, UiAccountEncoding::Base64, None, None),
+            })
+            .collect::<Vec<_>>(),
+    )
+    .unwrap()
+}
+
+pub fn program_account(owner: &Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: *owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// SPL token account of `owner` holding `amount` of `mint`
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    program_account(&spl_token::ID, data)
+}
+
+// Instructions of `program_id` in `transaction`, with their account metas resolved from the message
+pub fn find_instructions(transaction: &VersionedTransaction, program_id: &Pubkey) -> Vec<Instruction> {
+    let message = &transaction.message;
+    let keys = message.static_account_keys();
+    message
+        .instructions()
+        .iter()
+        .filter(|instruction| keys[instruction.program_id_index as usize] == *program_id)
+        .map(|instruction| Instruction {
+            program_id: *program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|index| {
+                    let index = *index as usize;
+                    AccountMeta {
+                        pubkey: keys[index],
+                        is_signer: message.is_signer(index),
+                        is_writable: message.is_maybe_writable(index, None),
+                    }
+                })
+                .collect(),
+            data: instruction.data.clone(),
+        })
+        .collect()
+}
+
+// The writable and signer flags of a compiled message are the union over its instructions, so only keys and order are compared
+pub fn assert_instruction(actual: &Instruction, expected: &Instruction) {
+    assert_eq!(actual.program_id, expected.program_id);
+    assert_eq!(actual.data, expected.data);
+    let actual_keys = actual.accounts.iter().map(|meta| meta.pubkey).collect::<Vec<_>>();
+    let expected_keys = expected.accounts.iter().map(|meta| meta.pubkey).collect::<Vec<_>>();
+    assert_eq!(actual_keys, expected_keys);
+    for (actual, expected) in actual.accounts.iter().zip(&expected.accounts) {
+        assert!(actual.is_writable || !expected.is_writable, "{} should be writable", expected.pubkey);
+        assert!(actual.is_signer || !expected.is_signer, "{} should sign", expected.pubkey);
+    }
+}
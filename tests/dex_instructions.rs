@@ -0,0 +1,736 @@
+mod common;
+
+use common::{
+    account_result, assert_instruction, find_instructions, mock_rpc, multiple_accounts_result, program_account, program_accounts_result, token_account,
+    StubSWQoS,
+};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+use solana_trading_sdk::{
+    common::{accounts::PUBKEY_WSOL, trading_endpoint::TradingEndpoint},
+    dex::{
+        amm_calc::{amm_buy_get_sol_in, amm_buy_get_token_out, amm_sell_get_sol_out, calculate_with_slippage_buy, calculate_with_slippage_sell},
+        dex_traits::DexTrait,
+        pumpfun::Pumpfun,
+        pumpfun_common_types::{BuyInfo, SellInfo},
+        pumpfun_types::{self, BondingCurveAccount},
+        pumpswap::PumpSwap,
+        pumpswap_types::{self, CreatePoolInfo, DepositInfo, PoolAccount, PoolSelector, WithdrawInfo},
+        types::{BatchBuyParam, BatchSellParam, SwapInfo, TokenAmountType},
+    },
+    instruction::builder::PriorityFee,
+    swqos::SWQoSTrait,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::sync::Arc;
+
+const SLIPPAGE_BASIS_POINTS: u64 = 100;
+const FEE: PriorityFee = PriorityFee {
+    unit_limit: 100_000,
+    unit_price: 1_000,
+};
+const TIP: u64 = 100_000;
+const BUY_SOL_AMOUNTS: [u64; 2] = [100_000_000, 200_000_000];
+const SELL_TOKEN_AMOUNTS: [u64; 2] = [1_000_000_000_000, 2_000_000_000_000];
+
+struct PumpfunFixture {
+    dex: Pumpfun,
+    stub: Arc<StubSWQoS>,
+    mint: Pubkey,
+    creator: Pubkey,
+    curve: BondingCurveAccount,
+}
+
+fn pumpfun_fixture() -> PumpfunFixture {
+    let mint = Pubkey::new_unique();
+    let creator = Pubkey::new_unique();
+    let curve = BondingCurveAccount {
+        discriminator: 0,
+        virtual_token_reserves: 1_000_000_000_000_000,
+        virtual_sol_reserves: 40_000_000_000,
+        real_token_reserves: 700_000_000_000_000,
+        real_sol_reserves: 10_000_000_000,
+        token_total_supply: 1_000_000_000_000_000,
+        complete: false,
+        creator,
+    };
+    let bonding_curve = Pumpfun::get_bonding_curve_pda(&mint).unwrap();
+    let account = program_account(&pumpfun_types::PUBKEY_PUMPFUN, bincode::serialize(&curve).unwrap());
+    let rpc = mock_rpc(vec![(RpcRequest::GetAccountInfo, account_result(&bonding_curve, &account))]);
+
+    let stub = Arc::new(StubSWQoS::default());
+    let endpoint = Arc::new(TradingEndpoint::new(rpc, vec![stub.clone() as Arc<dyn SWQoSTrait>]));
+    endpoint.set_token_program(&mint, &spl_token::ID);
+    let dex = Pumpfun::new(endpoint);
+    dex.global_account
+        .set(Arc::new(pumpfun_types::GlobalAccount {
+            discriminator: 0,
+            initialized: true,
+            authority: Pubkey::new_unique(),
+            fee_recipient: pumpfun_types::PUBKEY_FEE_RECIPIENT,
+            initial_virtual_token_reserves: pumpfun_types::INITIAL_VIRTUAL_TOKEN_RESERVES,
+            initial_virtual_sol_reserves: pumpfun_types::INITIAL_VIRTUAL_SOL_RESERVES,
+            initial_real_token_reserves: 793_100_000_000_000,
+            token_total_supply: 1_000_000_000_000_000,
+            fee_basis_points: 95,
+        }))
+        .unwrap();
+
+    PumpfunFixture {
+        dex,
+        stub,
+        mint,
+        creator,
+        curve,
+    }
+}
+
+fn pumpfun_instruction(payer: &Pubkey, fixture: &PumpfunFixture, data: Vec<u8>, sell: bool) -> Instruction {
+    let mint = &fixture.mint;
+    let bonding_curve = Pumpfun::get_bonding_curve_pda(mint).unwrap();
+    let creator_vault = AccountMeta::new(Pumpfun::get_creator_vault_pda(&fixture.creator).unwrap(), false);
+    let token_program = AccountMeta::new_readonly(spl_token::ID, false);
+    let (first, second) = if sell {
+        (creator_vault, token_program)
+    } else {
+        (token_program, creator_vault)
+    };
+
+    Instruction {
+        program_id: pumpfun_types::PUBKEY_PUMPFUN,
+        accounts: vec![
+            AccountMeta::new_readonly(pumpfun_types::PUBKEY_GLOBAL_ACCOUNT, false),
+            AccountMeta::new(pumpfun_types::PUBKEY_FEE_RECIPIENT, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(bonding_curve, false),
+            AccountMeta::new(get_associated_token_address_with_program_id(&bonding_curve, mint, &spl_token::ID), false),
+            AccountMeta::new(get_associated_token_address_with_program_id(payer, mint, &spl_token::ID), false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+            first,
+            second,
+            AccountMeta::new_readonly(pumpfun_types::PUBKEY_EVENT_AUTHORITY, false),
+            AccountMeta::new_readonly(pumpfun_types::PUBKEY_PUMPFUN, false),
+        ],
+        data,
+    }
+}
+
+// Expected buys, each one quoted against the reserves left by the previous ones
+fn expected_buys(mut sol_reserves: u64, mut token_reserves: u64, sol_amounts: &[u64]) -> Vec<Vec<u8>> {
+    sol_amounts
+        .iter()
+        .map(|sol_amount| {
+            let token_amount = amm_buy_get_token_out(sol_reserves, token_reserves, *sol_amount);
+            sol_reserves += sol_amount;
+            token_reserves -= token_amount;
+            BuyInfo::from(SwapInfo {
+                token_amount,
+                sol_amount: calculate_with_slippage_buy(*sol_amount, SLIPPAGE_BASIS_POINTS),
+            })
+            .to_buffer()
+            .unwrap()
+        })
+        .collect()
+}
+
+// Expected sells, with the token amount passed through unchanged
+fn expected_sells(mut sol_reserves: u64, mut token_reserves: u64, token_amounts: &[u64]) -> Vec<Vec<u8>> {
+    token_amounts
+        .iter()
+        .map(|token_amount| {
+            let sol_amount = amm_sell_get_sol_out(sol_reserves, token_reserves, *token_amount);
+            sol_reserves -= sol_amount;
+            token_reserves += token_amount;
+            SellInfo::from(SwapInfo {
+                token_amount: *token_amount,
+                sol_amount: calculate_with_slippage_sell(sol_amount, SLIPPAGE_BASIS_POINTS),
+            })
+            .to_buffer()
+            .unwrap()
+        })
+        .collect()
+}
+
+fn batch_payers() -> Vec<Arc<Keypair>> {
+    vec![Arc::new(Keypair::new()), Arc::new(Keypair::new())]
+}
+
+#[tokio::test]
+async fn pumpfun_buy() {
+    let fixture = pumpfun_fixture();
+    let payer = Keypair::new();
+    let curve = &fixture.curve;
+
+    fixture
+        .dex
+        .buy(&payer, &fixture.mint, BUY_SOL_AMOUNTS[0], SLIPPAGE_BASIS_POINTS, Some(FEE), Some(TIP))
+        .await
+        .unwrap();
+
+    let sent = fixture.stub.wait_sent(1).await;
+    let data = expected_buys(curve.virtual_sol_reserves, curve.virtual_token_reserves, &BUY_SOL_AMOUNTS[..1]).remove(0);
+    let instructions = find_instructions(&sent[0], &pumpfun_types::PUBKEY_PUMPFUN);
+    assert_eq!(instructions.len(), 1);
+    assert_instruction(&instructions[0], &pumpfun_instruction(&payer.pubkey(), &fixture, data, false));
+}
+
+#[tokio::test]
+async fn pumpfun_sell() {
+    let fixture = pumpfun_fixture();
+    let payer = Keypair::new();
+    let curve = &fixture.curve;
+
+    fixture
+        .dex
+        .sell(
+            &payer,
+            &fixture.mint,
+            TokenAmountType::Amount(SELL_TOKEN_AMOUNTS[0]),
+            SLIPPAGE_BASIS_POINTS,
+            false,
+            Some(FEE),
+            Some(TIP),
+        )
+        .await
+        .unwrap();
+
+    let sent = fixture.stub.wait_sent(1).await;
+    let data = expected_sells(curve.virtual_sol_reserves, curve.virtual_token_reserves, &SELL_TOKEN_AMOUNTS[..1]).remove(0);
+    let instructions = find_instructions(&sent[0], &pumpfun_types::PUBKEY_PUMPFUN);
+    assert_eq!(instructions.len(), 1);
+    assert_instruction(&instructions[0], &pumpfun_instruction(&payer.pubkey(), &fixture, data, true));
+}
+
+#[tokio::test]
+async fn pumpfun_batch_buy() {
+    let fixture = pumpfun_fixture();
+    let payers = batch_payers();
+    let curve = &fixture.curve;
+    let items = payers
+        .iter()
+        .zip(BUY_SOL_AMOUNTS)
+        .map(|(payer, sol_amount)| BatchBuyParam {
+            payer: payer.clone(),
+            sol_amount,
+        })
+        .collect();
+
+    fixture.dex.batch_buy(&fixture.mint, SLIPPAGE_BASIS_POINTS, FEE, TIP, items).await.unwrap();
+
+    let sent = fixture.stub.wait_sent(2).await;
+    assert_eq!(sent.len(), 2);
+    let expected = expected_buys(curve.virtual_sol_reserves, curve.virtual_token_reserves, &BUY_SOL_AMOUNTS);
+    for ((tx, payer), data) in sent.iter().zip(&payers).zip(expected) {
+        let instructions = find_instructions(tx, &pumpfun_types::PUBKEY_PUMPFUN);
+        assert_eq!(instructions.len(), 1);
+        assert_instruction(&instructions[0], &pumpfun_instruction(&payer.pubkey(), &fixture, data, false));
+    }
+}
+
+#[tokio::test]
+async fn pumpfun_batch_sell() {
+    let fixture = pumpfun_fixture();
+    let payers = batch_payers();
+    let curve = &fixture.curve;
+    let items = payers
+        .iter()
+        .zip(SELL_TOKEN_AMOUNTS)
+        .map(|(payer, token_amount)| BatchSellParam::new(payer.clone(), token_amount, false))
+        .collect();
+
+    fixture.dex.batch_sell(&fixture.mint, SLIPPAGE_BASIS_POINTS, FEE, TIP, items).await.unwrap();
+
+    let sent = fixture.stub.wait_sent(2).await;
+    assert_eq!(sent.len(), 2);
+    let expected = expected_sells(curve.virtual_sol_reserves, curve.virtual_token_reserves, &SELL_TOKEN_AMOUNTS);
+    for ((tx, payer), data) in sent.iter().zip(&payers).zip(expected) {
+        let instructions = find_instructions(tx, &pumpfun_types::PUBKEY_PUMPFUN);
+        assert_eq!(instructions.len(), 1);
+        assert_instruction(&instructions[0], &pumpfun_instruction(&payer.pubkey(), &fixture, data, true));
+    }
+}
+
+#[test]
+fn pumpfun_launch_buys_deduct_the_buy_fee() {
+    let fixture = pumpfun_fixture();
+    let (sol_reserves, token_reserves) = (pumpfun_types::INITIAL_VIRTUAL_SOL_RESERVES, pumpfun_types::INITIAL_VIRTUAL_TOKEN_RESERVES);
+    let projected = fixture.dex.project_launch_buys(&BUY_SOL_AMOUNTS);
+
+    // 95 bps paid on top of the SOL reaching the curve
+    let sol_in = BUY_SOL_AMOUNTS[0] * 10_000 / 10_095;
+    assert_eq!(projected[0], amm_buy_get_token_out(sol_reserves, token_reserves, sol_in));
+    let second_sol_in = BUY_SOL_AMOUNTS[1] * 10_000 / 10_095;
+    assert_eq!(
+        projected[1],
+        amm_buy_get_token_out(sol_reserves + sol_in, token_reserves - projected[0], second_sol_in)
+    );
+
+    // Bought exact-out, the tokens cost no more than the SOL amount once the fee is added
+    let cost = amm_buy_get_sol_in(sol_reserves, token_reserves, projected[0]);
+    assert!(cost + cost * 95 / 10_000 <= BUY_SOL_AMOUNTS[0]);
+}
+
+struct PumpSwapFixture {
+    dex: PumpSwap,
+    stub: Arc<StubSWQoS>,
+    mint: Pubkey,
+    pool: Pubkey,
+    pool_account: PoolAccount,
+    fee_recipient: Pubkey,
+    base_reserve: u64,
+    quote_reserve: u64,
+}
+
+fn pumpswap_fixture() -> PumpSwapFixture {
+    pumpswap_fixture_with_scanned_pool(None)
+}
+
+// `scanned_pool` is returned by pool scans, alongside the canonical pool returned by account lookups
+fn pumpswap_fixture_with_scanned_pool(scanned_pool: Option<(Pubkey, PoolAccount)>) -> PumpSwapFixture {
+    let mint = Pubkey::new_unique();
+    let pool = PumpSwap::get_pool_address(&mint).unwrap();
+    let (base_reserve, quote_reserve) = (200_000_000_000_000, 100_000_000_000);
+    let pool_account = PoolAccount {
+        discriminator: u64::from_le_bytes(pumpswap_types::POOL_ACCOUNT_DISCRIMINATOR),
+        pool_bump: 255,
+        index: 0,
+        creator: PumpSwap::get_pool_authority_pda(&mint).unwrap(),
+        base_mint: mint,
+        quote_mint: PUBKEY_WSOL,
+        lp_mint: Pubkey::new_unique(),
+        pool_base_token_account: Pubkey::new_unique(),
+        pool_quote_token_account: Pubkey::new_unique(),
+        lp_supply: 1_000_000_000,
+        coin_creator: Pubkey::new_unique(),
+    };
+    let vaults = [
+        (pool_account.pool_base_token_account, token_account(&mint, &pool, base_reserve)),
+        (pool_account.pool_quote_token_account, token_account(&PUBKEY_WSOL, &pool, quote_reserve)),
+    ];
+    let account = program_account(&pumpswap_types::PUBKEY_PUMPSWAP, bincode::serialize(&pool_account).unwrap());
+    let mut mocks = vec![
+        (RpcRequest::GetAccountInfo, account_result(&pool, &account)),
+        (RpcRequest::GetMultipleAccounts, multiple_accounts_result(&vaults)),
+    ];
+    if let Some((scanned_pool, mut scanned_account)) = scanned_pool {
+        scanned_account.base_mint = mint;
+        let vaults = [
+            (scanned_account.pool_base_token_account, token_account(&mint, &scanned_pool, base_reserve * 2)),
+            (
+                scanned_account.pool_quote_token_account,
+                token_account(&PUBKEY_WSOL, &scanned_pool, quote_reserve * 2),
+            ),
+        ];
+        let account = program_account(&pumpswap_types::PUBKEY_PUMPSWAP, bincode::serialize(&scanned_account).unwrap());
+        mocks.push((RpcRequest::GetProgramAccounts, program_accounts_result(&[(scanned_pool, account)])));
+        mocks.push((RpcRequest::GetMultipleAccounts, multiple_accounts_result(&vaults)));
+    }
+    let rpc = mock_rpc(mocks);
+
+    let stub = Arc::new(StubSWQoS::default());
+    let endpoint = Arc::new(TradingEndpoint::new(rpc, vec![stub.clone() as Arc<dyn SWQoSTrait>]));
+    endpoint.set_token_program(&mint, &spl_token::ID);
+    let dex = PumpSwap::new(endpoint);
+    let fee_recipient = Pubkey::new_unique();
+    dex.global_account
+        .set(Arc::new(pumpswap_types::GlobalAccount {
+            discriminator: 0,
+            admin: Pubkey::new_unique(),
+            lp_fee_basis_points: 20,
+            protocol_fee_basis_points: 5,
+            disable_flags: 0,
+            protocol_fee_recipients: [fee_recipient; 8],
+        }))
+        .unwrap();
+
+    PumpSwapFixture {
+        dex,
+        stub,
+        mint,
+        pool,
+        pool_account,
+        fee_recipient,
+        base_reserve,
+        quote_reserve,
+    }
+}
+
+fn pumpswap_instruction(payer: &Pubkey, fixture: &PumpSwapFixture, data: Vec<u8>) -> Instruction {
+    let (mint, pool_account) = (&fixture.mint, &fixture.pool_account);
+    let creator_vault = PumpSwap::get_creator_vault(&pool_account.coin_creator).unwrap();
+
+    Instruction {
+        program_id: pumpswap_types::PUBKEY_PUMPSWAP,
+        accounts: vec![
+            AccountMeta::new_readonly(fixture.pool, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(pumpswap_types::PUBKEY_GLOBAL_ACCOUNT, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(PUBKEY_WSOL, false),
+            AccountMeta::new(get_associated_token_address_with_program_id(payer, mint, &spl_token::ID), false),
+            AccountMeta::new(get_associated_token_address_with_program_id(payer, &PUBKEY_WSOL, &spl_token::ID), false),
+            AccountMeta::new(pool_account.pool_base_token_account, false),
+            AccountMeta::new(pool_account.pool_quote_token_account, false),
+            AccountMeta::new_readonly(fixture.fee_recipient, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(&fixture.fee_recipient, &PUBKEY_WSOL, &spl_token::ID),
+                false,
+            ),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(pumpswap_types::PUBKEY_EVENT_AUTHORITY, false),
+            AccountMeta::new_readonly(pumpswap_types::PUBKEY_PUMPSWAP, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(&creator_vault, &PUBKEY_WSOL, &spl_token::ID),
+                false,
+            ),
+            AccountMeta::new_readonly(creator_vault, false),
+        ],
+        data,
+    }
+}
+
+#[tokio::test]
+async fn pumpswap_buy() {
+    let fixture = pumpswap_fixture();
+    let payer = Keypair::new();
+
+    fixture
+        .dex
+        .buy(&payer, &fixture.mint, BUY_SOL_AMOUNTS[0], SLIPPAGE_BASIS_POINTS, Some(FEE), Some(TIP))
+        .await
+        .unwrap();
+
+    let sent = fixture.stub.wait_sent(1).await;
+    let data = expected_buys(fixture.quote_reserve, fixture.base_reserve, &BUY_SOL_AMOUNTS[..1]).remove(0);
+    let instructions = find_instructions(&sent[0], &pumpswap_types::PUBKEY_PUMPSWAP);
+    assert_eq!(instructions.len(), 1);
+    assert_instruction(&instructions[0], &pumpswap_instruction(&payer.pubkey(), &fixture, data));
+}
+
+#[tokio::test]
+async fn pumpswap_sell() {
+    let fixture = pumpswap_fixture();
+    let payer = Keypair::new();
+
+    fixture
+        .dex
+        .sell(
+            &payer,
+            &fixture.mint,
+            TokenAmountType::Amount(SELL_TOKEN_AMOUNTS[0]),
+            SLIPPAGE_BASIS_POINTS,
+            false,
+            Some(FEE),
+            Some(TIP),
+        )
+        .await
+        .unwrap();
+
+    let sent = fixture.stub.wait_sent(1).await;
+    let data = expected_sells(fixture.quote_reserve, fixture.base_reserve, &SELL_TOKEN_AMOUNTS[..1]).remove(0);
+    let instructions = find_instructions(&sent[0], &pumpswap_types::PUBKEY_PUMPSWAP);
+    assert_eq!(instructions.len(), 1);
+    assert_instruction(&instructions[0], &pumpswap_instruction(&payer.pubkey(), &fixture, data));
+}
+
+#[tokio::test]
+async fn pumpswap_batch_buy() {
+    let fixture = pumpswap_fixture();
+    let payers = batch_payers();
+    let items = payers
+        .iter()
+        .zip(BUY_SOL_AMOUNTS)
+        .map(|(payer, sol_amount)| BatchBuyParam {
+            payer: payer.clone(),
+            sol_amount,
+        })
+        .collect();
+
+    fixture.dex.batch_buy(&fixture.mint, SLIPPAGE_BASIS_POINTS, FEE, TIP, items).await.unwrap();
+
+    let sent = fixture.stub.wait_sent(2).await;
+    assert_eq!(sent.len(), 2);
+    let expected = expected_buys(fixture.quote_reserve, fixture.base_reserve, &BUY_SOL_AMOUNTS);
+    for ((tx, payer), data) in sent.iter().zip(&payers).zip(expected) {
+        let instructions = find_instructions(tx, &pumpswap_types::PUBKEY_PUMPSWAP);
+        assert_eq!(instructions.len(), 1);
+        assert_instruction(&instructions[0], &pumpswap_instruction(&payer.pubkey(), &fixture, data));
+    }
+}
+
+#[tokio::test]
+async fn pumpswap_batch_sell() {
+    let fixture = pumpswap_fixture();
+    let payers = batch_payers();
+    let items = payers
+        .iter()
+        .zip(SELL_TOKEN_AMOUNTS)
+        .map(|(payer, token_amount)| BatchSellParam {
+            payer: payer.clone(),
+            token_amount: TokenAmountType::Amount(token_amount),
+            close_mint_ata: true,
+        })
+        .collect();
+
+    fixture.dex.batch_sell(&fixture.mint, SLIPPAGE_BASIS_POINTS, FEE, TIP, items).await.unwrap();
+
+    let sent = fixture.stub.wait_sent(2).await;
+    assert_eq!(sent.len(), 2);
+    let expected = expected_sells(fixture.quote_reserve, fixture.base_reserve, &SELL_TOKEN_AMOUNTS);
+    for ((tx, payer), data) in sent.iter().zip(&payers).zip(expected) {
+        let instructions = find_instructions(tx, &pumpswap_types::PUBKEY_PUMPSWAP);
+        assert_eq!(instructions.len(), 1);
+        assert_instruction(&instructions[0], &pumpswap_instruction(&payer.pubkey(), &fixture, data));
+    }
+}
+
+#[tokio::test]
+async fn pumpswap_builds_against_the_quoted_pool() {
+    let other_pool = Pubkey::new_unique();
+    let other_account = PoolAccount {
+        discriminator: u64::from_le_bytes(pumpswap_types::POOL_ACCOUNT_DISCRIMINATOR),
+        pool_bump: 254,
+        index: 1,
+        creator: Pubkey::new_unique(),
+        base_mint: Pubkey::default(),
+        quote_mint: PUBKEY_WSOL,
+        lp_mint: Pubkey::new_unique(),
+        pool_base_token_account: Pubkey::new_unique(),
+        pool_quote_token_account: Pubkey::new_unique(),
+        lp_supply: 1_000_000_000,
+        coin_creator: Pubkey::new_unique(),
+    };
+    let fixture = pumpswap_fixture_with_scanned_pool(Some((other_pool, other_account)));
+    let payer = Keypair::new();
+    let quoted = fixture.dex.get_pool(&fixture.mint).await.unwrap();
+
+    // A lookup with another selector loads a different pool of the same mint between quoting and building
+    let other = fixture.dex.get_pool_with_selector(&fixture.mint, PoolSelector::DeepestLiquidity).await.unwrap();
+    assert_eq!(other.pool, other_pool);
+
+    let swap = SwapInfo {
+        token_amount: SELL_TOKEN_AMOUNTS[0],
+        sol_amount: BUY_SOL_AMOUNTS[0],
+    };
+    let instruction = fixture
+        .dex
+        .build_buy_instruction(&payer, &fixture.mint, quoted.extra_address.as_ref(), swap)
+        .unwrap();
+    let data = BuyInfo::from(swap).to_buffer().unwrap();
+    assert_instruction(&instruction, &pumpswap_instruction(&payer.pubkey(), &fixture, data));
+}
+
+#[tokio::test]
+async fn pumpswap_failed_pool_scan_is_not_cached() {
+    let mint = Pubkey::new_unique();
+    let pool = Pubkey::new_unique();
+    let pool_account = PoolAccount {
+        discriminator: u64::from_le_bytes(pumpswap_types::POOL_ACCOUNT_DISCRIMINATOR),
+        pool_bump: 254,
+        index: 1,
+        creator: Pubkey::new_unique(),
+        base_mint: mint,
+        quote_mint: PUBKEY_WSOL,
+        lp_mint: Pubkey::new_unique(),
+        pool_base_token_account: Pubkey::new_unique(),
+        pool_quote_token_account: Pubkey::new_unique(),
+        lp_supply: 1_000_000_000,
+        coin_creator: Pubkey::new_unique(),
+    };
+    let vaults = [
+        (pool_account.pool_base_token_account, token_account(&mint, &pool, 200_000_000_000_000)),
+        (pool_account.pool_quote_token_account, token_account(&PUBKEY_WSOL, &pool, 100_000_000_000)),
+    ];
+    let account = program_account(&pumpswap_types::PUBKEY_PUMPSWAP, bincode::serialize(&pool_account).unwrap());
+    // No canonical pool, a scan failing once, then finding the pool
+    let no_account = serde_json::json!({ "context": { "slot": 1 }, "value": null });
+    let rpc = mock_rpc(vec![
+        (RpcRequest::GetAccountInfo, no_account.clone()),
+        (RpcRequest::GetAccountInfo, no_account),
+        (RpcRequest::GetProgramAccounts, serde_json::json!("unavailable")),
+        (RpcRequest::GetProgramAccounts, program_accounts_result(&[(pool, account)])),
+        (RpcRequest::GetMultipleAccounts, multiple_accounts_result(&vaults)),
+    ]);
+    let endpoint = Arc::new(TradingEndpoint::new(rpc, vec![]));
+    endpoint.set_token_program(&mint, &spl_token::ID);
+    let dex = PumpSwap::new(endpoint);
+
+    assert!(dex.get_pool(&mint).await.is_err());
+    assert_eq!(dex.get_pool(&mint).await.unwrap().pool, pool);
+}
+
+// Accounts shared by the deposit and withdraw instructions
+fn pumpswap_liquidity_accounts(payer: &Pubkey, fixture: &PumpSwapFixture) -> Vec<AccountMeta> {
+    let (mint, pool_account) = (&fixture.mint, &fixture.pool_account);
+
+    vec![
+        AccountMeta::new(fixture.pool, false),
+        AccountMeta::new_readonly(pumpswap_types::PUBKEY_GLOBAL_ACCOUNT, false),
+        AccountMeta::new_readonly(*payer, true),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(PUBKEY_WSOL, false),
+        AccountMeta::new(pool_account.lp_mint, false),
+        AccountMeta::new(get_associated_token_address_with_program_id(payer, mint, &spl_token::ID), false),
+        AccountMeta::new(get_associated_token_address_with_program_id(payer, &PUBKEY_WSOL, &spl_token::ID), false),
+        AccountMeta::new(
+            get_associated_token_address_with_program_id(payer, &pool_account.lp_mint, &spl_token_2022::ID),
+            false,
+        ),
+        AccountMeta::new(pool_account.pool_base_token_account, false),
+        AccountMeta::new(pool_account.pool_quote_token_account, false),
+        AccountMeta::new_readonly(spl_token::ID, false),
+        AccountMeta::new_readonly(spl_token_2022::ID, false),
+        AccountMeta::new_readonly(pumpswap_types::PUBKEY_EVENT_AUTHORITY, false),
+        AccountMeta::new_readonly(pumpswap_types::PUBKEY_PUMPSWAP, false),
+    ]
+}
+
+#[test]
+fn pumpswap_liquidity_quotes() {
+    let pool_info = pumpswap_types::PoolInfo {
+        pool_address: Pubkey::new_unique(),
+        pool_account: PoolAccount {
+            lp_supply: 1_000,
+            ..pumpswap_fixture().pool_account
+        },
+        pool_base_reserve: 1_000_003,
+        pool_quote_reserve: 500_001,
+    };
+
+    // LP tokens are rounded down and the amounts they take rounded up, in favour of the pool
+    assert_eq!(PumpSwap::quote_deposit(&pool_info, 10_000), (9, 9_001, 4_501));
+    assert_eq!(PumpSwap::quote_deposit(&pool_info, 999), (0, 0, 0));
+    // Amounts paid out are rounded down
+    assert_eq!(PumpSwap::quote_withdraw(&pool_info, 9), (9_000, 4_500));
+    assert_eq!(PumpSwap::quote_withdraw(&pool_info, 1_001), (0, 0));
+}
+
+#[tokio::test]
+async fn pumpswap_deposit() {
+    let fixture = pumpswap_fixture();
+    let payer = Keypair::new();
+    let base_amount = 1_000_000_000_000;
+
+    fixture
+        .dex
+        .deposit(&payer, &fixture.mint, base_amount, SLIPPAGE_BASIS_POINTS, Some(FEE), Some(TIP))
+        .await
+        .unwrap();
+
+    let sent = fixture.stub.wait_sent(1).await;
+    // 1/200 of the pool, the same share of its quote reserve
+    let quote_amount = fixture.quote_reserve / 200;
+    let data = DepositInfo::new(
+        fixture.pool_account.lp_supply / 200,
+        calculate_with_slippage_buy(base_amount, SLIPPAGE_BASIS_POINTS),
+        calculate_with_slippage_buy(quote_amount, SLIPPAGE_BASIS_POINTS),
+    )
+    .to_buffer()
+    .unwrap();
+    let instructions = find_instructions(&sent[0], &pumpswap_types::PUBKEY_PUMPSWAP);
+    assert_eq!(instructions.len(), 1);
+    assert_instruction(
+        &instructions[0],
+        &Instruction {
+            program_id: pumpswap_types::PUBKEY_PUMPSWAP,
+            accounts: pumpswap_liquidity_accounts(&payer.pubkey(), &fixture),
+            data,
+        },
+    );
+}
+
+#[tokio::test]
+async fn pumpswap_withdraw() {
+    let fixture = pumpswap_fixture();
+    let payer = Keypair::new();
+    let lp_amount = fixture.pool_account.lp_supply / 200;
+
+    fixture
+        .dex
+        .withdraw(
+            &payer,
+            &fixture.mint,
+            TokenAmountType::Amount(lp_amount),
+            SLIPPAGE_BASIS_POINTS,
+            Some(FEE),
+            Some(TIP),
+        )
+        .await
+        .unwrap();
+
+    let sent = fixture.stub.wait_sent(1).await;
+    let data = WithdrawInfo::new(
+        lp_amount,
+        calculate_with_slippage_sell(fixture.base_reserve / 200, SLIPPAGE_BASIS_POINTS),
+        calculate_with_slippage_sell(fixture.quote_reserve / 200, SLIPPAGE_BASIS_POINTS),
+    )
+    .to_buffer()
+    .unwrap();
+    let instructions = find_instructions(&sent[0], &pumpswap_types::PUBKEY_PUMPSWAP);
+    assert_eq!(instructions.len(), 1);
+    assert_instruction(
+        &instructions[0],
+        &Instruction {
+            program_id: pumpswap_types::PUBKEY_PUMPSWAP,
+            accounts: pumpswap_liquidity_accounts(&payer.pubkey(), &fixture),
+            data,
+        },
+    );
+}
+
+#[tokio::test]
+async fn pumpswap_create_pool() {
+    let fixture = pumpswap_fixture();
+    let payer = Keypair::new();
+    let (index, base_amount, quote_amount) = (1, 1_000_000_000_000, 10_000_000_000);
+
+    fixture
+        .dex
+        .create_pool(&payer, index, &fixture.mint, &PUBKEY_WSOL, base_amount, quote_amount, Some(FEE), Some(TIP))
+        .await
+        .unwrap();
+
+    let sent = fixture.stub.wait_sent(1).await;
+    let (payer, mint) = (payer.pubkey(), fixture.mint);
+    let pool = PumpSwap::get_pool_pda(index, &payer, &mint, &PUBKEY_WSOL).unwrap();
+    let lp_mint = PumpSwap::get_lp_mint_pda(&pool).unwrap();
+    let data = CreatePoolInfo::new(index, base_amount, quote_amount, payer).to_buffer().unwrap();
+    let instructions = find_instructions(&sent[0], &pumpswap_types::PUBKEY_PUMPSWAP);
+    assert_eq!(instructions.len(), 1);
+    assert_instruction(
+        &instructions[0],
+        &Instruction {
+            program_id: pumpswap_types::PUBKEY_PUMPSWAP,
+            accounts: vec![
+                AccountMeta::new(pool, false),
+                AccountMeta::new_readonly(pumpswap_types::PUBKEY_GLOBAL_ACCOUNT, false),
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new_readonly(PUBKEY_WSOL, false),
+                AccountMeta::new(lp_mint, false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer, &mint, &spl_token::ID), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer, &PUBKEY_WSOL, &spl_token::ID), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&payer, &lp_mint, &spl_token_2022::ID), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&pool, &mint, &spl_token::ID), false),
+                AccountMeta::new(get_associated_token_address_with_program_id(&pool, &PUBKEY_WSOL, &spl_token::ID), false),
+                AccountMeta::new_readonly(solana_program::system_program::ID, false),
+                AccountMeta::new_readonly(spl_token_2022::ID, false),
+                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(spl_token::ID, false),
+                AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+                AccountMeta::new_readonly(pumpswap_types::PUBKEY_EVENT_AUTHORITY, false),
+                AccountMeta::new_readonly(pumpswap_types::PUBKEY_PUMPSWAP, false),
+            ],
+            data,
+        },
+    );
+}
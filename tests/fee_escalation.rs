@@ -0,0 +1,64 @@
+mod common;
+
+use common::{mock_rpc, StubSWQoS};
+use solana_client::{
+    rpc_request::RpcRequest,
+    rpc_response::{Response, RpcResponseContext},
+};
+use solana_sdk::{instruction::InstructionError, signature::Keypair, transaction::TransactionError};
+use solana_trading_sdk::{
+    common::{
+        submission_policy::{FeeEscalation, SubmissionPolicy},
+        trading_endpoint::TradingEndpoint,
+    },
+    swqos::SWQoSTrait,
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::sync::Arc;
+
+fn endpoint(mocks: Vec<(RpcRequest, serde_json::Value)>) -> (TradingEndpoint, Arc<StubSWQoS>) {
+    let stub = Arc::new(StubSWQoS::default());
+    let endpoint = TradingEndpoint::new(mock_rpc(mocks), vec![stub.clone() as Arc<dyn SWQoSTrait>]);
+    endpoint.set_submission_policy(SubmissionPolicy {
+        escalation: Some(FeeEscalation {
+            max_attempts: 1,
+            ..Default::default()
+        }),
+        ..Default::default()
+    });
+    (endpoint, stub)
+}
+
+// A status lookup that fails is polled again instead of aborting the submission
+#[tokio::test]
+async fn transient_status_error_keeps_polling() {
+    let (endpoint, stub) = endpoint(vec![(RpcRequest::GetSignatureStatuses, serde_json::json!("unavailable"))]);
+
+    let signature = endpoint
+        .submit_with_escalation(&Keypair::new(), vec![], None, Some(100_000), None, &[])
+        .await
+        .unwrap();
+    assert_eq!(stub.wait_sent(1).await[0].signatures[0], signature);
+}
+
+#[tokio::test]
+async fn landed_error_fails_the_submission() {
+    let failed = TransactionError::InstructionError(0, InstructionError::Custom(1));
+    let statuses = Response {
+        context: RpcResponseContext::new(1),
+        value: vec![Some(TransactionStatus {
+            slot: 1,
+            confirmations: None,
+            status: Err(failed.clone()),
+            err: Some(failed),
+            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+        })],
+    };
+    let (endpoint, _) = endpoint(vec![(RpcRequest::GetSignatureStatuses, serde_json::to_value(statuses).unwrap())]);
+
+    let error = endpoint
+        .submit_with_escalation(&Keypair::new(), vec![], None, Some(100_000), None, &[])
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Transaction failed"), "{}", error);
+}
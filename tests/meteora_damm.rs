@@ -0,0 +1,81 @@
+mod common;
+
+use common::{find_instructions, mock_rpc, optional_accounts_result, program_account, StubSWQoS};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, system_instruction::SystemInstruction};
+use solana_trading_sdk::{
+    common::{accounts::PUBKEY_WSOL, mint_info::MintInfo, trading_endpoint::TradingEndpoint},
+    dex::{
+        amm_calc::{amm_amount_after_fee, amm_buy_get_token_out, calculate_with_slippage_sell},
+        dex_traits::DexTrait,
+        meteora_damm_v2::MeteoraDammV2,
+        meteora_damm_v2_types::{FEE_DENOMINATOR, POOL_ACCOUNT_DISCRIMINATOR, PUBKEY_METEORA_DAMM_V2},
+        meteora_dbc_types::DBC_DAMM_V2_MIGRATION_CONFIGS,
+    },
+    swqos::SWQoSTrait,
+};
+use std::sync::Arc;
+
+const POOL_LEN: usize = 1112;
+const FEE_NUMERATOR: u64 = 10_000_000;
+
+// DAMM v2 pool of `mint` (token A) against WSOL at a price of 1e-4 lamports per unit, fees taken from the output
+fn pool_data(mint: &Pubkey) -> Vec<u8> {
+    let mut data = vec![0u8; POOL_LEN];
+    data[..8].copy_from_slice(&POOL_ACCOUNT_DISCRIMINATOR);
+    data[8..16].copy_from_slice(&FEE_NUMERATOR.to_le_bytes());
+    data[168..200].copy_from_slice(mint.as_ref());
+    data[200..232].copy_from_slice(PUBKEY_WSOL.as_ref());
+    data[232..264].copy_from_slice(Pubkey::new_unique().as_ref());
+    data[264..296].copy_from_slice(Pubkey::new_unique().as_ref());
+    // Liquidity 1e12 and sqrt price 0.01, in Q64.64
+    data[360..376].copy_from_slice(&(1_000_000_000_000u128 << 64).to_le_bytes());
+    data[456..472].copy_from_slice(&((1u128 << 64) / 100).to_le_bytes());
+    data
+}
+
+// Graduated DBC tokens are found at the pool derived from the migration config without scanning,
+// and buys spend exactly the SOL given for a slippage-bounded minimum of tokens
+#[tokio::test]
+async fn damm_v2_buy_from_migrated_pool() {
+    let mint = Pubkey::new_unique();
+    let pool = MeteoraDammV2::get_pool_pda(&DBC_DAMM_V2_MIGRATION_CONFIGS[2], &mint, &PUBKEY_WSOL).unwrap();
+    let accounts = DBC_DAMM_V2_MIGRATION_CONFIGS
+        .iter()
+        .enumerate()
+        .map(|(index, config)| {
+            let address = MeteoraDammV2::get_pool_pda(config, &mint, &PUBKEY_WSOL).unwrap();
+            (address, (index == 2).then(|| program_account(&PUBKEY_METEORA_DAMM_V2, pool_data(&mint))))
+        })
+        .collect::<Vec<_>>();
+    let stub = Arc::new(StubSWQoS::default());
+    let rpc = mock_rpc(vec![(RpcRequest::GetMultipleAccounts, optional_accounts_result(&accounts))]);
+    let endpoint = Arc::new(TradingEndpoint::new(rpc, vec![stub.clone() as Arc<dyn SWQoSTrait>]));
+    endpoint.set_mint_info(&mint, MintInfo::new(spl_token::ID));
+    let dex = MeteoraDammV2::new(endpoint);
+
+    // The mocked accounts are served once, to the lookup `buy` makes
+    let (sol_amount, slippage_basis_points) = (1_000_000_000, 300);
+    let payer = Keypair::new();
+    dex.buy(&payer, &mint, sol_amount, slippage_basis_points, None, Some(100_000)).await.unwrap();
+    let transaction = stub.wait_sent(1).await.remove(0);
+
+    let token_out = amm_buy_get_token_out(10_000_000_000, 100_000_000_000_000, sol_amount);
+    let minimum_amount_out = calculate_with_slippage_sell(amm_amount_after_fee(token_out, FEE_NUMERATOR, FEE_DENOMINATOR), slippage_basis_points);
+    let swap = find_instructions(&transaction, &PUBKEY_METEORA_DAMM_V2);
+    assert_eq!(swap.len(), 1);
+    assert_eq!(swap[0].accounts[1].pubkey, pool);
+    assert_eq!(swap[0].data[8..16], sol_amount.to_le_bytes());
+    assert_eq!(swap[0].data[16..24], minimum_amount_out.to_le_bytes());
+
+    // The WSOL account is funded with the same amount
+    let transfers = find_instructions(&transaction, &solana_program::system_program::ID)
+        .into_iter()
+        .filter_map(|instruction| match bincode::deserialize::<SystemInstruction>(&instruction.data) {
+            Ok(SystemInstruction::Transfer { lamports }) if instruction.accounts[0].pubkey != instruction.accounts[1].pubkey => Some(lamports),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(transfers.contains(&sol_amount), "{:?}", transfers);
+    assert!(transfers.iter().all(|lamports| *lamports <= sol_amount), "{:?}", transfers);
+}
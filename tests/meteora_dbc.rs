@@ -0,0 +1,168 @@
+mod common;
+
+use common::{mock_rpc, StubSWQoS};
+use solana_sdk::pubkey::Pubkey;
+use solana_trading_sdk::{
+    common::{mint_info::MintInfo, trading_endpoint::TradingEndpoint},
+    dex::{
+        dex_traits::DexTrait,
+        meteora_dbc::MeteoraDBC,
+        meteora_dbc_types::{CurveConfig, DBC_FEE_DENOMINATOR, DBC_MAX_CURVE_POINTS},
+        types::PoolInfo,
+    },
+    swqos::SWQoSTrait,
+};
+use std::sync::Arc;
+
+const Q64: f64 = 18446744073709551616.0;
+const CONFIG_LEN: usize = 1040;
+// Segment i holds real liquidity `LIQUIDITY[i]` up to sqrt price `SQRT_START * POINTS[i]`
+const SQRT_START: f64 = 0.005477;
+const POINTS: [f64; 2] = [2.0, 4.0];
+const LIQUIDITY: [f64; 2] = [1.826e12, 1.0e12];
+
+fn q64(value: f64) -> u128 {
+    (value * Q64) as u128
+}
+
+// `PoolConfig` account with the test curve, `fee_numerator` charged on the quote side or on the output
+fn config_data(fee_numerator: u64, fee_in_quote: bool) -> Vec<u8> {
+    let mut data = vec![0u8; CONFIG_LEN];
+    data[104..112].copy_from_slice(&fee_numerator.to_le_bytes());
+    data[232] = if fee_in_quote { 0 } else { 1 };
+    let start = CONFIG_LEN - 16 - DBC_MAX_CURVE_POINTS * 32;
+    data[start..start + 16].copy_from_slice(&q64(SQRT_START).to_le_bytes());
+    for (index, (point, liquidity)) in POINTS.iter().zip(LIQUIDITY).enumerate() {
+        let offset = start + 16 + index * 32;
+        data[offset..offset + 16].copy_from_slice(&q64(SQRT_START * point).to_le_bytes());
+        data[offset + 16..offset + 32].copy_from_slice(&q64(liquidity).to_le_bytes());
+    }
+    data
+}
+
+// Float reference of a fee-free buy from the start price: quote = L * d(sqrt price), base = L * d(1 / sqrt price)
+fn reference_buy(quote_in: f64) -> f64 {
+    let (mut current, mut left, mut base_out) = (SQRT_START, quote_in, 0.0);
+    for (point, liquidity) in POINTS.iter().zip(LIQUIDITY) {
+        let upper = SQRT_START * point;
+        let next = (current + left / liquidity).min(upper);
+        base_out += liquidity * (1.0 / current - 1.0 / next);
+        left -= liquidity * (next - current);
+        current = next;
+        if left <= 0.0 {
+            break;
+        }
+    }
+    base_out
+}
+
+fn assert_close(actual: u64, expected: f64) {
+    assert!((actual as f64 - expected).abs() / expected < 1e-6, "{} vs {}", actual, expected);
+}
+
+#[test]
+fn parses_config_curve() {
+    let config = CurveConfig::from_account_data(&config_data(25_000_000, false)).unwrap();
+    assert_eq!(config.cliff_fee_numerator, 25_000_000);
+    assert!(!config.fee_in_quote);
+    assert_eq!(config.sqrt_start_price, q64(SQRT_START));
+    assert_eq!(config.curve.len(), POINTS.len());
+    assert!(CurveConfig::from_account_data(&[0u8; 100]).is_err());
+}
+
+#[test]
+fn quote_buy_follows_curve_segments() {
+    let config = CurveConfig::from_account_data(&config_data(0, true)).unwrap();
+
+    // Within the first segment, then across into the second one
+    for quote_in in [1_000_000_000u64, 15_000_000_000] {
+        let (base_out, next) = config.quote_buy(config.sqrt_start_price, quote_in);
+        assert_close(base_out, reference_buy(quote_in as f64));
+        assert!(next > config.sqrt_start_price);
+    }
+    let (_, next) = config.quote_buy(config.sqrt_start_price, 15_000_000_000);
+    assert!(next > config.curve[0].0 && next < config.curve[1].0);
+
+    // Buying in two steps lands where one buy does
+    let (first, middle) = config.quote_buy(config.sqrt_start_price, 10_000_000_000);
+    let (second, end) = config.quote_buy(middle, 5_000_000_000);
+    let (whole, whole_end) = config.quote_buy(config.sqrt_start_price, 15_000_000_000);
+    assert_close(first + second, whole as f64);
+    assert!(end.abs_diff(whole_end) < whole_end / 1_000_000);
+}
+
+#[test]
+fn quote_sell_returns_at_most_the_buy() {
+    let config = CurveConfig::from_account_data(&config_data(0, true)).unwrap();
+    let quote_in = 15_000_000_000;
+    let (base_out, after_buy) = config.quote_buy(config.sqrt_start_price, quote_in);
+
+    let (quote_out, after_sell) = config.quote_sell(after_buy, base_out);
+    assert!(quote_out <= quote_in);
+    assert_close(quote_out, quote_in as f64);
+    assert!(after_sell >= config.sqrt_start_price && after_sell - config.sqrt_start_price < config.sqrt_start_price / 1_000_000);
+}
+
+#[test]
+fn fee_modes() {
+    let fee_numerator = 10_000_000;
+    let fee = |amount: u64| (amount as u128 * fee_numerator as u128).div_ceil(DBC_FEE_DENOMINATOR as u128) as u64;
+    let free = CurveConfig::from_account_data(&config_data(0, true)).unwrap();
+    let quote_in = 5_000_000_000;
+
+    let in_quote = CurveConfig::from_account_data(&config_data(fee_numerator, true)).unwrap();
+    let (base_out, _) = in_quote.quote_buy(in_quote.sqrt_start_price, quote_in);
+    assert_eq!(base_out, free.quote_buy(free.sqrt_start_price, quote_in - fee(quote_in)).0);
+
+    let in_output = CurveConfig::from_account_data(&config_data(fee_numerator, false)).unwrap();
+    let (base_out, _) = in_output.quote_buy(in_output.sqrt_start_price, quote_in);
+    let free_out = free.quote_buy(free.sqrt_start_price, quote_in).0;
+    assert_eq!(base_out, free_out - fee(free_out));
+
+    // Sells pay the fee in SOL in either mode
+    let (after_buy, base_in) = (free.quote_buy(free.sqrt_start_price, quote_in).1, 1_000_000_000_000);
+    let free_quote = free.quote_sell(after_buy, base_in).0;
+    assert_eq!(in_output.quote_sell(after_buy, base_in).0, free_quote - fee(free_quote));
+    assert_eq!(in_quote.quote_sell(after_buy, base_in).0, free_quote - fee(free_quote));
+}
+
+// The dex quotes against the cached config and moves the reported reserves along the curve
+#[tokio::test]
+async fn dex_quotes_along_cached_config() {
+    let endpoint = Arc::new(TradingEndpoint::new(
+        mock_rpc(vec![]),
+        vec![Arc::new(StubSWQoS::default()) as Arc<dyn SWQoSTrait>],
+    ));
+    let dex = MeteoraDBC::new(endpoint);
+    let config = Pubkey::new_unique();
+    let curve = Arc::new(CurveConfig::from_account_data(&config_data(0, true)).unwrap());
+    dex.configs.write().unwrap().insert(config, curve.clone());
+
+    let token_reserves = 800_000_000_000_000u64;
+    let mut pool_info = PoolInfo {
+        pool: Pubkey::new_unique(),
+        creator: None,
+        creator_vault: None,
+        config: Some(config),
+        extra_address: Some(config),
+        token_reserves,
+        sol_reserves: (SQRT_START * SQRT_START * token_reserves as f64) as u64,
+        complete: false,
+    };
+    let mint_info = MintInfo::new(spl_token::ID);
+
+    let quote = dex.quote_buy(&pool_info, &mint_info, 15_000_000_000);
+    assert_close(quote.token_amount, reference_buy(15_000_000_000.0));
+
+    dex.advance_pool(&mut pool_info, &quote, true);
+    let (_, next) = curve.quote_buy(curve.sqrt_start_price, 15_000_000_000);
+    let next_price = (next as f64 / Q64).powi(2);
+    assert_eq!(pool_info.token_reserves, token_reserves - quote.token_amount);
+    assert!((pool_info.price() - next_price).abs() / next_price < 1e-6);
+
+    // Without a cached config the reserves are read as constant product
+    pool_info.config = Some(Pubkey::new_unique());
+    let quote = dex.quote_buy(&pool_info, &mint_info, 1_000_000_000);
+    let expected = pool_info.token_reserves as u128 * 1_000_000_000 / (pool_info.sol_reserves as u128 + 1_000_000_000);
+    assert!(quote.token_amount.abs_diff(expected as u64) <= 1);
+}
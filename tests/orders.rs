@@ -0,0 +1,237 @@
+mod common;
+
+use common::{account_result, mock_rpc, program_account, StubSWQoS};
+use serde_json::Value;
+use solana_client::{
+    rpc_request::RpcRequest,
+    rpc_response::{Response, RpcResponseContext},
+};
+use solana_sdk::{instruction::InstructionError, pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::TransactionError};
+use solana_trading_sdk::{
+    common::{mint_info::MintInfo, trading_endpoint::TradingEndpoint},
+    dex::{
+        dex_traits::DexTrait,
+        pumpfun::Pumpfun,
+        pumpfun_types::{self, BondingCurveAccount},
+        types::DexType,
+    },
+    strategy::orders::{InFlightSlice, Order, OrderEngine, OrderEvent, OrderKind, OrderStatus},
+    swqos::SWQoSTrait,
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+
+const TIP: u64 = 100_000;
+
+struct Fixture {
+    engine: Arc<OrderEngine>,
+    stub: Arc<StubSWQoS>,
+    mint: Pubkey,
+    path: PathBuf,
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn fixture(mocks: Vec<(RpcRequest, Value)>) -> Fixture {
+    let mint = Pubkey::new_unique();
+    let curve = BondingCurveAccount {
+        discriminator: 0,
+        virtual_token_reserves: 1_000_000_000_000_000,
+        virtual_sol_reserves: 40_000_000_000,
+        real_token_reserves: 700_000_000_000_000,
+        real_sol_reserves: 10_000_000_000,
+        token_total_supply: 1_000_000_000_000_000,
+        complete: false,
+        creator: Pubkey::new_unique(),
+    };
+    let bonding_curve = Pumpfun::get_bonding_curve_pda(&mint).unwrap();
+    let account = program_account(&pumpfun_types::PUBKEY_PUMPFUN, bincode::serialize(&curve).unwrap());
+    let mut mocks = mocks;
+    mocks.push((RpcRequest::GetAccountInfo, account_result(&bonding_curve, &account)));
+    let rpc = mock_rpc(mocks);
+
+    let stub = Arc::new(StubSWQoS::default());
+    let endpoint = Arc::new(TradingEndpoint::new(rpc, vec![stub.clone() as Arc<dyn SWQoSTrait>]));
+    endpoint.set_mint_info(&mint, MintInfo::new(spl_token::ID));
+    let dex = Pumpfun::new(endpoint);
+    dex.global_account
+        .set(Arc::new(pumpfun_types::GlobalAccount {
+            discriminator: 0,
+            initialized: true,
+            authority: Pubkey::new_unique(),
+            fee_recipient: pumpfun_types::PUBKEY_FEE_RECIPIENT,
+            initial_virtual_token_reserves: pumpfun_types::INITIAL_VIRTUAL_TOKEN_RESERVES,
+            initial_virtual_sol_reserves: pumpfun_types::INITIAL_VIRTUAL_SOL_RESERVES,
+            initial_real_token_reserves: 793_100_000_000_000,
+            token_total_supply: 1_000_000_000_000_000,
+            fee_basis_points: 95,
+        }))
+        .unwrap();
+
+    let path = std::env::temp_dir().join(format!("orders-{}.json", mint));
+    let dexs = HashMap::from([(DexType::Pumpfun, Arc::new(dex) as Arc<dyn DexTrait>)]);
+    let mut engine = OrderEngine::new(dexs, &path).unwrap();
+    engine.max_attempts = 2;
+    engine.retry_backoff = Duration::ZERO;
+
+    Fixture {
+        engine: Arc::new(engine),
+        stub,
+        mint,
+        path,
+    }
+}
+
+// The mocked bonding curve answers one pool fetch, so the orders execute at most once
+fn place_dca_buy(fixture: &Fixture, payer: &Pubkey) -> u64 {
+    let kind = OrderKind::DcaBuy {
+        sol_amount: 100_000_000,
+        slices: 1,
+        interval_secs: 0,
+    };
+    fixture
+        .engine
+        .place(DexType::Pumpfun, payer, &fixture.mint, kind, 100, None, Some(TIP))
+        .unwrap()
+}
+
+fn get_order(fixture: &Fixture, id: u64) -> Order {
+    fixture.engine.get_orders().into_iter().find(|order| order.id == id).unwrap()
+}
+
+#[tokio::test]
+async fn failed_execution_is_retried() {
+    let fixture = fixture(vec![]);
+    let payer = Arc::new(Keypair::new());
+    let id = place_dca_buy(&fixture, &payer.pubkey());
+
+    // No signer registered yet
+    let events = fixture.engine.tick().await.unwrap();
+    assert!(matches!(events[..], [OrderEvent::Retrying { id: event_id, attempts: 1, .. }] if event_id == id));
+    let order = get_order(&fixture, id);
+    assert_eq!((order.status, order.attempts), (OrderStatus::Open, 1));
+    assert!(order.last_error.is_some());
+
+    fixture.engine.add_signer(payer);
+    let events = fixture.engine.tick().await.unwrap();
+    assert!(matches!(events[..], [OrderEvent::Sent { id: event_id, .. }] if event_id == id));
+    let order = get_order(&fixture, id);
+    assert_eq!((order.status, order.slices_done), (OrderStatus::Open, 0));
+    assert_eq!(order.in_flight.unwrap().signatures.len(), 1);
+
+    // Counted once the signature lands
+    let events = fixture.engine.tick().await.unwrap();
+    assert!(matches!(events[..], [OrderEvent::Executed { id: event_id, .. }] if event_id == id));
+    let order = get_order(&fixture, id);
+    assert_eq!((order.status, order.attempts, order.slices_done), (OrderStatus::Filled, 0, 1));
+    assert_eq!((order.last_error, order.in_flight), (None, None));
+
+    assert_eq!(fixture.stub.wait_sent(1).await.len(), 1);
+}
+
+#[tokio::test]
+async fn order_fails_after_max_attempts() {
+    let fixture = fixture(vec![]);
+    let id = place_dca_buy(&fixture, &Pubkey::new_unique());
+
+    fixture.engine.tick().await.unwrap();
+    let events = fixture.engine.tick().await.unwrap();
+    assert!(matches!(events[..], [OrderEvent::Failed { id: event_id, .. }] if event_id == id));
+    assert!(matches!(get_order(&fixture, id).status, OrderStatus::Failed(_)));
+
+    // Persisted with its attempts
+    let reloaded = OrderEngine::new(HashMap::new(), &fixture.path).unwrap();
+    assert_eq!(reloaded.get_orders()[0].attempts, 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn concurrent_ticks_execute_once() {
+    let fixture = fixture(vec![]);
+    let payer = Arc::new(Keypair::new());
+    fixture.engine.add_signer(payer.clone());
+    let id = fixture
+        .engine
+        .place(
+            DexType::Pumpfun,
+            &payer.pubkey(),
+            &fixture.mint,
+            OrderKind::DcaBuy {
+                sol_amount: 100_000_000,
+                slices: 2,
+                interval_secs: 3_600,
+            },
+            100,
+            None,
+            Some(TIP),
+        )
+        .unwrap();
+
+    let (first, second) = tokio::join!(fixture.engine.tick(), fixture.engine.tick());
+    let sent = first
+        .unwrap()
+        .into_iter()
+        .chain(second.unwrap())
+        .filter(|event| matches!(event, OrderEvent::Sent { .. }))
+        .count();
+    assert_eq!(sent, 1);
+    assert_eq!(fixture.stub.wait_sent(1).await.len(), 1);
+    assert_eq!(get_order(&fixture, id).slices_done, 1);
+}
+
+#[tokio::test]
+async fn slice_failing_on_chain_counts_an_attempt() {
+    let failed = TransactionError::InstructionError(0, InstructionError::Custom(6003));
+    let statuses = Response {
+        context: RpcResponseContext::new(1),
+        value: vec![Some(TransactionStatus {
+            slot: 1,
+            confirmations: None,
+            status: Err(failed.clone()),
+            err: Some(failed.clone()),
+            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+        })],
+    };
+    let mut fixture = fixture(vec![(RpcRequest::GetSignatureStatuses, serde_json::to_value(statuses).unwrap())]);
+    // Not retried within the same tick
+    Arc::get_mut(&mut fixture.engine).unwrap().retry_backoff = Duration::from_secs(60);
+    let payer = Arc::new(Keypair::new());
+    fixture.engine.add_signer(payer.clone());
+    let id = place_dca_buy(&fixture, &payer.pubkey());
+
+    fixture.engine.tick().await.unwrap();
+    let events = fixture.engine.tick().await.unwrap();
+    assert!(matches!(&events[..], [OrderEvent::Retrying { id: event_id, attempts: 1, error }] if *event_id == id && *error == failed.to_string()));
+    let order = get_order(&fixture, id);
+    assert_eq!((order.status, order.slices_done, order.in_flight), (OrderStatus::Open, 0, None));
+    assert!(order.signatures.is_empty());
+}
+
+#[tokio::test]
+async fn slice_interrupted_while_sending_is_not_sent_again() {
+    let fixture = fixture(vec![]);
+    let payer = Arc::new(Keypair::new());
+    let id = place_dca_buy(&fixture, &payer.pubkey());
+
+    // As saved right before sending, by an engine that stopped before recording the signatures
+    let mut order = get_order(&fixture, id);
+    order.in_flight = Some(InFlightSlice {
+        slice: 0,
+        signatures: vec![],
+        sent_at: order.created_at,
+    });
+    let book = serde_json::json!({ "next_id": id + 1, "orders": [order] });
+    std::fs::write(&fixture.path, book.to_string()).unwrap();
+
+    let dexs = HashMap::from([(DexType::Pumpfun, fixture.engine.dexs[&DexType::Pumpfun].clone())]);
+    let engine = OrderEngine::new(dexs, &fixture.path).unwrap();
+    engine.add_signer(payer);
+    let events = engine.tick().await.unwrap();
+    assert!(matches!(events[..], [OrderEvent::Failed { id: event_id, .. }] if event_id == id));
+    assert!(matches!(engine.get_orders()[0].status, OrderStatus::Failed(_)));
+    assert!(engine.tick().await.unwrap().is_empty());
+    assert!(fixture.stub.sent.lock().unwrap().is_empty());
+}
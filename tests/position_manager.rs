@@ -0,0 +1,216 @@
+mod common;
+
+use common::{account_result, mock_rpc, program_account, StubSWQoS};
+use solana_client::{
+    rpc_request::RpcRequest,
+    rpc_response::{Response, RpcResponseContext},
+};
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    transaction::TransactionError,
+};
+use solana_trading_sdk::{
+    common::{mint_info::MintInfo, signer::TxSigner, trading_endpoint::TradingEndpoint},
+    dex::{
+        dex_traits::DexTrait,
+        pumpfun::Pumpfun,
+        pumpfun_types::{self, BondingCurveAccount},
+        types::TokenAmountType,
+    },
+    strategy::position_manager::{ExitConfig, ExitRule, ExitStatus, ExitTrigger, PendingExit, Position, PositionManager},
+    swqos::SWQoSTrait,
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::{sync::Arc, time::Instant};
+
+const TOKEN_AMOUNT: u64 = 1_000_000_000_000;
+
+struct Fixture {
+    manager: PositionManager,
+    dex: Arc<Pumpfun>,
+    stub: Arc<StubSWQoS>,
+    payer: Arc<dyn TxSigner>,
+    mint: Pubkey,
+}
+
+fn fixture(mocks: Vec<(RpcRequest, serde_json::Value)>) -> Fixture {
+    let mint = Pubkey::new_unique();
+    let curve = BondingCurveAccount {
+        discriminator: 0,
+        virtual_token_reserves: 1_000_000_000_000_000,
+        virtual_sol_reserves: 40_000_000_000,
+        real_token_reserves: 700_000_000_000_000,
+        real_sol_reserves: 10_000_000_000,
+        token_total_supply: 1_000_000_000_000_000,
+        complete: false,
+        creator: Pubkey::new_unique(),
+    };
+    let bonding_curve = Pumpfun::get_bonding_curve_pda(&mint).unwrap();
+    let account = program_account(&pumpfun_types::PUBKEY_PUMPFUN, bincode::serialize(&curve).unwrap());
+    let mut mocks = mocks;
+    mocks.push((RpcRequest::GetAccountInfo, account_result(&bonding_curve, &account)));
+
+    let stub = Arc::new(StubSWQoS::default());
+    let endpoint = Arc::new(TradingEndpoint::new(mock_rpc(mocks), vec![stub.clone() as Arc<dyn SWQoSTrait>]));
+    endpoint.set_token_program(&mint, &spl_token::ID);
+    let dex = Arc::new(Pumpfun::new(endpoint.clone()));
+    dex.global_account
+        .set(Arc::new(pumpfun_types::GlobalAccount {
+            discriminator: 0,
+            initialized: true,
+            authority: Pubkey::new_unique(),
+            fee_recipient: pumpfun_types::PUBKEY_FEE_RECIPIENT,
+            initial_virtual_token_reserves: pumpfun_types::INITIAL_VIRTUAL_TOKEN_RESERVES,
+            initial_virtual_sol_reserves: pumpfun_types::INITIAL_VIRTUAL_SOL_RESERVES,
+            initial_real_token_reserves: 793_100_000_000_000,
+            token_total_supply: 1_000_000_000_000_000,
+            fee_basis_points: 95,
+        }))
+        .unwrap();
+
+    Fixture {
+        manager: PositionManager::new(endpoint),
+        dex,
+        stub,
+        payer: Arc::new(Keypair::new()),
+        mint,
+    }
+}
+
+// A position far in profit, half of it taken at once, and a stop loss that never fires
+fn open_position(fixture: &Fixture) {
+    let position = Position {
+        dex: fixture.dex.clone(),
+        payer: fixture.payer.clone(),
+        mint: fixture.mint,
+        mint_info: MintInfo::new(spl_token::ID),
+        token_amount: TOKEN_AMOUNT,
+        cost_basis: 1,
+        peak_bps: 10_000,
+        rules: vec![
+            ExitRule::new(ExitTrigger::TakeProfit(0), TokenAmountType::Percent(50)),
+            ExitRule::new(ExitTrigger::StopLoss(9_000), TokenAmountType::Percent(100)),
+        ],
+        pending: vec![],
+        exit: ExitConfig {
+            slippage_basis_points: 100,
+            fee: None,
+            tip: Some(100_000),
+        },
+    };
+    fixture
+        .manager
+        .positions
+        .lock()
+        .unwrap()
+        .insert((fixture.payer.pubkey(), fixture.mint), position);
+}
+
+fn position_state(fixture: &Fixture) -> (u64, usize, usize) {
+    let positions = fixture.manager.positions.lock().unwrap();
+    let position = &positions[&(fixture.payer.pubkey(), fixture.mint)];
+    (position.token_amount, position.rules.len(), position.pending.len())
+}
+
+#[tokio::test]
+async fn exit_reduces_position_on_confirmation() {
+    let fixture = fixture(vec![]);
+    open_position(&fixture);
+    let pool_info = fixture.dex.get_pool(&fixture.mint).await.unwrap();
+
+    let events = fixture.manager.on_pool_update(&fixture.mint, &pool_info).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].status, ExitStatus::Sent);
+    assert_eq!(events[0].token_amount, TOKEN_AMOUNT / 2);
+    assert_eq!(fixture.stub.wait_sent(1).await.len(), 1);
+    // Broadcast only, the tokens are still held
+    assert_eq!(position_state(&fixture), (TOKEN_AMOUNT, 1, 1));
+
+    let events = fixture.manager.on_pool_update(&fixture.mint, &pool_info).await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].status, ExitStatus::Confirmed(events[0].signatures[0]));
+    assert_eq!(position_state(&fixture), (TOKEN_AMOUNT / 2, 1, 0));
+}
+
+#[tokio::test]
+async fn failed_exit_rearms_its_rule() {
+    let failed = TransactionError::InstructionError(0, InstructionError::Custom(6003));
+    let statuses = Response {
+        context: RpcResponseContext::new(1),
+        value: vec![Some(TransactionStatus {
+            slot: 1,
+            confirmations: None,
+            status: Err(failed.clone()),
+            err: Some(failed.clone()),
+            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+        })],
+    };
+    let fixture = fixture(vec![(RpcRequest::GetSignatureStatuses, serde_json::to_value(statuses).unwrap())]);
+    open_position(&fixture);
+    let pool_info = fixture.dex.get_pool(&fixture.mint).await.unwrap();
+
+    fixture.manager.on_pool_update(&fixture.mint, &pool_info).await.unwrap();
+    let events = fixture.manager.on_pool_update(&fixture.mint, &pool_info).await.unwrap();
+
+    // The failed sell is reported and its rule fires again on the same update
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].status, ExitStatus::Failed(failed.to_string()));
+    assert_eq!(events[1].status, ExitStatus::Sent);
+    assert_eq!(position_state(&fixture), (TOKEN_AMOUNT, 1, 1));
+}
+
+#[tokio::test]
+async fn overlapping_exits_landing_together_close_the_position() {
+    let fixture = fixture(vec![]);
+    open_position(&fixture);
+    let pool_info = fixture.dex.get_pool(&fixture.mint).await.unwrap();
+
+    // Two sells in flight for more than the position holds, the first one selling all of it
+    {
+        let mut positions = fixture.manager.positions.lock().unwrap();
+        let position = positions.get_mut(&(fixture.payer.pubkey(), fixture.mint)).unwrap();
+        let rules = std::mem::take(&mut position.rules);
+        position.pending = [(1, rules[1], TOKEN_AMOUNT), (2, rules[0], TOKEN_AMOUNT / 2)]
+            .into_iter()
+            .map(|(id, rule, token_amount)| PendingExit {
+                id,
+                rule,
+                value_bps: 10_000,
+                token_amount,
+                min_sol_amount: 0,
+                signatures: vec![Signature::new_unique()],
+                sent: Instant::now(),
+            })
+            .collect();
+    }
+
+    let events = fixture.manager.on_pool_update(&fixture.mint, &pool_info).await.unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|event| matches!(event.status, ExitStatus::Confirmed(_))));
+    assert!(fixture.manager.positions.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn position_without_cost_basis_is_rejected() {
+    let fixture = fixture(vec![]);
+    let rules = vec![ExitRule::new(ExitTrigger::StopLoss(5_000), TokenAmountType::Percent(100))];
+    let exit = ExitConfig {
+        slippage_basis_points: 100,
+        fee: None,
+        tip: Some(100_000),
+    };
+    let result = fixture
+        .manager
+        .open(fixture.dex.clone(), fixture.payer.clone(), &fixture.mint, TOKEN_AMOUNT, 0, rules, exit)
+        .await;
+    assert!(result.is_err());
+
+    let pool_info = fixture.dex.get_pool(&fixture.mint).await.unwrap();
+    let events = fixture.manager.on_pool_update(&fixture.mint, &pool_info).await.unwrap();
+    assert!(events.is_empty());
+    assert!(fixture.manager.positions.lock().unwrap().is_empty());
+    assert!(fixture.stub.sent.lock().unwrap().is_empty());
+}
@@ -0,0 +1,352 @@
+mod common;
+
+use common::{assert_instruction, mock_rpc, multiple_accounts_result, program_account, program_accounts_result, token_account};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+};
+use solana_trading_sdk::{
+    common::{accounts::PUBKEY_WSOL, mint_info::MintInfo, trading_endpoint::TradingEndpoint},
+    dex::{
+        amm_calc::{amm_amount_after_fee, amm_buy_get_token_out, amm_sell_get_sol_out},
+        dex_traits::DexTrait,
+        raydium_amm_v4::RaydiumAmmV4,
+        raydium_amm_v4_types::{AMM_ACCOUNT_SIZE, AMM_STATUS_SWAP_ONLY, AMM_STATUS_WAITING_TRADE, PUBKEY_RAYDIUM_AMM_V4, PUBKEY_RAYDIUM_AMM_V4_AUTHORITY},
+        raydium_cpmm::RaydiumCpmm,
+        raydium_cpmm_types::{
+            CREATOR_FEE_ON_TOKEN_0, FEE_RATE_DENOMINATOR, POOL_ACCOUNT_SIZE, POOL_STATUS_SWAP_DISABLED, PUBKEY_RAYDIUM_CPMM, PUBKEY_RAYDIUM_CPMM_AUTHORITY,
+        },
+        types::{PoolInfo, SwapInfo},
+    },
+};
+use spl_associated_token_account::{get_associated_token_address, get_associated_token_address_with_program_id};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const TOKEN_RESERVE: u64 = 500_000_000_000_000;
+const SOL_RESERVE: u64 = 50_000_000_000;
+const SOL_AMOUNT: u64 = 1_000_000_000;
+const TOKEN_AMOUNT: u64 = 10_000_000_000_000;
+// Accrued in the vaults on top of the reserves
+const PENDING_FEES: u64 = 1_000_000;
+
+const CPMM_CONFIG_LEN: usize = 236;
+const TRADE_FEE_RATE: u64 = 2_500;
+const CREATOR_FEE_RATE: u64 = 1_000;
+const SWAP_FEE: (u64, u64) = (25, 10_000);
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn endpoint(mint: &Pubkey, mocks: Vec<(RpcRequest, serde_json::Value)>) -> Arc<TradingEndpoint> {
+    let endpoint = Arc::new(TradingEndpoint::new(mock_rpc(mocks), vec![]));
+    endpoint.set_mint_info(mint, MintInfo::new(spl_token::ID));
+    endpoint
+}
+
+struct CpmmPool {
+    address: Pubkey,
+    config: Pubkey,
+    token_0_vault: Pubkey,
+    token_1_vault: Pubkey,
+    observation: Pubkey,
+    status: u8,
+    open_time: u64,
+    sol_reserve: u64,
+}
+
+impl CpmmPool {
+    fn new(sol_reserve: u64) -> Self {
+        Self {
+            address: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            token_0_vault: Pubkey::new_unique(),
+            token_1_vault: Pubkey::new_unique(),
+            observation: Pubkey::new_unique(),
+            status: 0,
+            open_time: 0,
+            sol_reserve,
+        }
+    }
+
+    // Pool state of `mint` (token 0) against WSOL (token 1), charging the creator fee in token 0 only
+    fn data(&self, mint: &Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; POOL_ACCOUNT_SIZE as usize];
+        data[8..40].copy_from_slice(self.config.as_ref());
+        data[40..72].copy_from_slice(Pubkey::new_unique().as_ref());
+        data[72..104].copy_from_slice(self.token_0_vault.as_ref());
+        data[104..136].copy_from_slice(self.token_1_vault.as_ref());
+        data[168..200].copy_from_slice(mint.as_ref());
+        data[200..232].copy_from_slice(PUBKEY_WSOL.as_ref());
+        data[296..328].copy_from_slice(self.observation.as_ref());
+        data[329] = self.status;
+        // Protocol fees in token 0, fund fees in token 1
+        data[341..349].copy_from_slice(&PENDING_FEES.to_le_bytes());
+        data[365..373].copy_from_slice(&PENDING_FEES.to_le_bytes());
+        data[373..381].copy_from_slice(&self.open_time.to_le_bytes());
+        data[389] = CREATOR_FEE_ON_TOKEN_0;
+        data[390] = 1;
+        data
+    }
+
+    // Vaults and config, in the order the pool lookup fetches them
+    fn keys(&self, mint: &Pubkey) -> Vec<(Pubkey, Account)> {
+        let mut config = vec![0u8; CPMM_CONFIG_LEN];
+        config[12..20].copy_from_slice(&TRADE_FEE_RATE.to_le_bytes());
+        config[108..116].copy_from_slice(&CREATOR_FEE_RATE.to_le_bytes());
+        vec![
+            (
+                self.token_0_vault,
+                token_account(mint, &PUBKEY_RAYDIUM_CPMM_AUTHORITY, TOKEN_RESERVE + PENDING_FEES),
+            ),
+            (
+                self.token_1_vault,
+                token_account(&PUBKEY_WSOL, &PUBKEY_RAYDIUM_CPMM_AUTHORITY, self.sol_reserve + PENDING_FEES),
+            ),
+            (self.config, program_account(&PUBKEY_RAYDIUM_CPMM, config)),
+        ]
+    }
+}
+
+fn cpmm(mint: &Pubkey, pools: &[CpmmPool]) -> RaydiumCpmm {
+    let accounts = pools
+        .iter()
+        .map(|pool| (pool.address, program_account(&PUBKEY_RAYDIUM_CPMM, pool.data(mint))))
+        .collect::<Vec<_>>();
+    let keys = pools.iter().flat_map(|pool| pool.keys(mint)).collect::<Vec<_>>();
+    RaydiumCpmm::new(endpoint(
+        mint,
+        vec![
+            (RpcRequest::GetProgramAccounts, program_accounts_result(&accounts)),
+            (RpcRequest::GetMultipleAccounts, multiple_accounts_result(&keys)),
+        ],
+    ))
+}
+
+// Reserves exclude the accrued fees. Buys pay the trade fee from the WSOL in and the creator fee from the tokens out,
+// sells pay both from the tokens in.
+#[tokio::test]
+async fn cpmm_quotes_charge_the_creator_fee_on_its_side() {
+    let mint = Pubkey::new_unique();
+    assert!(mint < PUBKEY_WSOL);
+    let dex = cpmm(&mint, &[CpmmPool::new(SOL_RESERVE)]);
+    let pool_info = dex.get_pool(&mint).await.unwrap();
+    assert_eq!((pool_info.token_reserves, pool_info.sol_reserves), (TOKEN_RESERVE, SOL_RESERVE));
+
+    let mint_info = MintInfo::new(spl_token::ID);
+    let sol_in = amm_amount_after_fee(SOL_AMOUNT, TRADE_FEE_RATE, FEE_RATE_DENOMINATOR);
+    let token_out = amm_buy_get_token_out(SOL_RESERVE, TOKEN_RESERVE, sol_in);
+    let buy = dex.quote_buy(&pool_info, &mint_info, SOL_AMOUNT);
+    assert_eq!(buy.token_amount, amm_amount_after_fee(token_out, CREATOR_FEE_RATE, FEE_RATE_DENOMINATOR));
+
+    let token_in = amm_amount_after_fee(TOKEN_AMOUNT, TRADE_FEE_RATE + CREATOR_FEE_RATE, FEE_RATE_DENOMINATOR);
+    let sell = dex.quote_sell(&pool_info, &mint_info, TOKEN_AMOUNT);
+    assert_eq!(sell.sol_amount, amm_sell_get_sol_out(SOL_RESERVE, TOKEN_RESERVE, token_in));
+}
+
+#[tokio::test]
+async fn cpmm_swap_instructions() {
+    let mint = Pubkey::new_unique();
+    let pool = CpmmPool::new(SOL_RESERVE);
+    let dex = cpmm(&mint, std::slice::from_ref(&pool));
+    let pool_info = dex.get_pool(&mint).await.unwrap();
+    let payer = Keypair::new();
+    let swap = SwapInfo {
+        token_amount: TOKEN_AMOUNT,
+        sol_amount: SOL_AMOUNT,
+    };
+
+    let mint_ata = get_associated_token_address_with_program_id(&payer.pubkey(), &mint, &spl_token::ID);
+    let wsol_ata = get_associated_token_address_with_program_id(&payer.pubkey(), &PUBKEY_WSOL, &spl_token::ID);
+    let instruction = |discriminator: u64, input: (Pubkey, Pubkey, Pubkey), output: (Pubkey, Pubkey, Pubkey), amounts: [u64; 2]| Instruction {
+        program_id: PUBKEY_RAYDIUM_CPMM,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(PUBKEY_RAYDIUM_CPMM_AUTHORITY, false),
+            AccountMeta::new_readonly(pool.config, false),
+            AccountMeta::new(pool.address, false),
+            AccountMeta::new(input.0, false),
+            AccountMeta::new(output.0, false),
+            AccountMeta::new(input.1, false),
+            AccountMeta::new(output.1, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(input.2, false),
+            AccountMeta::new_readonly(output.2, false),
+            AccountMeta::new(pool.observation, false),
+        ],
+        data: [discriminator.to_le_bytes(), amounts[0].to_le_bytes(), amounts[1].to_le_bytes()].concat(),
+    };
+    let mint_side = (mint_ata, pool.token_0_vault, mint);
+    let sol_side = (wsol_ata, pool.token_1_vault, PUBKEY_WSOL);
+
+    // Buys swap base output: at most `sol_amount` in for exactly `token_amount` out
+    let buy = dex.build_buy_instruction(&payer, &mint, pool_info.extra_address.as_ref(), swap).unwrap();
+    assert_instruction(&buy, &instruction(12516711329758894391, sol_side, mint_side, [SOL_AMOUNT, TOKEN_AMOUNT]));
+
+    // Sells swap base input: exactly `token_amount` in for at least `sol_amount` out
+    let sell = dex.build_sell_instruction(&payer, &mint, pool_info.extra_address.as_ref(), swap).unwrap();
+    assert_instruction(&sell, &instruction(16011174931058048655, mint_side, sol_side, [TOKEN_AMOUNT, SOL_AMOUNT]));
+}
+
+#[tokio::test]
+async fn cpmm_skips_pools_that_cannot_swap() {
+    let mint = Pubkey::new_unique();
+    let mut disabled = CpmmPool::new(SOL_RESERVE * 4);
+    disabled.status = POOL_STATUS_SWAP_DISABLED;
+    let mut not_open = CpmmPool::new(SOL_RESERVE * 2);
+    not_open.open_time = now() + 3_600;
+    let open = CpmmPool::new(SOL_RESERVE);
+    let address = open.address;
+
+    let dex = cpmm(&mint, &[disabled, not_open, open]);
+    assert_eq!(dex.get_pool(&mint).await.unwrap().pool, address);
+
+    let mut disabled = CpmmPool::new(SOL_RESERVE);
+    disabled.status = POOL_STATUS_SWAP_DISABLED;
+    assert!(cpmm(&mint, &[disabled]).get_pool(&mint).await.is_err());
+}
+
+struct AmmPool {
+    address: Pubkey,
+    coin_vault: Pubkey,
+    pc_vault: Pubkey,
+    status: u64,
+    open_time: u64,
+    sol_reserve: u64,
+}
+
+impl AmmPool {
+    fn new(status: u64, open_time: u64, sol_reserve: u64) -> Self {
+        Self {
+            address: Pubkey::new_unique(),
+            coin_vault: Pubkey::new_unique(),
+            pc_vault: Pubkey::new_unique(),
+            status,
+            open_time,
+            sol_reserve,
+        }
+    }
+
+    // AMM of `mint` (coin) against WSOL (pc), with pnl not yet taken on both sides
+    fn data(&self, mint: &Pubkey) -> Vec<u8> {
+        let mut data = vec![0u8; AMM_ACCOUNT_SIZE as usize];
+        data[0..8].copy_from_slice(&self.status.to_le_bytes());
+        data[176..184].copy_from_slice(&SWAP_FEE.0.to_le_bytes());
+        data[184..192].copy_from_slice(&SWAP_FEE.1.to_le_bytes());
+        data[192..200].copy_from_slice(&PENDING_FEES.to_le_bytes());
+        data[200..208].copy_from_slice(&PENDING_FEES.to_le_bytes());
+        data[224..232].copy_from_slice(&self.open_time.to_le_bytes());
+        data[336..368].copy_from_slice(self.coin_vault.as_ref());
+        data[368..400].copy_from_slice(self.pc_vault.as_ref());
+        data[400..432].copy_from_slice(mint.as_ref());
+        data[432..464].copy_from_slice(PUBKEY_WSOL.as_ref());
+        data
+    }
+}
+
+// Pools with `mint` as the coin, none with it as the pc
+fn amm_v4(mint: &Pubkey, pools: &[AmmPool]) -> RaydiumAmmV4 {
+    let accounts = pools
+        .iter()
+        .map(|pool| (pool.address, program_account(&PUBKEY_RAYDIUM_AMM_V4, pool.data(mint))))
+        .collect::<Vec<_>>();
+    let vaults = pools
+        .iter()
+        .flat_map(|pool| {
+            [
+                (
+                    pool.coin_vault,
+                    token_account(mint, &PUBKEY_RAYDIUM_AMM_V4_AUTHORITY, TOKEN_RESERVE + PENDING_FEES),
+                ),
+                (
+                    pool.pc_vault,
+                    token_account(&PUBKEY_WSOL, &PUBKEY_RAYDIUM_AMM_V4_AUTHORITY, pool.sol_reserve + PENDING_FEES),
+                ),
+            ]
+        })
+        .collect::<Vec<_>>();
+    RaydiumAmmV4::new(endpoint(
+        mint,
+        vec![
+            (RpcRequest::GetProgramAccounts, program_accounts_result(&accounts)),
+            (RpcRequest::GetProgramAccounts, program_accounts_result(&[])),
+            (RpcRequest::GetMultipleAccounts, multiple_accounts_result(&vaults)),
+        ],
+    ))
+}
+
+#[tokio::test]
+async fn amm_v4_quotes_take_the_swap_fee_from_the_input() {
+    let mint = Pubkey::new_unique();
+    let dex = amm_v4(&mint, &[AmmPool::new(AMM_STATUS_SWAP_ONLY, 0, SOL_RESERVE)]);
+    let pool_info = dex.get_pool(&mint).await.unwrap();
+    assert_eq!((pool_info.token_reserves, pool_info.sol_reserves), (TOKEN_RESERVE, SOL_RESERVE));
+
+    let mint_info = MintInfo::new(spl_token::ID);
+    let sol_in = amm_amount_after_fee(SOL_AMOUNT, SWAP_FEE.0, SWAP_FEE.1);
+    let buy = dex.quote_buy(&pool_info, &mint_info, SOL_AMOUNT);
+    assert_eq!(buy.token_amount, amm_buy_get_token_out(SOL_RESERVE, TOKEN_RESERVE, sol_in));
+
+    let token_in = amm_amount_after_fee(TOKEN_AMOUNT, SWAP_FEE.0, SWAP_FEE.1);
+    let sell = dex.quote_sell(&pool_info, &mint_info, TOKEN_AMOUNT);
+    assert_eq!(sell.sol_amount, amm_sell_get_sol_out(SOL_RESERVE, TOKEN_RESERVE, token_in));
+}
+
+#[tokio::test]
+async fn amm_v4_swap_instructions() {
+    let mint = Pubkey::new_unique();
+    let pool = AmmPool::new(AMM_STATUS_SWAP_ONLY, 0, SOL_RESERVE);
+    let dex = amm_v4(&mint, std::slice::from_ref(&pool));
+    let pool_info: PoolInfo = dex.get_pool(&mint).await.unwrap();
+    let payer = Keypair::new();
+    let swap = SwapInfo {
+        token_amount: TOKEN_AMOUNT,
+        sol_amount: SOL_AMOUNT,
+    };
+
+    let mint_ata = get_associated_token_address(&payer.pubkey(), &mint);
+    let wsol_ata = get_associated_token_address(&payer.pubkey(), &PUBKEY_WSOL);
+    let instruction = |tag: u8, source: Pubkey, destination: Pubkey, amounts: [u64; 2]| Instruction {
+        program_id: PUBKEY_RAYDIUM_AMM_V4,
+        accounts: vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new(pool.address, false),
+            AccountMeta::new_readonly(PUBKEY_RAYDIUM_AMM_V4_AUTHORITY, false),
+            AccountMeta::new(pool.coin_vault, false),
+            AccountMeta::new(pool.pc_vault, false),
+            AccountMeta::new(source, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(payer.pubkey(), true),
+        ],
+        data: [vec![tag], amounts[0].to_le_bytes().to_vec(), amounts[1].to_le_bytes().to_vec()].concat(),
+    };
+
+    // Buys swap base out (17), sells swap base in (16)
+    let buy = dex.build_buy_instruction(&payer, &mint, pool_info.extra_address.as_ref(), swap).unwrap();
+    assert_instruction(&buy, &instruction(17, wsol_ata, mint_ata, [SOL_AMOUNT, TOKEN_AMOUNT]));
+    let sell = dex.build_sell_instruction(&payer, &mint, pool_info.extra_address.as_ref(), swap).unwrap();
+    assert_instruction(&sell, &instruction(16, mint_ata, wsol_ata, [TOKEN_AMOUNT, SOL_AMOUNT]));
+}
+
+#[tokio::test]
+async fn amm_v4_skips_pools_that_cannot_swap() {
+    let mint = Pubkey::new_unique();
+    let disabled = AmmPool::new(2, 0, SOL_RESERVE * 4);
+    let waiting = AmmPool::new(AMM_STATUS_WAITING_TRADE, now() + 3_600, SOL_RESERVE * 2);
+    let open = AmmPool::new(AMM_STATUS_WAITING_TRADE, now() - 60, SOL_RESERVE);
+    let address = open.address;
+
+    let dex = amm_v4(&mint, &[disabled, waiting, open]);
+    assert_eq!(dex.get_pool(&mint).await.unwrap().pool, address);
+
+    let disabled = AmmPool::new(2, 0, SOL_RESERVE);
+    assert!(amm_v4(&mint, &[disabled]).get_pool(&mint).await.is_err());
+}
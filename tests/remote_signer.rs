@@ -0,0 +1,176 @@
+use solana_sdk::{
+    signature::{Keypair, Signature},
+    signer::Signer,
+};
+use solana_trading_sdk::common::signer::RemoteSigner;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+const MESSAGE: &[u8] = b"remote signer test message";
+
+// How the stand-in frames its HTTP response body
+#[derive(Clone, Copy)]
+enum Framing {
+    ContentLength,
+    Chunked,
+    Unframed,
+}
+
+// Serves one HTTP request, signing the posted message with `keypair`. Sends back the path and the JSON body it received.
+fn serve_http(keypair: Keypair, framing: Framing) -> (String, mpsc::Receiver<(String, serde_json::Value)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/sign", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim_end().is_empty() {
+                break;
+            }
+            if let Some(value) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let request = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+
+        let message = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, request["message"].as_str().unwrap()).unwrap();
+        let response = serde_json::json!({ "signature": keypair.sign_message(&message).to_string() }).to_string();
+        let (head, body) = response.split_at(response.len() / 2);
+        let response = match framing {
+            Framing::ContentLength => format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", response.len(), response),
+            Framing::Chunked => format!(
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n{:x};ext=1\r\n{}\r\n0\r\n\r\n",
+                head.len(),
+                head,
+                body.len(),
+                body
+            ),
+            Framing::Unframed => format!("HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{}", response),
+        };
+        let mut stream = reader.into_inner();
+        stream.write_all(response.as_bytes()).unwrap();
+
+        let path = request_line.split_whitespace().nth(1).unwrap().to_string();
+        sender.send((path, request)).unwrap();
+    });
+
+    (url, receiver)
+}
+
+fn assert_request(keypair: &Keypair, receiver: mpsc::Receiver<(String, serde_json::Value)>) {
+    let (path, request) = receiver.recv().unwrap();
+    assert_eq!(path, "/sign");
+    assert_eq!(request["pubkey"], keypair.pubkey().to_string());
+    assert_eq!(request["message"], base64::Engine::encode(&base64::engine::general_purpose::STANDARD, MESSAGE));
+}
+
+#[test]
+fn http_content_length() {
+    let keypair = Keypair::new();
+    let expected = keypair.sign_message(MESSAGE);
+    let pubkey = keypair.pubkey();
+    let (url, receiver) = serve_http(keypair.insecure_clone(), Framing::ContentLength);
+
+    let signer = RemoteSigner::http(pubkey, &url).unwrap();
+    assert_eq!(signer.try_sign_message(MESSAGE).unwrap(), expected);
+    assert_request(&keypair, receiver);
+}
+
+#[test]
+fn http_chunked() {
+    let keypair = Keypair::new();
+    let expected = keypair.sign_message(MESSAGE);
+    let (url, receiver) = serve_http(keypair.insecure_clone(), Framing::Chunked);
+
+    let signer = RemoteSigner::http(keypair.pubkey(), &url).unwrap();
+    assert_eq!(signer.try_sign_message(MESSAGE).unwrap(), expected);
+    assert_request(&keypair, receiver);
+}
+
+#[test]
+fn http_without_length_is_rejected() {
+    let keypair = Keypair::new();
+    let (url, _receiver) = serve_http(keypair.insecure_clone(), Framing::Unframed);
+
+    let signer = RemoteSigner::http(keypair.pubkey(), &url).unwrap();
+    let error = signer.try_sign_message(MESSAGE).unwrap_err();
+    assert!(error.to_string().contains("neither Content-Length nor chunked"), "{}", error);
+}
+
+#[test]
+fn http_rejects_signature_of_another_key() {
+    let (url, _receiver) = serve_http(Keypair::new(), Framing::ContentLength);
+
+    let signer = RemoteSigner::http(Keypair::new().pubkey(), &url).unwrap();
+    assert!(signer.try_sign_message(MESSAGE).is_err());
+}
+
+// A host that never answers the connect gives up after the signer timeout instead of the OS one
+#[test]
+fn http_connect_times_out() {
+    let mut signer = RemoteSigner::http(Keypair::new().pubkey(), "http://10.255.255.1:80/sign").unwrap();
+    signer.timeout = Duration::from_millis(200);
+
+    let started = Instant::now();
+    assert!(signer.try_sign_message(MESSAGE).is_err());
+    assert!(started.elapsed() < Duration::from_secs(5), "{:?}", started.elapsed());
+}
+
+// Signing from async code goes through the blocking pool, or hands off the worker, instead of stalling the runtime
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn http_from_async() {
+    let keypair = Keypair::new();
+    let expected = keypair.sign_message(MESSAGE);
+    let (url, receiver) = serve_http(keypair.insecure_clone(), Framing::Chunked);
+    let signer = RemoteSigner::http(keypair.pubkey(), &url).unwrap();
+    assert_eq!(signer.sign_message_async(MESSAGE).await.unwrap(), expected);
+    assert_request(&keypair, receiver);
+
+    let (url, receiver) = serve_http(keypair.insecure_clone(), Framing::ContentLength);
+    let signer = RemoteSigner::http(keypair.pubkey(), &url).unwrap();
+    let signature: Signature = signer.try_sign_message(MESSAGE).unwrap();
+    assert_eq!(signature, expected);
+    assert_request(&keypair, receiver);
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_socket() {
+    use std::os::unix::net::UnixListener;
+
+    let keypair = Keypair::new();
+    let expected = keypair.sign_message(MESSAGE);
+    let path = std::env::temp_dir().join(format!("remote-signer-{}.sock", keypair.pubkey()));
+    let listener = UnixListener::bind(&path).unwrap();
+    let server_keypair = keypair.insecure_clone();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let request = serde_json::from_str::<serde_json::Value>(&line).unwrap();
+        let message = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, request["message"].as_str().unwrap()).unwrap();
+        let response = serde_json::json!({ "signature": server_keypair.sign_message(&message).to_string() });
+        writeln!(reader.into_inner(), "{}", response).unwrap();
+        request
+    });
+
+    let signer = RemoteSigner::unix(keypair.pubkey(), &path);
+    assert_eq!(signer.try_sign_message(MESSAGE).unwrap(), expected);
+    assert_eq!(server.join().unwrap()["pubkey"], keypair.pubkey().to_string());
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,50 @@
+use solana_sdk::pubkey::Pubkey;
+use solana_trading_sdk::common::risk_policy::{RiskGuard, RiskPolicy};
+
+fn guard(max_sol_per_window: u64) -> RiskGuard {
+    RiskGuard::new(RiskPolicy {
+        max_sol_per_window: Some(max_sol_per_window),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn settle_keeps_spending_of_broadcast_trades() {
+    let guard = guard(100);
+    let wallet = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    let reservation = guard.check_trade(&wallet, &mint, 60).unwrap();
+    guard.settle(reservation, anyhow::Ok(())).unwrap();
+
+    assert_eq!(guard.get_spent(&wallet), 60);
+    assert!(guard.check_trade(&wallet, &mint, 60).is_err());
+}
+
+#[test]
+fn settle_releases_spending_of_failed_trades() {
+    let guard = guard(100);
+    let wallet = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    let kept = guard.check_trade(&wallet, &mint, 30).unwrap();
+    guard.settle(kept, anyhow::Ok(())).unwrap();
+    let failed = guard.check_trades(&mint, &[(wallet, 60), (other, 10)]).unwrap();
+    assert_eq!(guard.get_spent(&wallet), 90);
+
+    assert!(guard.settle(failed, anyhow::Result::<()>::Err(anyhow::anyhow!("broadcast failed"))).is_err());
+    assert_eq!(guard.get_spent(&wallet), 30);
+    assert_eq!(guard.get_spent(&other), 0);
+    guard.check_trade(&wallet, &mint, 70).unwrap();
+}
+
+#[test]
+fn rejected_batch_reserves_nothing() {
+    let guard = guard(100);
+    let wallet = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    assert!(guard.check_trades(&mint, &[(wallet, 60), (wallet, 60)]).is_err());
+    assert_eq!(guard.get_spent(&wallet), 0);
+}
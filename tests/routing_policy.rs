@@ -0,0 +1,45 @@
+mod common;
+
+use common::StubSWQoS;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_trading_sdk::swqos::{
+    nextblock::NextBlockClient,
+    regional::RegionalSWQoS,
+    scoreboard::{ProviderScoreboard, RoutingPolicy},
+    SWQoSTrait,
+};
+use std::sync::Arc;
+
+fn regional(region: &str) -> Arc<dyn SWQoSTrait> {
+    Arc::new(RegionalSWQoS::new(Arc::new(StubSWQoS::default()), region))
+}
+
+// Only the configured region is matched, providers without one are never picked
+#[test]
+fn by_region_matches_configured_region() {
+    let swqos = vec![
+        regional("ny"),
+        Arc::new(StubSWQoS::default()) as Arc<dyn SWQoSTrait>,
+        regional("Frankfurt"),
+        regional("ams"),
+    ];
+    let scoreboard = ProviderScoreboard::new(swqos.len());
+
+    let policy = RoutingPolicy::ByRegion(vec!["frankfurt".to_string(), "ny".to_string()]);
+    assert_eq!(scoreboard.select(&policy, &swqos), vec![0, 2]);
+    assert!(scoreboard.select(&RoutingPolicy::ByRegion(vec!["fra".to_string()]), &swqos).is_empty());
+
+    assert_eq!(swqos[0].get_region(), Some("ny"));
+    assert_eq!(swqos[1].get_region(), None);
+    assert_eq!(swqos[0].get_name(), "stub");
+}
+
+// Kept for existing callers until it is removed
+#[test]
+#[allow(deprecated)]
+fn regional_provider_still_reports_its_endpoint() {
+    let rpc = Arc::new(RpcClient::new("http://127.0.0.1:8899".to_string()));
+    let nextblock = Arc::new(NextBlockClient::new(rpc, "https://fra.nextblock.io".to_string(), "token".to_string()));
+    assert_eq!(RegionalSWQoS::new(nextblock, "frankfurt").get_endpoint(), "https://fra.nextblock.io");
+    assert_eq!(StubSWQoS::default().get_endpoint(), "");
+}
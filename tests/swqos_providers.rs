@@ -0,0 +1,404 @@
+mod common;
+
+use base64::{engine::general_purpose, Engine};
+use common::mock_rpc;
+use solana_sdk::{hash::Hash, signature::Keypair, transaction::VersionedTransaction};
+use solana_trading_sdk::{
+    instruction::builder::build_transaction,
+    swqos::{
+        blox::BloxSubmitProtection,
+        jito::JitoClient,
+        spawn_keep_alive,
+        swqos_rpc::{SWQoSClientTrait, SWQoSHttpClient, SWQoSPing, SWQoSRequest, SWQOS_LATENCY_WINDOW},
+        BundleStatus, SWQoSTrait, SWQoSType,
+    },
+};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+const AUTH_TOKEN: &str = "secret";
+
+struct Request {
+    target: String,
+    headers: HashMap<String, String>,
+    body: serde_json::Value,
+}
+
+// What the stand-in answers to one request, after waiting `delay`
+struct Reply {
+    status: u16,
+    body: serde_json::Value,
+    delay: Duration,
+}
+
+impl Reply {
+    fn result(result: serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            body: serde_json::json!({ "jsonrpc": "2.0", "result": result, "id": 1 }),
+            delay: Duration::ZERO,
+        }
+    }
+}
+
+// Stand-in provider answering one JSON-RPC request, sends back what it received
+fn serve_provider() -> (String, mpsc::Receiver<Request>) {
+    serve_replies(vec![Reply::result(serde_json::json!("ok"))])
+}
+
+// Stand-in provider answering one request per reply in order, sends back what it received
+fn serve_replies(replies: Vec<Reply>) -> (String, mpsc::Receiver<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for reply in replies {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut headers = HashMap::new();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                match header.trim_end().split_once(':') {
+                    Some((name, value)) => headers.insert(name.to_ascii_lowercase(), value.trim().to_string()),
+                    None => break,
+                };
+            }
+            let content_length = headers.get("content-length").map_or(0, |length| length.parse().unwrap());
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            thread::sleep(reply.delay);
+            let response = reply.body.to_string();
+            let response = format!(
+                "HTTP/1.1 {} Stand-in\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                reply.status,
+                response.len(),
+                response
+            );
+            reader.into_inner().write_all(response.as_bytes()).unwrap();
+            // Pings are sent without a body
+            let request = Request {
+                target: request_line.split_whitespace().nth(1).unwrap().to_string(),
+                headers,
+                body: serde_json::from_slice(&body).unwrap_or_default(),
+            };
+            if sender.send(request).is_err() {
+                break;
+            }
+        }
+    });
+
+    (url, receiver)
+}
+
+fn transaction() -> VersionedTransaction {
+    build_transaction(&Keypair::new(), vec![], Hash::new_unique(), None, None, None).unwrap()
+}
+
+fn encoded(transaction: &VersionedTransaction) -> String {
+    general_purpose::STANDARD.encode(bincode::serialize(transaction).unwrap())
+}
+
+// Sends one transaction through the provider built from `swqos` pointed at a stand-in
+async fn send(swqos: impl FnOnce(String) -> SWQoSType) -> (VersionedTransaction, Request) {
+    let (url, requests) = serve_provider();
+    let provider = swqos(url).instantiate(mock_rpc(vec![])).unwrap();
+    let transaction = transaction();
+    provider.send_transaction(transaction.clone()).await.unwrap();
+    (transaction, requests.recv().unwrap())
+}
+
+// JSON-RPC `sendTransaction` of one base64 transaction, returning its options
+fn assert_send_transaction(request: &Request, transaction: &VersionedTransaction) -> serde_json::Value {
+    assert_eq!(request.body["method"], "sendTransaction");
+    assert_eq!(request.body["params"][0], encoded(transaction));
+    assert_eq!(request.body["params"][1]["encoding"], "base64");
+    request.body["params"][1].clone()
+}
+
+#[tokio::test]
+async fn helius_sender() {
+    let (transaction, request) = send(SWQoSType::HeliusSender).await;
+    assert_eq!(request.target, "/fast");
+    assert!(!request.headers.contains_key("authorization"));
+    let options = assert_send_transaction(&request, &transaction);
+    assert_eq!(options["skipPreflight"], true);
+    assert_eq!(options["maxRetries"], 0);
+}
+
+#[tokio::test]
+async fn astralane() {
+    let (transaction, request) = send(|url| SWQoSType::Astralane(url, AUTH_TOKEN.to_string())).await;
+    assert_eq!(request.target, format!("/iris?api-key={}", AUTH_TOKEN));
+    assert_send_transaction(&request, &transaction);
+}
+
+#[tokio::test]
+async fn blox_leader_aware() {
+    let (transaction, request) = send(|url| SWQoSType::BloxLeaderAware(url, AUTH_TOKEN.to_string(), BloxSubmitProtection::High)).await;
+    assert_eq!(request.target, "/api/v2/submit");
+    assert_eq!(request.headers["authorization"], AUTH_TOKEN);
+    assert_eq!(request.body["transaction"]["content"], encoded(&transaction));
+    assert_eq!(request.body["submitProtection"], "SP_HIGH");
+    assert_eq!(request.body["useStakedRPCs"], true);
+}
+
+#[tokio::test]
+async fn node1() {
+    let (transaction, request) = send(|url| SWQoSType::Node1(url, AUTH_TOKEN.to_string())).await;
+    assert_eq!(request.target, "/");
+    assert_eq!(request.headers["api-key"], AUTH_TOKEN);
+    assert_send_transaction(&request, &transaction);
+}
+
+#[tokio::test]
+async fn flashblock() {
+    let (transaction, request) = send(|url| SWQoSType::Flashblock(url, AUTH_TOKEN.to_string())).await;
+    assert_eq!(request.target, "/api/v2/submit-batch");
+    assert_eq!(request.headers["authorization"], AUTH_TOKEN);
+    assert_eq!(request.body, serde_json::json!({ "transactions": [encoded(&transaction)] }));
+}
+
+#[tokio::test]
+async fn stellium() {
+    let (transaction, request) = send(|url| SWQoSType::Stellium(url, AUTH_TOKEN.to_string())).await;
+    assert_eq!(request.target, format!("/{}", AUTH_TOKEN));
+    assert!(!request.headers.contains_key("authorization"));
+    assert_send_transaction(&request, &transaction);
+}
+
+// Posts one JSON-RPC request through `client`, recording its latency
+async fn post(client: &SWQoSHttpClient, url: &str) -> anyhow::Result<serde_json::Value> {
+    let request = SWQoSRequest {
+        name: "stand-in".to_string(),
+        url: url.to_string(),
+        auth_header: None,
+        transactions: vec![],
+    };
+    client
+        .swqos_json_post_with_response(request, serde_json::json!({ "method": "getHealth" }))
+        .await
+}
+
+#[tokio::test]
+async fn latency_window_drops_the_oldest_sample() {
+    let slow = Duration::from_millis(300);
+    let mut replies = vec![Reply {
+        delay: slow,
+        ..Reply::result(serde_json::json!("ok"))
+    }];
+    replies.extend((0..SWQOS_LATENCY_WINDOW).map(|_| Reply::result(serde_json::json!("ok"))));
+    let (url, _requests) = serve_replies(replies);
+    let client = SWQoSHttpClient::new();
+
+    for _ in 0..SWQOS_LATENCY_WINDOW {
+        post(&client, &url).await.unwrap();
+    }
+    let latency = client.get_latency();
+    assert_eq!(latency.requests, SWQOS_LATENCY_WINDOW as u64);
+    assert_eq!(latency.errors, 0);
+    assert!(latency.max.unwrap() >= slow);
+    assert!(latency.average.unwrap() >= slow / SWQOS_LATENCY_WINDOW as u32);
+    assert!(latency.average <= latency.max);
+    assert!(latency.last.unwrap() < slow);
+
+    // The slow sample leaves the window
+    post(&client, &url).await.unwrap();
+    let latency = client.get_latency();
+    assert_eq!(latency.requests, SWQOS_LATENCY_WINDOW as u64 + 1);
+    assert!(latency.max.unwrap() < slow);
+    assert!(latency.average <= latency.max);
+}
+
+#[tokio::test]
+async fn failed_requests_are_counted_without_a_sample() {
+    let (url, _requests) = serve_replies(vec![
+        Reply::result(serde_json::json!("ok")),
+        Reply {
+            status: 500,
+            ..Reply::result(serde_json::json!("ok"))
+        },
+        Reply {
+            body: serde_json::json!({ "jsonrpc": "2.0", "error": { "code": -32000, "message": "busy" }, "id": 1 }),
+            ..Reply::result(serde_json::json!("ok"))
+        },
+    ]);
+    let client = SWQoSHttpClient::new();
+
+    post(&client, &url).await.unwrap();
+    let sampled = client.get_latency();
+    assert!(post(&client, &url).await.is_err());
+    assert!(post(&client, &url).await.is_err());
+
+    let latency = client.get_latency();
+    assert_eq!(latency.requests, 3);
+    assert_eq!(latency.errors, 2);
+    assert_eq!(latency.last, sampled.last);
+    assert_eq!(latency.average, sampled.average);
+    assert_eq!(latency.max, sampled.max);
+}
+
+#[tokio::test]
+async fn ping_counts_any_response_as_alive() {
+    let (url, requests) = serve_replies(vec![
+        Reply {
+            status: 500,
+            ..Reply::result(serde_json::json!("ok"))
+        },
+        Reply::result(serde_json::json!("ok")),
+    ]);
+    let client = SWQoSHttpClient::new();
+
+    client
+        .swqos_ping(&SWQoSPing::Get(format!("{}/ping", url)), Some(("api-key".to_string(), AUTH_TOKEN.to_string())))
+        .await
+        .unwrap();
+    let request = requests.recv().unwrap();
+    assert_eq!(request.target, "/ping");
+    assert_eq!(request.headers["api-key"], AUTH_TOKEN);
+
+    client.swqos_ping(&SWQoSPing::JsonRpc(url, "getHealth".to_string()), None).await.unwrap();
+    assert_eq!(requests.recv().unwrap().body["method"], "getHealth");
+
+    let latency = client.get_latency();
+    assert!(latency.last_ping.is_some());
+    assert_eq!(latency.ping_errors, 0);
+    // Pings are not requests
+    assert_eq!(latency.requests, 0);
+}
+
+#[tokio::test]
+async fn unreachable_ping_is_counted() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let client = SWQoSHttpClient::new();
+
+    assert!(client.swqos_ping(&SWQoSPing::Get(url), None).await.is_err());
+    let latency = client.get_latency();
+    assert_eq!(latency.ping_errors, 1);
+    assert_eq!(latency.last_ping, None);
+}
+
+// Multi-threaded so the keep-alive task runs while the test waits on the stand-ins
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn keep_alive_pings_every_provider() {
+    let (helius_url, helius_requests) = serve_replies((0..2).map(|_| Reply::result(serde_json::json!("ok"))).collect());
+    let (jito_url, jito_requests) = serve_replies((0..2).map(|_| Reply::result(serde_json::json!([]))).collect());
+    let swqos = vec![
+        SWQoSType::HeliusSender(helius_url).instantiate(mock_rpc(vec![])).unwrap(),
+        SWQoSType::Jito(jito_url).instantiate(mock_rpc(vec![])).unwrap(),
+    ];
+    let providers = Arc::new(swqos.clone());
+
+    let keep_alive = spawn_keep_alive(providers, Duration::from_millis(50));
+    // Right away, then again after the interval
+    for _ in 0..2 {
+        assert_eq!(helius_requests.recv_timeout(Duration::from_secs(5)).unwrap().target, "/ping");
+        let request = jito_requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(request.target, "/api/v1/bundles");
+        assert_eq!(request.body["method"], "getTipAccounts");
+    }
+    keep_alive.abort();
+
+    for provider in swqos.iter() {
+        assert!(provider.get_latency().unwrap().last_ping.is_some(), "{}", provider.get_name());
+    }
+}
+
+#[tokio::test]
+async fn jito_send_bundle() {
+    let (url, requests) = serve_replies(vec![Reply::result(serde_json::json!("bundle"))]);
+    let jito = JitoClient::new(mock_rpc(vec![]), url);
+    let transactions = vec![transaction(), transaction()];
+
+    assert_eq!(jito.send_bundle(transactions.clone()).await.unwrap(), "bundle");
+    let request = requests.recv().unwrap();
+    assert_eq!(request.target, "/api/v1/bundles");
+    assert_eq!(request.body["method"], "sendBundle");
+    assert_eq!(
+        request.body["params"][0],
+        serde_json::json!(transactions.iter().map(encoded).collect::<Vec<_>>())
+    );
+    assert_eq!(request.body["params"][1]["encoding"], "base64");
+}
+
+#[tokio::test]
+async fn jito_inflight_bundle_statuses() {
+    let (url, requests) = serve_replies(vec![Reply::result(serde_json::json!({
+        "context": { "slot": 10 },
+        "value": [
+            { "bundle_id": "pending", "status": "Pending", "landed_slot": null },
+            { "bundle_id": "failed", "status": "Failed", "landed_slot": null },
+            { "bundle_id": "landed", "status": "Landed", "landed_slot": 9 },
+        ],
+    }))]);
+    let jito = JitoClient::new(mock_rpc(vec![]), url);
+    let bundle_ids = ["landed", "pending", "failed"].map(String::from);
+
+    let statuses = jito.get_bundle_statuses(&bundle_ids).await.unwrap();
+    assert_eq!(statuses[0], BundleStatus::Landed { slot: 9 });
+    assert_eq!(statuses[1], BundleStatus::Pending);
+    assert!(matches!(statuses[2], BundleStatus::Failed(_)));
+    let request = requests.recv().unwrap();
+    assert_eq!(request.body["method"], "getInflightBundleStatuses");
+    assert_eq!(request.body["params"], serde_json::json!([bundle_ids]));
+}
+
+// Bundles the inflight statuses report as `Invalid` or leave out are looked up in the landed statuses
+#[tokio::test]
+async fn jito_bundle_statuses_fall_back_to_landed_statuses() {
+    let (url, requests) = serve_replies(vec![
+        Reply::result(serde_json::json!({
+            "context": { "slot": 10 },
+            "value": [{ "bundle_id": "landed", "status": "Invalid", "landed_slot": null }],
+        })),
+        Reply::result(serde_json::json!({
+            "context": { "slot": 10 },
+            "value": [{ "bundle_id": "landed", "transactions": [], "slot": 8, "confirmation_status": "confirmed", "err": { "Ok": null } }],
+        })),
+        Reply::result(serde_json::json!({
+            "context": { "slot": 10 },
+            "value": [{ "bundle_id": "failed", "transactions": [], "slot": 8, "confirmation_status": "confirmed", "err": { "Err": "BundleFailed" } }],
+        })),
+    ]);
+    let jito = JitoClient::new(mock_rpc(vec![]), url);
+
+    let statuses = jito.get_bundle_statuses(&["landed".to_string(), "failed".to_string()]).await.unwrap();
+    assert_eq!(statuses[0], BundleStatus::Landed { slot: 8 });
+    assert!(
+        matches!(&statuses[1], BundleStatus::Failed(error) if error.contains("BundleFailed")),
+        "{:?}",
+        statuses[1]
+    );
+
+    assert_eq!(requests.recv().unwrap().body["method"], "getInflightBundleStatuses");
+    for bundle_id in ["landed", "failed"] {
+        let request = requests.recv().unwrap();
+        assert_eq!(request.body["method"], "getBundleStatuses");
+        assert_eq!(request.body["params"], serde_json::json!([[bundle_id]]));
+    }
+}
+
+// Right after `sendBundle` the block engine knows nothing about the bundle yet
+#[tokio::test]
+async fn jito_unknown_bundle_is_pending() {
+    let (url, _requests) = serve_replies(vec![
+        Reply::result(serde_json::json!({ "context": { "slot": 10 }, "value": [] })),
+        Reply::result(serde_json::json!({ "context": { "slot": 10 }, "value": [null] })),
+    ]);
+    let jito = JitoClient::new(mock_rpc(vec![]), url);
+
+    let statuses = jito.get_bundle_statuses(&["unknown".to_string()]).await.unwrap();
+    assert_eq!(statuses, vec![BundleStatus::Pending]);
+}
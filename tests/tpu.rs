@@ -0,0 +1,60 @@
+mod common;
+
+use common::mock_rpc;
+use quinn::{crypto::rustls::QuicServerConfig, Endpoint, ServerConfig};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Keypair};
+use solana_tls_utils::{new_dummy_x509_certificate, tls_server_config_builder};
+use solana_trading_sdk::{instruction::builder::build_transaction, swqos::tpu::TpuClient, swqos::SWQoSTrait};
+use std::{net::SocketAddr, sync::Arc};
+
+const ALPN_TPU_PROTOCOL_ID: &[u8] = b"solana-tpu";
+
+// QUIC listener standing in for a leader's TPU, yielding the payload of every unidirectional stream it accepts
+fn serve_tpu() -> (SocketAddr, tokio::sync::mpsc::UnboundedReceiver<Vec<u8>>) {
+    let (cert, key) = new_dummy_x509_certificate(&Keypair::new());
+    let mut tls = tls_server_config_builder().with_single_cert(vec![cert], key).unwrap();
+    tls.alpn_protocols = vec![ALPN_TPU_PROTOCOL_ID.to_vec()];
+    let config = ServerConfig::with_crypto(Arc::new(QuicServerConfig::try_from(tls).unwrap()));
+    let endpoint = Endpoint::server(config, "127.0.0.1:0".parse().unwrap()).unwrap();
+    let address = endpoint.local_addr().unwrap();
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Some(incoming) = endpoint.accept().await {
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                let connection = incoming.await.unwrap();
+                while let Ok(mut stream) = connection.accept_uni().await {
+                    let _ = sender.send(stream.read_to_end(usize::MAX).await.unwrap());
+                }
+            });
+        }
+    });
+
+    (address, receiver)
+}
+
+// The next leader is resolved to the listener, which receives the serialized transaction
+#[tokio::test]
+async fn sends_to_leader_tpu() {
+    let (address, mut received) = serve_tpu();
+    let leader = Pubkey::new_unique();
+    let rpc = mock_rpc(vec![
+        (RpcRequest::GetSlot, serde_json::json!(100)),
+        (RpcRequest::GetSlotLeaders, serde_json::json!(vec![leader.to_string(); 100])),
+        (
+            RpcRequest::GetClusterNodes,
+            serde_json::json!([
+                { "pubkey": Pubkey::new_unique().to_string(), "tpuQuic": "127.0.0.1:1" },
+                { "pubkey": leader.to_string(), "tpuQuic": address.to_string() },
+            ]),
+        ),
+    ]);
+    let tpu = TpuClient::new(rpc, 1, None);
+    assert_eq!(tpu.get_leader_addresses().await.unwrap(), vec![address]);
+
+    let transaction = build_transaction(&Keypair::new(), vec![], Hash::new_unique(), None, None, None).unwrap();
+    tpu.send_transaction(transaction.clone()).await.unwrap();
+    assert_eq!(received.recv().await.unwrap(), bincode::serialize(&transaction).unwrap());
+}
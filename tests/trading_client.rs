@@ -0,0 +1,147 @@
+mod common;
+
+use common::{mock_rpc, pool_info, StubDex, StubSWQoS};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair};
+use solana_trading_sdk::{
+    common::{
+        mint_info::MintInfo,
+        trading_client::{NoLivePool, TradingClient, TradingConfig},
+        trading_endpoint::TradingEndpoint,
+    },
+    dex::{
+        dex_traits::DexTrait,
+        types::{DexType, PoolInfo},
+    },
+    swqos::SWQoSTrait,
+};
+use std::{collections::HashMap, sync::Arc};
+
+fn client(venues: Vec<(DexType, Option<PoolInfo>, Option<Pubkey>)>) -> TradingClient {
+    client_with_fees(
+        venues
+            .into_iter()
+            .map(|(dex_type, pool_info, quote_mint)| (dex_type, pool_info, quote_mint, 0))
+            .collect(),
+    )
+}
+
+fn client_with_fees(venues: Vec<(DexType, Option<PoolInfo>, Option<Pubkey>, u64)>) -> TradingClient {
+    let stub = Arc::new(StubSWQoS::default());
+    let endpoint = Arc::new(TradingEndpoint::new(mock_rpc(vec![]), vec![stub as Arc<dyn SWQoSTrait>]));
+    let dexs = venues
+        .into_iter()
+        .map(|(dex_type, pool_info, quote_mint, buy_fee_basis_points)| {
+            let mut dex = StubDex::new(endpoint.clone(), pool_info);
+            if let Some(quote_mint) = quote_mint {
+                dex.quote_mint = quote_mint;
+            }
+            dex.buy_fee_basis_points = buy_fee_basis_points;
+            (dex_type, Arc::new(dex) as Arc<dyn DexTrait>)
+        })
+        .collect::<HashMap<_, _>>();
+
+    TradingClient { endpoint, dexs }
+}
+
+#[tokio::test]
+async fn resolve_prefers_live_bonding_curve() {
+    let curve = pool_info(40_000_000_000, 1_000_000_000_000_000);
+    let client = client(vec![
+        (DexType::Pumpfun, Some(curve.clone()), None),
+        (DexType::PumpSwap, Some(pool_info(500_000_000_000, 100_000_000_000_000)), None),
+    ]);
+
+    let route = client.resolve(&Pubkey::new_unique()).await.unwrap();
+    assert_eq!((route.dex_type, route.pool_info.pool), (DexType::Pumpfun, curve.pool));
+}
+
+#[tokio::test]
+async fn resolve_skips_completed_curve() {
+    let mut curve = pool_info(85_000_000_000, 200_000_000_000_000);
+    curve.complete = true;
+    let pool = pool_info(1_000_000_000, 100_000_000_000_000);
+    let client = client(vec![(DexType::Pumpfun, Some(curve), None), (DexType::PumpSwap, Some(pool.clone()), None)]);
+
+    let route = client.resolve(&Pubkey::new_unique()).await.unwrap();
+    assert_eq!((route.dex_type, route.pool_info.pool), (DexType::PumpSwap, pool.pool));
+}
+
+#[tokio::test]
+async fn resolve_only_compares_sol_quoted_pools() {
+    let pool = pool_info(1_000_000_000, 100_000_000_000_000);
+    let mixed = client(vec![
+        // Deeper in raw units, but quoted in another mint
+        (
+            DexType::RaydiumCpmm,
+            Some(pool_info(900_000_000_000_000, 100_000_000_000_000)),
+            Some(Pubkey::new_unique()),
+        ),
+        (DexType::PumpSwap, Some(pool.clone()), None),
+        (DexType::RaydiumAmmV4, None, None),
+    ]);
+
+    let route = mixed.resolve(&Pubkey::new_unique()).await.unwrap();
+    assert_eq!((route.dex_type, route.pool_info.pool), (DexType::PumpSwap, pool.pool));
+
+    let quoted_only = client(vec![(DexType::RaydiumCpmm, Some(pool_info(1, 1)), Some(Pubkey::new_unique()))]);
+    assert!(quoted_only.resolve(&Pubkey::new_unique()).await.is_err());
+}
+
+// Equal pools, one charging 1% on top: the split leans to the cheaper venue and the whole spend, fees included, stays within the budget
+#[tokio::test]
+async fn split_buy_weighs_venue_fees() {
+    let mint = Pubkey::new_unique();
+    let cheap = pool_info(100_000_000_000, 100_000_000_000_000);
+    let client = client_with_fees(vec![
+        (DexType::PumpSwap, Some(cheap.clone()), None, 0),
+        (DexType::Pumpfun, Some(pool_info(100_000_000_000, 100_000_000_000_000)), None, 100),
+    ]);
+    client.endpoint.set_mint_info(&mint, MintInfo::new(spl_token::ID));
+
+    let sol_amount = 20_000_000_000;
+    let trade = client.split_buy(&Keypair::new(), &mint, sol_amount, 0, None, Some(100_000)).await.unwrap();
+    let cheap_leg = trade.legs.iter().find(|leg| leg.pool == cheap.pool).unwrap();
+    let fee_leg = trade.legs.iter().find(|leg| leg.pool != cheap.pool).unwrap();
+    assert!(cheap_leg.sol_amount > fee_leg.sol_amount);
+    assert!(cheap_leg.token_amount > fee_leg.token_amount);
+
+    let spent = cheap_leg.sol_amount + fee_leg.sol_amount * 10_100 / 10_000;
+    assert!(spent <= sol_amount && spent + 2 >= sol_amount, "{}", spent);
+}
+
+#[test]
+fn aggregator_is_opt_in() {
+    let config = TradingConfig {
+        rpc_url: "http://127.0.0.1:8899".to_string(),
+        swqos: vec![],
+    };
+    let client = TradingClient::new(config).unwrap();
+    assert!(!client.dexs.contains_key(&DexType::Aggregator));
+    assert!(!DexType::all().contains(&DexType::Aggregator));
+
+    let client = client.with_aggregator(Some("http://127.0.0.1:8080/swap/v1"), None);
+    assert!(client.dexs.contains_key(&DexType::Aggregator));
+}
+
+#[tokio::test]
+async fn buy_falls_back_to_aggregator_without_live_pool() {
+    let mint = Pubkey::new_unique();
+    let mut curve = pool_info(85_000_000_000, 200_000_000_000_000);
+    curve.complete = true;
+
+    let native = client(vec![(DexType::Pumpfun, Some(curve.clone()), None)]);
+    native.endpoint.set_mint_info(&mint, MintInfo::new(spl_token::ID));
+    let error = native.buy(&Keypair::new(), &mint, 1_000_000_000, 100, None, Some(100_000)).await.unwrap_err();
+    assert_eq!(error.downcast_ref::<NoLivePool>(), Some(&NoLivePool(mint)));
+
+    let with_aggregator = client(vec![
+        (DexType::Pumpfun, Some(curve), None),
+        (DexType::Aggregator, Some(pool_info(1_000_000_000, 100_000_000_000_000)), None),
+    ]);
+    with_aggregator.endpoint.set_mint_info(&mint, MintInfo::new(spl_token::ID));
+    let trade = with_aggregator
+        .buy(&Keypair::new(), &mint, 1_000_000_000, 100, None, Some(100_000))
+        .await
+        .unwrap();
+    assert_eq!((trade.dex_type, trade.pool), (DexType::Aggregator, Pubkey::default()));
+}
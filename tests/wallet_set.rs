@@ -0,0 +1,80 @@
+use solana_derivation_path::DerivationPath;
+use solana_keypair::seed_derivable::keypair_from_seed_and_derivation_path;
+use solana_sdk::signer::Signer;
+use solana_trading_sdk::common::wallet_set::{Allocation, WalletSet};
+
+const SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+#[test]
+fn allocation_each() {
+    assert_eq!(Allocation::Each(5).amounts(3).unwrap(), vec![5, 5, 5]);
+}
+
+#[test]
+fn allocation_split() {
+    assert_eq!(Allocation::Split(10).amounts(3).unwrap(), vec![4, 3, 3]);
+    assert_eq!(Allocation::Split(10).amounts(0).unwrap(), Vec::<u64>::new());
+}
+
+#[test]
+fn allocation_weighted() {
+    assert_eq!(Allocation::Weighted(100, vec![1, 1, 2]).amounts(3).unwrap(), vec![25, 25, 50]);
+    assert!(Allocation::Weighted(100, vec![1, 1]).amounts(3).is_err());
+    assert!(Allocation::Weighted(100, vec![0, 0, 0]).amounts(3).is_err());
+}
+
+#[test]
+fn allocation_random() {
+    let amounts = Allocation::Random(10, 20).amounts(50).unwrap();
+    assert_eq!(amounts.len(), 50);
+    assert!(amounts.iter().all(|amount| (10..=20).contains(amount)));
+    assert_eq!(Allocation::Random(7, 7).amounts(2).unwrap(), vec![7, 7]);
+    assert!(Allocation::Random(20, 10).amounts(1).is_err());
+}
+
+#[test]
+fn allocation_amounts() {
+    assert_eq!(Allocation::Amounts(vec![1, 2, 3]).amounts(3).unwrap(), vec![1, 2, 3]);
+    assert!(Allocation::Amounts(vec![1, 2]).amounts(3).is_err());
+}
+
+#[test]
+fn batch_buy_params_skip_zero_amounts() {
+    let wallets = WalletSet::from_seed_phrase(SEED_PHRASE, "", 3).unwrap();
+    let params = wallets.batch_buy_params(&Allocation::Amounts(vec![1, 0, 3])).unwrap();
+    let pubkeys = wallets.pubkeys();
+
+    assert_eq!(
+        params.iter().map(|param| (param.payer.pubkey(), param.sol_amount)).collect::<Vec<_>>(),
+        vec![(pubkeys[0], 1), (pubkeys[2], 3)]
+    );
+}
+
+#[test]
+fn from_seed_derives_bip44_paths() {
+    let seed = solana_seed_phrase::generate_seed_from_seed_phrase_and_passphrase(SEED_PHRASE, "");
+    let wallets = WalletSet::from_seed(&seed, 3).unwrap();
+
+    for (index, pubkey) in wallets.pubkeys().iter().enumerate() {
+        let path = DerivationPath::from_absolute_path_str(&format!("m/44'/501'/{}'/0'", index)).unwrap();
+        let keypair = keypair_from_seed_and_derivation_path(&seed, Some(path)).unwrap();
+        assert_eq!(*pubkey, keypair.pubkey());
+    }
+}
+
+#[test]
+fn from_seed_phrase_matches_from_seed() {
+    let seed = solana_seed_phrase::generate_seed_from_seed_phrase_and_passphrase(SEED_PHRASE, "passphrase");
+    let from_phrase = WalletSet::from_seed_phrase(SEED_PHRASE, "passphrase", 2).unwrap();
+    let from_seed = WalletSet::from_seed(&seed, 2).unwrap();
+
+    assert_eq!(from_phrase.pubkeys(), from_seed.pubkeys());
+    assert_ne!(from_phrase.pubkeys()[0], from_phrase.pubkeys()[1]);
+    assert_ne!(from_phrase.pubkeys(), WalletSet::from_seed_phrase(SEED_PHRASE, "", 2).unwrap().pubkeys());
+}
+
+#[test]
+fn from_seed_phrase_known_address() {
+    let wallets = WalletSet::from_seed_phrase(SEED_PHRASE, "", 1).unwrap();
+    assert_eq!(wallets.pubkeys()[0].to_string(), "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk");
+}